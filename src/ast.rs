@@ -1,28 +1,79 @@
+use std::collections::HashMap;
+
 use num_bigint::BigInt;
+use num_rational::BigRational;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Expr {
     Ident(String),
     Int(BigInt),
+    /// A decimal literal: `0.25`, `0.1(6)`, `1.5e-3`
+    Decimal(BigRational),
+    /// A residue class literal `value (mod modulus)`, as written
+    Residue(BigInt, BigInt),
+    /// `True` / `False`
+    Bool(bool),
     /// Function application: `f(a, b, ...)`
     App(String, Vec<Expr>),
     BinOp(Op, Box<Expr>, Box<Expr>),
+    /// Application of a user-declared infix operator: `a ∘ b`
+    Infix(InfixOp, Box<Expr>, Box<Expr>),
     UnaryOp(UnaryOp, Box<Expr>),
     /// `∀ vars ∈ domain. body`
     Forall(Vec<String>, Box<Expr>, Box<Expr>),
     /// `{var ∈ domain | pred}` — predicate-subset comprehension
     SetBuilder(String, Box<Expr>, Box<Expr>),
+    /// `let name [: ty] = value in body` — local definition
+    Let(String, Option<Box<Expr>>, Box<Expr>, Box<Expr>),
+    /// `if cond then a else b` — conditional (piecewise) expression
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// `∑ body for var from lo to hi`, or `∏ …` — finite sum or product
+    BigOp(BigOp, String, Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+/// The operator of a finite sum or product.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BigOp {
+    Sum,
+    Product,
+}
+
+impl BigOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BigOp::Sum => "∑",
+            BigOp::Product => "∏",
+        }
+    }
+
+    /// The ASCII spelling of the operator; the lexer accepts both.
+    pub fn ascii_symbol(self) -> &'static str {
+        match self {
+            BigOp::Sum => "sum",
+            BigOp::Product => "prod",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UnaryOp {
     Neg,
+    Not,
 }
 
 impl UnaryOp {
     pub fn symbol(self) -> &'static str {
         match self {
             UnaryOp::Neg => "-",
+            UnaryOp::Not => "¬",
+        }
+    }
+
+    /// The ASCII spelling of the operator; the lexer accepts both.
+    pub fn ascii_symbol(self) -> &'static str {
+        match self {
+            UnaryOp::Neg => "-",
+            UnaryOp::Not => "~",
         }
     }
 }
@@ -66,6 +117,16 @@ impl Op {
         matches!(self, Op::Pow | Op::Implies | Op::Arrow)
     }
 
+    pub fn assoc(self) -> Assoc {
+        match self {
+            Op::Pow | Op::Implies | Op::Arrow => Assoc::Right,
+            Op::Eq | Op::Ne | Op::Subset | Op::In | Op::Lt | Op::Gt | Op::Le | Op::Ge => {
+                Assoc::None
+            }
+            _ => Assoc::Left,
+        }
+    }
+
     pub fn symbol(self) -> &'static str {
         match self {
             Op::Arrow => "→",
@@ -87,6 +148,128 @@ impl Op {
             Op::Pow => "^",
         }
     }
+
+    /// The ASCII spelling of the operator; the lexer accepts both.
+    pub fn ascii_symbol(self) -> &'static str {
+        match self {
+            Op::Arrow => "->",
+            Op::Implies => "=>",
+            Op::Or => "\\/",
+            Op::And => "/\\",
+            Op::Ne => "!=",
+            Op::Subset => "subset",
+            Op::In => "in",
+            Op::Le => "<=",
+            Op::Ge => ">=",
+            Op::Mul => "*",
+            _ => self.symbol(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+    None,
+}
+
+impl Assoc {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Assoc::Left => "left",
+            Assoc::Right => "right",
+            Assoc::None => "none",
+        }
+    }
+}
+
+/// Monomial order for `prove … using <order>` (see `kernel::groebner`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonomialOrder {
+    Lex,
+    Grevlex,
+    /// Weighted by KBO symbol counts, with indeterminates ranked as KBO does.
+    Kbo,
+}
+
+impl MonomialOrder {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            MonomialOrder::Lex => "lex",
+            MonomialOrder::Grevlex => "grevlex",
+            MonomialOrder::Kbo => "kbo",
+        }
+    }
+}
+
+/// A user-declared infix operator. `prec` uses the same scale as `Op::prec`
+/// (higher binds tighter), so `infix 65 right ∘` sits between `·` and `^`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfixOp {
+    pub symbol: String,
+    pub prec: u8,
+    pub assoc: Assoc,
+}
+
+/// The built-in infix operators every `InfixTable` starts with.
+pub const PREDECLARED_INFIX: [&str; 2] = ["mod", "div"];
+
+/// Runtime operator table filled by `infix` declarations. Consulted by the
+/// lexer (to recognise the symbols), the parser (for precedence and
+/// associativity), and `kernel::print` (to print applications infix again).
+/// A new table already holds the integer operators `mod` and `div`
+/// (`PREDECLARED_INFIX`), at the precedence of `·`; they cannot be
+/// redeclared.
+#[derive(Debug, Clone)]
+pub struct InfixTable {
+    ops: HashMap<String, InfixOp>,
+}
+
+impl Default for InfixTable {
+    fn default() -> Self {
+        let mut table = Self { ops: HashMap::new() };
+        for symbol in PREDECLARED_INFIX {
+            table.declare(InfixOp {
+                symbol: symbol.into(),
+                prec: Op::Mul.prec(),
+                assoc: Assoc::Left,
+            });
+        }
+        table
+    }
+}
+
+impl InfixTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `op`, replacing any earlier declaration of the same symbol.
+    pub fn declare(&mut self, op: InfixOp) {
+        self.ops.insert(op.symbol.clone(), op);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&InfixOp> {
+        self.ops.get(symbol)
+    }
+
+    /// Whether some declared symbol starts with `c`.
+    pub fn any_starts_with(&self, c: char) -> bool {
+        self.ops.keys().any(|symbol| symbol.starts_with(c))
+    }
+
+    /// The longest declared symbol that is a prefix of `s`.
+    pub fn longest_prefix(&self, s: &str) -> Option<&InfixOp> {
+        self.ops
+            .values()
+            .filter(|op| s.starts_with(op.symbol.as_str()))
+            .max_by_key(|op| op.symbol.len())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -97,9 +280,34 @@ pub enum Command {
     Fact(Option<String>, Expr, Option<Expr>),
     Print(Expr),
     Evaluate(Expr),
-    Simplify(Expr),
+    /// `simplify expr [assuming cond] [by cases]`
+    Simplify(Expr, Option<Expr>, bool),
+    /// `expand expr` — polynomial normal form of the ring subterms
+    Expand(Expr),
+    /// `normalize expr` — simplify with ring subterms in polynomial normal form
+    Normalize(Expr),
+    /// `together expr` — combine field subterms into single fractions
+    Together(Expr),
+    /// `cancel expr` — `together`, then cancel common factors
+    Cancel(Expr),
+    /// `factor expr` — factor polynomials over ℤ and ℚ
+    Factor(Expr),
+    /// `approx expr [digits]` — decimal value to `digits` places
+    Approx(Expr, Option<u32>),
+    /// `diff expr for x` — derivative of `expr` with respect to `x`
+    Diff(Expr, String),
+    /// `series expr about x = a to order n` — Taylor series to `O((x - a) ^ n)`
+    Series(Expr, String, Expr, u32),
+    /// `solve eq for x` or `solve {eq1, eq2, …} for x, y, …` — linear systems
+    Solve(Vec<Expr>, Vec<String>),
     /// `apply name to expr`
     Apply(String, Expr),
     /// `apply ← name to expr`
     ApplyRev(String, Expr),
+    /// `infix prec assoc symbol` — declare a user infix operator
+    Infix(InfixOp),
+    /// `prove prop [assuming h1, h2, …] [using order] [by cases]`
+    Prove(Expr, Vec<Expr>, Option<MonomialOrder>, bool),
+    /// `check name` — search for a counterexample to a named fact
+    Check(String),
 }
@@ -0,0 +1,410 @@
+//! Real algebraic numbers built from square roots: exact sums
+//! `c0 + c1 · sqrt(m1) + … + ck · sqrt(mk)` with rational `ci` and distinct
+//! square-free integers `mi > 1`.
+//!
+//! Square roots of distinct square-free integers are linearly independent
+//! over ℚ, so this form is canonical: two numbers are equal exactly when
+//! their coefficients are. Sums and products stay in the form
+//! (`sqrt(a) · sqrt(b) = g · sqrt(a·b / g²)` with `g = gcd(a, b)`), and so do
+//! inverses, by multiplying with conjugates one square root at a time. A
+//! square root of such a number is found when it is rational, a rational
+//! multiple of one square root, or a denestable `sqrt(a + b · sqrt(c))`.
+//!
+//! The sign of a nonzero number is decided by isolating intervals: every
+//! square root is enclosed between dyadic rationals, and the enclosure of the
+//! sum is refined until it excludes zero.
+//!
+//! In terms, `sqrt` is a built-in head. `evaluate` and `simplify` fold
+//! arithmetic on these numbers to the canonical form, written
+//! `c0 + c1 · sqrt(m1) + …` with the radicands ascending, and
+//! `eval::compare_literals` compares them.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_bigint::{BigInt, BigUint};
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::kernel::eval::{radical, rat_to_term, term_to_rat, EvalError};
+use crate::kernel::poly::{Monomial, Poly, MAX_POWER};
+use crate::kernel::term::{sym, Term};
+
+/// A real number `Σ c · sqrt(m)`, keyed by the square-free radicand `m`; the
+/// rational part has radicand 1. No coefficient is zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Algebraic {
+    terms: BTreeMap<BigUint, BigRational>,
+}
+
+impl Algebraic {
+    pub fn zero() -> Algebraic {
+        Algebraic {
+            terms: BTreeMap::new(),
+        }
+    }
+
+    pub fn rational(c: BigRational) -> Algebraic {
+        let mut terms = BTreeMap::new();
+        if !c.is_zero() {
+            terms.insert(BigUint::one(), c);
+        }
+        Algebraic { terms }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The value, if it is rational.
+    pub fn as_rational(&self) -> Option<BigRational> {
+        match self.terms.iter().next() {
+            None => Some(BigRational::zero()),
+            Some((m, c)) if m.is_one() && self.terms.len() == 1 => Some(c.clone()),
+            _ => None,
+        }
+    }
+
+    /// `c · sqrt(m)` for `m` square-free.
+    fn radical(c: BigRational, m: BigUint) -> Algebraic {
+        let mut terms = BTreeMap::new();
+        if !c.is_zero() {
+            terms.insert(m, c);
+        }
+        Algebraic { terms }
+    }
+
+    fn add_term(&mut self, m: BigUint, c: BigRational) {
+        let slot = self
+            .terms
+            .entry(m.clone())
+            .or_insert_with(BigRational::zero);
+        *slot += c;
+        if slot.is_zero() {
+            self.terms.remove(&m);
+        }
+    }
+
+    /// The square root of a non-negative rational, as `c · sqrt(m)`.
+    pub fn sqrt_rational(r: &BigRational) -> Option<Algebraic> {
+        if r.is_negative() {
+            return None;
+        }
+        // sqrt(n/d) = sqrt(n · d) / d
+        let (n, d) = (r.numer().magnitude(), r.denom().magnitude());
+        let (root, rest, _) = radical(n * d, 2);
+        let c = BigRational::new(BigInt::from(root), BigInt::from(d.clone()));
+        Some(Algebraic::radical(c, rest))
+    }
+
+    /// The non-negative square root, when it has this form; `None` for
+    /// negative numbers and roots outside it, such as `sqrt(1 + sqrt(2))`.
+    pub fn sqrt(&self) -> Option<Algebraic> {
+        if let Some(r) = self.as_rational() {
+            return Algebraic::sqrt_rational(&r);
+        }
+        if self.signum() == Ordering::Less {
+            return None;
+        }
+        let zero = BigRational::zero();
+        let (a, (c, b)) = match self.terms.len() {
+            1 => (&zero, self.terms.iter().next()?),
+            2 => (self.terms.get(&BigUint::one())?, self.terms.iter().nth(1)?),
+            _ => return None,
+        };
+        // sqrt(a + b · sqrt(c)) = sqrt(x) ± sqrt(y) with x + y = a and
+        // 4 · x · y = b² · c, i.e. x, y = (a ± s) / 2 for s² = a² - b² · c.
+        let c = BigRational::from(BigInt::from(c.clone()));
+        let s = Algebraic::sqrt_rational(&(a * a - b * b * c))?.as_rational()?;
+        let two = BigRational::from_integer(2.into());
+        let x = Algebraic::sqrt_rational(&((a + &s) / &two))?;
+        let y = Algebraic::sqrt_rational(&((a - &s) / &two))?;
+        let root = if b.is_negative() { &x - &y } else { &x + &y };
+        (&(&root * &root) == self).then_some(root)
+    }
+
+    /// The multiplicative inverse, `None` for zero.
+    pub fn recip(&self) -> Option<Algebraic> {
+        if self.is_zero() {
+            return None;
+        }
+        if let Some(r) = self.as_rational() {
+            return Some(Algebraic::rational(r.recip()));
+        }
+        // A radicand factor `p` that every radicand is either a multiple of
+        // or coprime to, so that `self = u + v · sqrt(p)` with `u`, `v` free
+        // of `p`. Then `1 / self = (u - v · sqrt(p)) / (u² - p · v²)`.
+        let mut p = self.terms.keys().next_back()?.clone();
+        loop {
+            let q = self
+                .terms
+                .keys()
+                .map(|m| gcd(&p, m))
+                .find(|g| !g.is_one() && g != &p);
+            match q {
+                Some(q) => p = q,
+                None => break,
+            }
+        }
+        let (mut u, mut v) = (Algebraic::zero(), Algebraic::zero());
+        for (m, c) in &self.terms {
+            if (m % &p).is_zero() {
+                v.add_term(m / &p, c.clone());
+            } else {
+                u.add_term(m.clone(), c.clone());
+            }
+        }
+        let sqrt_p = Algebraic::radical(BigRational::one(), p.clone());
+        let conjugate = &u - &(&v * &sqrt_p);
+        let p = Algebraic::rational(BigRational::from(BigInt::from(p)));
+        let norm = &(&u * &u) - &(&p * &(&v * &v));
+        Some(&conjugate * &norm.recip()?)
+    }
+
+    pub fn pow(&self, e: i64) -> Option<Algebraic> {
+        let base = if e < 0 { self.recip()? } else { self.clone() };
+        let mut e = e.unsigned_abs();
+        let (mut acc, mut sq) = (Algebraic::rational(BigRational::one()), base);
+        while e > 0 {
+            if e & 1 == 1 {
+                acc = &acc * &sq;
+            }
+            e >>= 1;
+            if e > 0 {
+                sq = &sq * &sq;
+            }
+        }
+        Some(acc)
+    }
+
+    /// The sign, by refining isolating intervals until one excludes zero.
+    pub fn signum(&self) -> Ordering {
+        if self.is_zero() {
+            return Ordering::Equal;
+        }
+        let mut bits = 16;
+        loop {
+            let (lo, hi) = self.enclosure(bits);
+            if lo.is_positive() {
+                return Ordering::Greater;
+            }
+            if hi.is_negative() {
+                return Ordering::Less;
+            }
+            bits *= 2;
+        }
+    }
+
+    /// Rational bounds `lo ≤ self ≤ hi`, with every square root enclosed to
+    /// within `2^-bits`.
+    pub fn enclosure(&self, bits: u32) -> (BigRational, BigRational) {
+        let scale = BigInt::one() << bits;
+        let (mut lo, mut hi) = (BigRational::zero(), BigRational::zero());
+        for (m, c) in &self.terms {
+            let (r_lo, r_hi) = if m.is_one() {
+                (BigRational::one(), BigRational::one())
+            } else {
+                let below = BigInt::from((m << (2 * bits as usize)).sqrt());
+                (
+                    BigRational::new(below.clone(), scale.clone()),
+                    BigRational::new(below + 1, scale.clone()),
+                )
+            };
+            if c.is_negative() {
+                lo += c * r_hi;
+                hi += c * r_lo;
+            } else {
+                lo += c * r_lo;
+                hi += c * r_hi;
+            }
+        }
+        (lo, hi)
+    }
+
+    /// Read a term built from literals, `+`, `-`, `·`, `/`, `sqrt` and `^`
+    /// with integer or half-integer exponents. `None` if it is not of that
+    /// shape, divides by zero, or takes a root outside the form.
+    pub fn from_term(t: &Term) -> Option<Algebraic> {
+        if let Some(r) = term_to_rat(t) {
+            return Some(Algebraic::rational(r));
+        }
+        let (head, args) = match t {
+            Term::App(head, args) => (head.as_ref(), args),
+            _ => return None,
+        };
+        match (head, args.as_slice()) {
+            ("sqrt", [a]) => Algebraic::from_term(a)?.sqrt(),
+            ("-", [a]) => Some(-&Algebraic::from_term(a)?),
+            ("-", [a, b]) => Some(&Algebraic::from_term(a)? - &Algebraic::from_term(b)?),
+            ("/", [a, b]) => Some(&Algebraic::from_term(a)? * &Algebraic::from_term(b)?.recip()?),
+            ("+", [_, _, ..]) => args
+                .iter()
+                .map(Algebraic::from_term)
+                .reduce(|a, b| Some(&a? + &b?))?,
+            ("·", [_, _, ..]) => args
+                .iter()
+                .map(Algebraic::from_term)
+                .reduce(|a, b| Some(&a? * &b?))?,
+            ("^", [a, e]) => {
+                let e = term_to_rat(e)?;
+                let a = Algebraic::from_term(a)?;
+                let twice = &e + &e;
+                if !twice.is_integer() {
+                    return None;
+                }
+                let twice = twice
+                    .to_integer()
+                    .to_i64()
+                    .filter(|n| n.unsigned_abs() <= 2 * u64::from(MAX_POWER))?;
+                if twice % 2 == 0 {
+                    a.pow(twice / 2)
+                } else {
+                    a.sqrt()?.pow(twice)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The canonical term: the rational part first, then `c · sqrt(m)` by
+    /// ascending `m`, negative coefficients after the first as subtraction.
+    pub fn to_term(&self) -> Term {
+        let mut out: Option<Term> = None;
+        for (m, c) in &self.terms {
+            let negative = out.is_some() && c.is_negative();
+            let c = if negative { -c } else { c.clone() };
+            let t = if m.is_one() {
+                rat_to_term(c)
+            } else {
+                let root = Term::App(sym("sqrt"), vec![Term::Nat(m.clone())]);
+                if c.is_one() {
+                    root
+                } else if (-&c).is_one() {
+                    Term::App(sym("-"), vec![root])
+                } else {
+                    Term::App(sym("·"), vec![rat_to_term(c), root])
+                }
+            };
+            out = Some(match out {
+                None => t,
+                Some(acc) if negative => Term::App(sym("-"), vec![acc, t]),
+                Some(acc) => Term::App(sym("+"), vec![acc, t]),
+            });
+        }
+        out.unwrap_or_else(|| rat_to_term(BigRational::zero()))
+    }
+}
+
+impl Add for &Algebraic {
+    type Output = Algebraic;
+    fn add(self, other: &Algebraic) -> Algebraic {
+        let mut out = self.clone();
+        for (m, c) in &other.terms {
+            out.add_term(m.clone(), c.clone());
+        }
+        out
+    }
+}
+
+impl Sub for &Algebraic {
+    type Output = Algebraic;
+    fn sub(self, other: &Algebraic) -> Algebraic {
+        self + &-other
+    }
+}
+
+impl Neg for &Algebraic {
+    type Output = Algebraic;
+    fn neg(self) -> Algebraic {
+        Algebraic {
+            terms: self.terms.iter().map(|(m, c)| (m.clone(), -c)).collect(),
+        }
+    }
+}
+
+impl Mul for &Algebraic {
+    type Output = Algebraic;
+    fn mul(self, other: &Algebraic) -> Algebraic {
+        let mut out = Algebraic::zero();
+        for (a, ca) in &self.terms {
+            for (b, cb) in &other.terms {
+                // sqrt(a) · sqrt(b) = g · sqrt((a / g) · (b / g)), g = gcd(a, b)
+                let g = gcd(a, b);
+                let m = (a / &g) * (b / &g);
+                out.add_term(m, ca * cb * BigRational::from(BigInt::from(g)));
+            }
+        }
+        out
+    }
+}
+
+impl PartialOrd for Algebraic {
+    fn partial_cmp(&self, other: &Algebraic) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Algebraic {
+    fn cmp(&self, other: &Algebraic) -> Ordering {
+        (self - other).signum()
+    }
+}
+
+fn gcd(a: &BigUint, b: &BigUint) -> BigUint {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    while !b.is_zero() {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// `p` with the square roots of integers in each monomial multiplied out, as
+/// the polynomial normal form treats them as indeterminates:
+/// `sqrt(2) ^ 2 · x` becomes `2 · x`, `sqrt(2) · sqrt(6)` becomes
+/// `2 · sqrt(3)`.
+pub(crate) fn reduce_radicals(p: &Poly) -> Poly {
+    let is_root = |x: &Term| matches!(x, Term::App(f, args) if f.as_ref() == "sqrt" && args.len() == 1 && term_to_rat(&args[0]).is_some());
+    if !p.terms().any(|(m, _)| m.keys().any(is_root)) {
+        return p.clone();
+    }
+    let mut out = Poly::zero();
+    for (m, c) in p.terms() {
+        let mut value = Algebraic::rational(c.clone());
+        let mut rest = Monomial::new();
+        for (x, e) in m {
+            match Algebraic::from_term(x).filter(|_| is_root(x)) {
+                Some(root) => value = &value * &root.pow(i64::from(*e)).expect("nonzero"),
+                None => {
+                    rest.insert(x.clone(), *e);
+                }
+            }
+        }
+        let mut monomial = Poly::zero();
+        monomial.add_term(rest, BigRational::one());
+        out = &out + &(&monomial * &Poly::from_term(&value.to_term()));
+    }
+    out
+}
+
+/// Fold `head(args)` when it is arithmetic on algebraic numbers and not
+/// already handled as rational arithmetic: some argument involves a square
+/// root, or `head` is `sqrt`. Division by zero is an error; a root outside
+/// the form leaves the term alone.
+pub(crate) fn fold(head: &str, args: &[Term]) -> Result<Option<Term>, EvalError> {
+    if !matches!(head, "+" | "-" | "·" | "/" | "^" | "sqrt") {
+        return Ok(None);
+    }
+    if head != "sqrt" && args.iter().all(|a| term_to_rat(a).is_some()) {
+        return Ok(None);
+    }
+    if let [_, d] = args {
+        if head == "/" && Algebraic::from_term(d).is_some_and(|d| d.is_zero()) {
+            return Err(EvalError("division by zero".into()));
+        }
+    }
+    let t = Term::App(sym(head), args.to_vec());
+    Ok(Algebraic::from_term(&t).map(|a| a.to_term()))
+}
@@ -0,0 +1,529 @@
+//! Numeric approximation of closed real terms with guaranteed error bounds.
+//!
+//! A term is enclosed in an `Interval` with rational endpoints that is
+//! certain to contain its value. Literals are exact; `+`, `-`, `·`, `/` and
+//! `^` use interval arithmetic; `sqrt`, `exp`, `log`, `sin`, `cos` and the
+//! constant `π` are computed in fixed point from their series, with every
+//! truncation counted into the bounds. Endpoints are rounded outward to a
+//! fixed number of binary places after each step so that they stay small.
+//!
+//! `approx` refines the enclosure until both endpoints round to the same
+//! decimal, and prints that. `compare` uses the same enclosures to decide
+//! `<` and `≠` between closed terms whose values differ; equal values are
+//! never decided, since no enclosure can show it.
+
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::kernel::bigop;
+use crate::kernel::eval::{evaluate, term_to_rat};
+use crate::kernel::term::{as_big_op, sym, Term};
+
+/// Decimal places printed by `approx` when none are given.
+pub const DEFAULT_DIGITS: u32 = 10;
+
+/// Most decimal places `approx` will produce.
+pub const MAX_DIGITS: u32 = 10_000;
+
+/// Times the working precision is doubled before giving up.
+const MAX_REFINEMENTS: u32 = 8;
+
+/// Binary places carried beyond the working precision inside each function.
+const GUARD: u32 = 16;
+
+/// Largest `|x|` for which `exp(x)` is computed; `exp(100000)` already has
+/// about 144 000 bits before the binary point.
+const MAX_EXP_ARG: i64 = 100_000;
+
+/// Largest integer exponent in a power.
+const MAX_EXPONENT: u32 = 1 << 16;
+
+#[derive(Debug)]
+pub struct ApproxError(pub String);
+
+/// A closed interval `[lo, hi]` of rationals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval {
+    pub lo: BigRational,
+    pub hi: BigRational,
+}
+
+/// Why an enclosure could not be computed.
+enum Fail {
+    /// The term has no real value: a free variable, an unknown function, a
+    /// domain error.
+    Undefined(String),
+    /// The enclosure straddles a point where an operation is undefined, such
+    /// as zero for `/`; more precision may settle it.
+    Imprecise,
+}
+
+type Enclosure = Result<Interval, Fail>;
+
+fn undefined<T>(msg: String) -> Result<T, Fail> {
+    Err(Fail::Undefined(msg))
+}
+
+/// Print `t` in decimal with `digits` places after the point. The result is
+/// correctly rounded (halves away from zero), except when the value lies too
+/// close to a rounding boundary to tell which way it goes; then it is off by
+/// at most one unit in the last place.
+pub fn approx(t: &Term, digits: u32) -> Result<String, ApproxError> {
+    let ulp = BigRational::new(BigInt::one(), BigInt::from(10).pow(digits));
+    let mut bits = digits * 10 / 3 + 32;
+    let mut last = None;
+    for _ in 0..MAX_REFINEMENTS {
+        match enclose_at(t, bits) {
+            Ok(iv) => {
+                let (lo, hi) = (decimal(&iv.lo, digits), decimal(&iv.hi, digits));
+                if lo == hi {
+                    return Ok(lo);
+                }
+                last = Some(iv);
+            }
+            Err(Fail::Undefined(msg)) => return Err(ApproxError(msg)),
+            Err(Fail::Imprecise) => {}
+        }
+        bits *= 2;
+    }
+    match last {
+        Some(iv) if iv.width() <= ulp => Ok(decimal(&iv.mid(), digits)),
+        _ => Err(ApproxError(format!(
+            "could not reach {digits} decimal places"
+        ))),
+    }
+}
+
+/// Enclose the value of `t`, rounding outward to `bits` binary places.
+pub fn enclose(t: &Term, bits: u32) -> Result<Interval, ApproxError> {
+    enclose_at(t, bits).map_err(|fail| match fail {
+        Fail::Undefined(msg) => ApproxError(msg),
+        Fail::Imprecise => ApproxError(format!("{bits} bits are not enough to decide a sign")),
+    })
+}
+
+/// Order two closed real terms whose values differ, by enclosing their
+/// difference until it excludes zero. `None` when either has no value or the
+/// values are too close (or equal) to separate.
+pub(crate) fn compare(a: &Term, b: &Term) -> Option<Ordering> {
+    if a == b {
+        return None;
+    }
+    let d = Term::App(sym("-"), vec![a.clone(), b.clone()]);
+    for bits in [32, 128, 512] {
+        match enclose_at(&d, bits) {
+            Ok(iv) if iv.lo.is_positive() => return Some(Ordering::Greater),
+            Ok(iv) if iv.hi.is_negative() => return Some(Ordering::Less),
+            Ok(_) | Err(Fail::Imprecise) => {}
+            Err(Fail::Undefined(_)) => return None,
+        }
+    }
+    None
+}
+
+fn enclose_at(t: &Term, w: u32) -> Enclosure {
+    if let Some(r) = term_to_rat(t) {
+        return Ok(Interval::point(r));
+    }
+    match t {
+        Term::Complex(..) => undefined("complex numbers have no decimal value".into()),
+        Term::Residue(..) => undefined("residues have no decimal value".into()),
+        Term::Var(x) if x.as_ref() == "π" => Ok(pi(w)),
+        Term::Var(x) => undefined(format!("`{x}` has no value")),
+        Term::App(..) if as_big_op(t).is_some() => big_op(t, w),
+        Term::App(head, args) => {
+            let ivs = args
+                .iter()
+                .map(|a| enclose_at(a, w))
+                .collect::<Result<Vec<_>, _>>()?;
+            let out = match (head.as_ref(), ivs.as_slice()) {
+                ("+", [_, ..]) => ivs.iter().skip(1).fold(ivs[0].clone(), |acc, x| acc.add(x)),
+                ("·", [_, ..]) => ivs.iter().skip(1).fold(ivs[0].clone(), |acc, x| acc.mul(x)),
+                ("-", [x]) => x.neg(),
+                ("-", [x, y]) => x.add(&y.neg()),
+                ("/", [x, y]) => x.mul(&y.recip()?),
+                ("^", [x, y]) => power(x, y, w)?,
+                ("sqrt", [x]) => sqrt(x, w)?,
+                ("exp", [x]) => {
+                    let lo = exp(&x.lo, w)?.lo;
+                    Interval::new(lo, exp(&x.hi, w)?.hi)
+                }
+                ("log", [x]) => log(x, w)?,
+                ("sin", [x]) => sin_cos(x, w)?.0,
+                ("cos", [x]) => sin_cos(x, w)?.1,
+                _ => return undefined(format!("no numeric value for `{head}`")),
+            };
+            // Exact results stay exact, so `x ^ (1/3)` sees a rational
+            // exponent.
+            if out.lo == out.hi {
+                Ok(out)
+            } else {
+                Ok(out.round(w))
+            }
+        }
+        _ => unreachable!("literals are handled above"),
+    }
+}
+
+/// Enclose a finite sum or product term by term; its bounds must be
+/// integer literals.
+fn big_op(t: &Term, w: u32) -> Enclosure {
+    let (head, var, lo, hi, body) = as_big_op(t).expect("a sum or product");
+    let bound = |b: &Term| evaluate(b).unwrap_or_else(|_| b.clone());
+    let terms = match bigop::instances(var, &bound(lo), &bound(hi), body) {
+        Ok(Some(terms)) => terms,
+        Ok(None) => return undefined(format!("the bounds of `{head}` must be integers")),
+        Err(e) => return undefined(e.0),
+    };
+    let mut acc = enclose_at(&bigop::operator(head).1, w)?;
+    for term in &terms {
+        let x = enclose_at(term, w)?;
+        // Rounding every step keeps long sums of fractions cheap.
+        acc = if head.as_ref() == "∏" { acc.mul(&x) } else { acc.add(&x) }.round(w);
+    }
+    Ok(acc)
+}
+
+fn pow2(n: u32) -> BigInt {
+    BigInt::one() << n
+}
+
+/// `x · 2^w`, rounded down.
+fn floor_at(x: &BigRational, w: u32) -> BigInt {
+    (x * BigRational::from(pow2(w))).floor().to_integer()
+}
+
+/// `x · 2^w`, rounded up.
+fn ceil_at(x: &BigRational, w: u32) -> BigInt {
+    (x * BigRational::from(pow2(w))).ceil().to_integer()
+}
+
+/// The interval `(n ± err) / 2^w` for a fixed-point result `n` with an error
+/// of at most `err` units.
+fn fixed(n: BigInt, err: u64, w: u32) -> Interval {
+    let err = BigInt::from(err);
+    Interval::new(
+        BigRational::new(&n - &err, pow2(w)),
+        BigRational::new(n + err, pow2(w)),
+    )
+}
+
+impl Interval {
+    pub fn new(lo: BigRational, hi: BigRational) -> Interval {
+        debug_assert!(lo <= hi);
+        Interval { lo, hi }
+    }
+
+    pub fn point(x: BigRational) -> Interval {
+        Interval::new(x.clone(), x)
+    }
+
+    pub fn width(&self) -> BigRational {
+        &self.hi - &self.lo
+    }
+
+    pub fn mid(&self) -> BigRational {
+        (&self.lo + &self.hi) / BigRational::from_integer(2.into())
+    }
+
+    /// Round outward to `w` binary places.
+    fn round(self, w: u32) -> Interval {
+        let lo = BigRational::new(floor_at(&self.lo, w), pow2(w));
+        let hi = BigRational::new(ceil_at(&self.hi, w), pow2(w));
+        Interval::new(lo, hi)
+    }
+
+    fn add(&self, y: &Interval) -> Interval {
+        Interval::new(&self.lo + &y.lo, &self.hi + &y.hi)
+    }
+
+    fn neg(&self) -> Interval {
+        Interval::new(-&self.hi, -&self.lo)
+    }
+
+    fn mul(&self, y: &Interval) -> Interval {
+        let ps = [
+            &self.lo * &y.lo,
+            &self.lo * &y.hi,
+            &self.hi * &y.lo,
+            &self.hi * &y.hi,
+        ];
+        let lo = ps.iter().min().expect("four products").clone();
+        let hi = ps.iter().max().expect("four products").clone();
+        Interval::new(lo, hi)
+    }
+
+    fn scale(&self, k: &BigRational) -> Interval {
+        self.mul(&Interval::point(k.clone()))
+    }
+
+    fn recip(&self) -> Enclosure {
+        if self.lo.is_zero() && self.hi.is_zero() {
+            return undefined("division by zero".into());
+        }
+        if !self.lo.is_positive() && !self.hi.is_negative() {
+            return Err(Fail::Imprecise);
+        }
+        Ok(Interval::new(self.hi.recip(), self.lo.recip()))
+    }
+
+    /// Widen by `r` on both sides.
+    fn widen(&self, r: &BigRational) -> Interval {
+        Interval::new(&self.lo - r, &self.hi + r)
+    }
+
+    fn clamp_unit(self) -> Interval {
+        let one = BigRational::one();
+        Interval::new(self.lo.max(-&one), self.hi.min(one))
+    }
+}
+
+/// `x ^ y`: an integer power when `y` is an exact integer, otherwise
+/// `exp(y · log(x))`, which needs `x > 0`.
+fn power(x: &Interval, y: &Interval, w: u32) -> Enclosure {
+    if y.lo == y.hi && y.lo.is_integer() {
+        let n = y.lo.to_integer();
+        let k = match n.abs().to_u32() {
+            Some(k) if k <= MAX_EXPONENT => k,
+            _ => return undefined(format!("exponent {n} is too large")),
+        };
+        let p = int_power(x, k);
+        return if n.is_negative() { p.recip() } else { Ok(p) };
+    }
+    if x.lo.is_zero() && x.hi.is_zero() && y.lo.is_positive() {
+        return Ok(Interval::point(BigRational::zero()));
+    }
+    if x.hi.is_negative() {
+        // An odd root of a negative number: `x ^ (p/q) = ±(-x) ^ (p/q)`.
+        return match (y.lo == y.hi, y.lo.denom().bit(0)) {
+            (true, true) => {
+                let p = power(&x.neg(), y, w)?;
+                Ok(if y.lo.numer().bit(0) { p.neg() } else { p })
+            }
+            _ => undefined("power of a negative number with a non-integer exponent".into()),
+        };
+    }
+    let e = log(x, w)?.mul(y).round(w);
+    let lo = exp(&e.lo, w)?.lo;
+    Ok(Interval::new(lo, exp(&e.hi, w)?.hi))
+}
+
+fn int_power(x: &Interval, k: u32) -> Interval {
+    let (lo, hi) = (pow(&x.lo, k), pow(&x.hi, k));
+    if k % 2 == 1 || !x.lo.is_negative() {
+        Interval::new(lo, hi)
+    } else if !x.hi.is_positive() {
+        Interval::new(hi, lo)
+    } else {
+        Interval::new(BigRational::zero(), lo.max(hi))
+    }
+}
+
+fn pow(x: &BigRational, k: u32) -> BigRational {
+    BigRational::new(x.numer().pow(k), x.denom().pow(k))
+}
+
+fn sqrt(x: &Interval, w: u32) -> Enclosure {
+    if x.hi.is_negative() {
+        return undefined("square root of a negative number".into());
+    }
+    if x.lo.is_negative() {
+        return Err(Fail::Imprecise);
+    }
+    // ⌊√⌊x · 4^w⌋⌋ ≤ √x · 2^w ≤ ⌈√⌈x · 4^w⌉⌉.
+    let lo = floor_at(&x.lo, 2 * w).sqrt();
+    let n = ceil_at(&x.hi, 2 * w);
+    let r = n.sqrt();
+    let hi = if &r * &r == n { r } else { r + 1 };
+    Ok(Interval::new(
+        BigRational::new(lo, pow2(w)),
+        BigRational::new(hi, pow2(w)),
+    ))
+}
+
+/// `exp(x)` for a rational `x`: halve `x` until `|x| ≤ 1/2`, sum the Taylor
+/// series, and square the result back.
+fn exp(x: &BigRational, w: u32) -> Enclosure {
+    if x.is_zero() {
+        return Ok(Interval::point(BigRational::one()));
+    }
+    if x.abs() > BigRational::from_integer(MAX_EXP_ARG.into()) {
+        return undefined("argument of `exp` is too large".into());
+    }
+    let half = BigRational::new(1.into(), 2.into());
+    let mut s = 0;
+    let mut y = x.clone();
+    while y.abs() > half {
+        y /= BigRational::from_integer(2.into());
+        s += 1;
+    }
+    // Each squaring doubles the relative error, and the result has up to
+    // `1.5 · x` bits before the point.
+    let magnitude = if x.is_positive() {
+        x.ceil().to_integer().to_u32().unwrap_or(0) * 3 / 2 + 1
+    } else {
+        0
+    };
+    let w2 = w + GUARD + s + magnitude;
+    let one = pow2(w2);
+    let y = floor_at(&y, w2);
+    let (mut term, mut sum) = (one.clone(), one.clone());
+    let mut k: u64 = 1;
+    while !term.is_zero() {
+        term = &term * &y / (&one * k);
+        sum += &term;
+        k += 1;
+    }
+    // One unit from rounding `y` (the slope is below 2), one per term, and
+    // a tail below two units.
+    let mut iv = fixed(sum, 2 * k + 4, w2);
+    for _ in 0..s {
+        iv = Interval::new(&iv.lo * &iv.lo, &iv.hi * &iv.hi).round(w2);
+    }
+    Ok(iv)
+}
+
+/// `atanh(z) = z + z³/3 + z⁵/5 + …` for `0 ≤ z ≤ 1/2`.
+fn atanh(z: &BigRational, w: u32) -> Interval {
+    let z = floor_at(z, w);
+    let z2 = (&z * &z) >> w;
+    let (mut p, mut sum) = (z, BigInt::zero());
+    let mut k: u64 = 0;
+    while !p.is_zero() {
+        sum += &p / (2 * k + 1);
+        p = (&p * &z2) >> w;
+        k += 1;
+    }
+    // Two units from rounding `z` (the slope is at most 4/3), two per term,
+    // and a tail below two units.
+    fixed(sum, 2 * k + 6, w)
+}
+
+/// `log(x)` for `x > 0`: with `x = m · 2^e` and `1 ≤ m < 2`,
+/// `log(x) = e · log(2) + 2 · atanh((m - 1) / (m + 1))`.
+fn log(x: &Interval, w: u32) -> Enclosure {
+    if !x.hi.is_positive() {
+        return undefined("logarithm of a non-positive number".into());
+    }
+    if !x.lo.is_positive() {
+        return Err(Fail::Imprecise);
+    }
+    Ok(Interval::new(
+        log_point(&x.lo, w).lo,
+        log_point(&x.hi, w).hi,
+    ))
+}
+
+fn log_point(x: &BigRational, w: u32) -> Interval {
+    let two = BigRational::from_integer(2.into());
+    let mut e = x.numer().bits() as i64 - x.denom().bits() as i64;
+    let mut m = x / pow_signed(&two, e);
+    if m < BigRational::one() {
+        e -= 1;
+        m *= &two;
+    } else if m >= two {
+        e += 1;
+        m /= &two;
+    }
+    let w2 = w + GUARD + 64 - e.unsigned_abs().leading_zeros();
+    let one = BigRational::one();
+    let lm = atanh(&((&m - &one) / (&m + &one)), w2).scale(&two);
+    let l2 = atanh(&BigRational::new(1.into(), 3.into()), w2).scale(&two);
+    lm.add(&l2.scale(&BigRational::from_integer(e.into())))
+}
+
+fn pow_signed(x: &BigRational, e: i64) -> BigRational {
+    let p = pow(x, e.unsigned_abs() as u32);
+    if e < 0 {
+        p.recip()
+    } else {
+        p
+    }
+}
+
+/// `atan(1/n) = 1/n - 1/(3 n³) + 1/(5 n⁵) - …`.
+fn atan_inv(n: u32, w: u32) -> Interval {
+    let n2 = BigInt::from(n) * n;
+    let mut p = pow2(w) / n;
+    let mut sum = BigInt::zero();
+    let mut k: u64 = 0;
+    while !p.is_zero() {
+        let t = &p / (2 * k + 1);
+        if k.is_multiple_of(2) {
+            sum += t;
+        } else {
+            sum -= t;
+        }
+        p /= &n2;
+        k += 1;
+    }
+    fixed(sum, 2 * k + 3, w)
+}
+
+/// `π = 16 · atan(1/5) - 4 · atan(1/239)` (Machin's formula).
+fn pi(w: u32) -> Interval {
+    let w2 = w + GUARD;
+    let a = atan_inv(5, w2).scale(&BigRational::from_integer(16.into()));
+    let b = atan_inv(239, w2).scale(&BigRational::from_integer(4.into()));
+    a.add(&b.neg()).round(w)
+}
+
+/// `sin` and `cos` over an interval: their values at the midpoint, widened
+/// by the radius since both have slope at most 1.
+fn sin_cos(x: &Interval, w: u32) -> Result<(Interval, Interval), Fail> {
+    let c = x.mid();
+    let r = &x.hi - &c;
+    let k = c.abs().ceil().to_integer().bits();
+    if k > 100_000 {
+        return undefined("argument of `sin` or `cos` is too large".into());
+    }
+    // Reduce to `c = k · π + y` with `|y| ≤ π/2`; then
+    // `sin(c) = (-1)^k sin(y)` and `cos(c) = (-1)^k cos(y)`.
+    let w2 = w + GUARD + k as u32;
+    let p = pi(w2);
+    let q = (&c / p.mid()).round().to_integer();
+    let y = Interval::point(c).add(&p.scale(&BigRational::from(q.clone())).neg());
+    let ym = y.mid();
+    let yr = &y.hi - &ym;
+    let one = pow2(w2);
+    let yf = floor_at(&ym, w2);
+    let y2 = (&yf * &yf) >> w2;
+    let series = |mut term: BigInt, offset: u64| {
+        let mut sum = term.clone();
+        let mut j: u64 = 1;
+        while !term.is_zero() {
+            term = -((&term * &y2) >> w2) / ((2 * j - 1 + offset) * (2 * j + offset));
+            sum += &term;
+            j += 1;
+        }
+        // One unit from rounding `y`, two per term, and a tail of at most
+        // the last term.
+        fixed(sum, 2 * j + 4, w2).widen(&yr).widen(&r)
+    };
+    let sin = series(yf.clone(), 1);
+    let cos = series(one, 0);
+    let (sin, cos) = if q.bit(0) {
+        (sin.neg(), cos.neg())
+    } else {
+        (sin, cos)
+    };
+    Ok((sin.clamp_unit(), cos.clamp_unit()))
+}
+
+/// `x` rounded to `digits` decimal places, halves away from zero.
+fn decimal(x: &BigRational, digits: u32) -> String {
+    let scaled = x * BigRational::from(BigInt::from(10).pow(digits));
+    let n = scaled.round().to_integer();
+    let sign = if n.is_negative() { "-" } else { "" };
+    let s = n.abs().to_string();
+    if digits == 0 {
+        return format!("{sign}{s}");
+    }
+    let d = digits as usize;
+    let s = format!("{s:0>width$}", width = d + 1);
+    let (int, frac) = s.split_at(s.len() - d);
+    format!("{sign}{int}.{frac}")
+}
@@ -0,0 +1,141 @@
+//! Finite sums and products, `∑ body for k from a to b` and
+//! `∏ body for k from a to b`.
+//!
+//! Both are applications of a reserved head to the bound variable, the bounds
+//! and the body (see `term::big_op`). With integer literals as bounds they
+//! expand into their terms, `body[k := a] + … + body[k := b]`, an empty range
+//! giving `0` or `1`; with equal bounds they are their one term. Otherwise
+//! they stay as they are, and rules may rewrite them. A rule's pattern
+//! matches a sum whatever its bound variable is called, and its right side
+//! can instantiate the matched body with a one-term sum: the index shift
+//! `∑ e for k from a + 1 to b + 1 = ∑ (∑ e for k from k + 1 to k + 1) for k
+//! from a to b` rewrites `e` at `k + 1`.
+
+use std::collections::HashMap;
+
+use num_bigint::BigInt;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::kernel::eval::{rat_to_term, term_to_rat, EvalError};
+use crate::kernel::subst::{subst, subst_match};
+use crate::kernel::term::{as_big_op, Symbol, Term};
+
+/// Largest number of terms a sum or product is expanded into.
+pub const MAX_TERMS: u32 = 100_000;
+
+/// The binary operator a big operator iterates, and its value on an empty
+/// range.
+pub(crate) fn operator(head: &str) -> (&'static str, Term) {
+    match head {
+        "∏" => ("·", Term::Nat(One::one())),
+        _ => ("+", Term::Nat(Zero::zero())),
+    }
+}
+
+/// `body` with `var := value`.
+pub(crate) fn instance(var: &Symbol, body: &Term, value: Term) -> Term {
+    let mut sigma = HashMap::new();
+    sigma.insert(var.clone(), value);
+    subst(body, &sigma)
+}
+
+/// The terms of a sum or product whose bounds are integer literals, in
+/// order; `Ok(None)` for other bounds. More than `MAX_TERMS` terms is an
+/// error.
+pub(crate) fn instances(
+    var: &Symbol,
+    lo: &Term,
+    hi: &Term,
+    body: &Term,
+) -> Result<Option<Vec<Term>>, EvalError> {
+    let (a, b) = match (term_to_rat(lo), term_to_rat(hi)) {
+        (Some(a), Some(b)) if a.is_integer() && b.is_integer() => (a.to_integer(), b.to_integer()),
+        _ => return Ok(None),
+    };
+    let count = (&b - &a + BigInt::one()).max(BigInt::zero());
+    match count.to_u32() {
+        Some(n) if n <= MAX_TERMS => {}
+        _ => {
+            return Err(EvalError(format!(
+                "{count} terms too many to expand (must be at most {MAX_TERMS})"
+            )))
+        }
+    }
+    let mut terms = Vec::new();
+    let mut j = a;
+    while j <= b {
+        terms.push(instance(var, body, rat_to_term(j.clone().into())));
+        j += 1;
+    }
+    Ok(Some(terms))
+}
+
+/// The one term of a sum or product whose bounds are the same term.
+pub(crate) fn single(var: &Symbol, lo: &Term, hi: &Term, body: &Term) -> Option<Term> {
+    (lo == hi).then(|| instance(var, body, lo.clone()))
+}
+
+/// Replace every sum or product in `t` with equal bounds by its one term.
+fn collapse(t: &Term) -> Term {
+    match t {
+        Term::App(head, args) => {
+            let t = Term::App(head.clone(), args.iter().map(collapse).collect());
+            match as_big_op(&t).and_then(|(_, var, lo, hi, body)| single(var, lo, hi, body)) {
+                Some(one) => collapse(&one),
+                None => t,
+            }
+        }
+        _ => t.clone(),
+    }
+}
+
+/// The right side `rhs` of a rule instantiated by the match `sigma` of its
+/// left side `lhs`, with one-term sums and products taken apart. `None` when
+/// a variable the pattern bound under a sum would escape it: the match of
+/// `c` in `∑ c · e for k from a to b = c · ∑ e for k from a to b` must not
+/// mention `k`.
+pub(crate) fn instantiate(lhs: &Term, rhs: &Term, sigma: &HashMap<Symbol, Term>) -> Option<Term> {
+    let mut bound = Vec::new();
+    bound_vars(lhs, sigma, &mut bound);
+    if !bound.is_empty() && escapes(rhs, sigma, &bound) {
+        return None;
+    }
+    Some(collapse(&subst_match(rhs, sigma)))
+}
+
+/// The variables a match binds in place of the bound variables of `pat`.
+fn bound_vars(pat: &Term, sigma: &HashMap<Symbol, Term>, out: &mut Vec<Symbol>) {
+    if let Some((_, var, _, _, _)) = as_big_op(pat) {
+        if let Some(Term::Var(x)) = sigma.get(var) {
+            out.push(x.clone());
+        }
+    }
+    if let Term::App(_, args) = pat {
+        for a in args {
+            bound_vars(a, sigma, out);
+        }
+    }
+}
+
+/// Whether a variable of `rhs` outside the body of every sum and product is
+/// matched to a term mentioning one of `bound`.
+fn escapes(rhs: &Term, sigma: &HashMap<Symbol, Term>, bound: &[Symbol]) -> bool {
+    if let Some((_, _, lo, hi, _)) = as_big_op(rhs) {
+        return escapes(lo, sigma, bound) || escapes(hi, sigma, bound);
+    }
+    match rhs {
+        Term::Var(x) => sigma
+            .get(x)
+            .is_some_and(|t| bound.iter().any(|b| mentions(t, b))),
+        Term::App(_, args) => args.iter().any(|a| escapes(a, sigma, bound)),
+        _ => false,
+    }
+}
+
+fn mentions(t: &Term, x: &Symbol) -> bool {
+    match t {
+        Term::Var(y) => x == y,
+        Term::App(_, args) => args.iter().any(|a| mentions(a, x)),
+        _ => false,
+    }
+}
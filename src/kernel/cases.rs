@@ -0,0 +1,87 @@
+//! Case splitting for `simplify … by cases` and `prove … by cases`.
+//!
+//! After an ordinary `simplify`, the result is searched for a conditional rule
+//! that matches somewhere but whose condition simplifies to neither `True` nor
+//! `False`, or, failing that, for an undecided atom of the result itself when
+//! it is a proposition. One atom of that condition is split on: the term is
+//! simplified again assuming the atom and assuming its complement, and the two
+//! results are joined into the piecewise term `if atom then … else …`.
+//! Splitting recurses into both branches up to a fixed number of levels.
+
+use crate::kernel::logic::{complement, piecewise};
+use crate::kernel::pmatch::pmatch;
+use crate::kernel::rewrite::{assume, simplify_at, CONDITION_DEPTH};
+use crate::kernel::subst::subst_match;
+use crate::kernel::term::{as_truth, Term};
+use crate::kernel::theory::Theory;
+
+/// How many case splits may nest along one branch.
+const MAX_SPLITS: usize = 4;
+
+/// Simplify `t`, optionally assuming `assumption` as `simplify_assuming`
+/// does, splitting on undecided conditions. The result is piecewise where the
+/// branches differ; a proposition that reduces to `True` in every branch
+/// comes back as `True`.
+pub fn simplify_by_cases(t: &Term, assumption: Option<&Term>, theory: &Theory) -> Term {
+    let (t, hyps) = match assumption {
+        Some(a) => assume(t, a, theory),
+        None => (t.clone(), Vec::new()),
+    };
+    split(&t, theory, &hyps, MAX_SPLITS)
+}
+
+fn split(t: &Term, theory: &Theory, hyps: &[Term], budget: usize) -> Term {
+    let r = simplify_at(t, theory, hyps, CONDITION_DEPTH);
+    if budget == 0 {
+        return r;
+    }
+    let atom = match undecided_atom(&r, theory, hyps) {
+        Some(atom) => atom,
+        None => return r,
+    };
+    let mut yes = hyps.to_vec();
+    yes.push(atom.clone());
+    let mut no = hyps.to_vec();
+    no.push(complement(&atom));
+    let a = split(&r, theory, &yes, budget - 1);
+    let b = split(&r, theory, &no, budget - 1);
+    piecewise(atom, a, b)
+}
+
+/// An atom worth splitting on: first from the residual condition of a
+/// conditional rule matching some subterm (outermost first), else from `t`
+/// itself if it is an undecided proposition.
+fn undecided_atom(t: &Term, theory: &Theory, hyps: &[Term]) -> Option<Term> {
+    blocked_condition(t, theory, hyps).or_else(|| first_atom(t))
+}
+
+fn blocked_condition(t: &Term, theory: &Theory, hyps: &[Term]) -> Option<Term> {
+    for r in &theory.rules {
+        if let (Some(c), Some(sigma)) = (&r.condition, pmatch(&r.lhs, t)) {
+            let residual = simplify_at(&subst_match(c, &sigma), theory, hyps, CONDITION_DEPTH);
+            if as_truth(&residual).is_none() {
+                if let Some(atom) = first_atom(&residual) {
+                    return Some(atom);
+                }
+            }
+        }
+    }
+    match t {
+        Term::App(_, args) => args.iter().find_map(|a| blocked_condition(a, theory, hyps)),
+        _ => None,
+    }
+}
+
+/// The leftmost atom of a proposition built from `¬`, `∧`, `∨`, `⇒` and
+/// atoms (memberships and comparisons). `None` for anything else.
+fn first_atom(p: &Term) -> Option<Term> {
+    let (head, args) = match p {
+        Term::App(head, args) => (head, args),
+        _ => return None,
+    };
+    match (head.as_ref(), args.len()) {
+        ("¬", 1) | ("∧", _) | ("∨", _) | ("⇒", 2) => first_atom(&args[0]),
+        ("∈" | "=" | "≠" | "<" | "≤" | ">" | "≥", 2) => Some(p.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,234 @@
+//! Counterexample search for facts: `check name` and the `--check-facts` mode.
+//!
+//! A fact is tested on random numeric instances. Each free variable is
+//! replaced by a literal drawn from its `∀` domain: `ℕ` and `ℤ` draw integers,
+//! anything else (`ℚ`, `ℝ`, `ℂ`, an undeclared domain) draws rationals, and a
+//! predicate-defined set draws from its own domain. Instances whose condition
+//! does not simplify to `True` are discarded. The fact itself is decided with
+//! `eval::evaluate`: an equation by comparing its evaluated sides, any other
+//! proposition by evaluating it to a truth constant. Draws start tiny and
+//! grow with each instance, so the first counterexample found is small, and
+//! the generator has a fixed seed so a check gives the same answer every time.
+
+use std::collections::HashMap;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+
+use crate::kernel::eval::{evaluate, rat_to_term, term_to_rat, EvalError};
+use crate::kernel::rewrite::simplify;
+use crate::kernel::subst::subst;
+use crate::kernel::term::{as_big_op, as_truth, sym, Symbol, Term};
+use crate::kernel::theory::Theory;
+
+/// How many instances must pass before a fact counts as checked.
+pub const TRIALS: usize = 100;
+
+/// How many instances are drawn at most, counting discarded ones.
+const MAX_DRAWS: usize = 10 * TRIALS;
+
+/// The largest magnitude drawn, reached after this many instances.
+const MAX_SIZE: u64 = 1000;
+
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Outcome of `check_fact`. Instances list the variables in order of first
+/// occurrence.
+#[derive(Debug)]
+pub enum CheckOutcome {
+    /// This many instances satisfied the condition, and the fact held on all.
+    Passed(usize),
+    /// An instance satisfying the condition on which the fact fails.
+    Counterexample(Vec<(Symbol, Term)>, Failure),
+    /// An instance on which the fact does not evaluate to a verdict, e.g.
+    /// because it applies a function with no built-in evaluation.
+    Undecided(Vec<(Symbol, Term)>, Term),
+    /// No drawn instance satisfied the condition.
+    Vacuous,
+}
+
+/// How a fact fails on a counterexample.
+#[derive(Debug)]
+pub enum Failure {
+    /// The sides of an equation evaluate to different values.
+    Unequal(Term, Term),
+    /// The proposition evaluates to `False`.
+    False,
+    /// Evaluating the fact fails, e.g. on a division by zero.
+    Error(EvalError),
+}
+
+enum Verdict {
+    Holds,
+    Fails(Failure),
+    Undecided(Term),
+}
+
+/// Search for an instance of `prop` that satisfies `condition` but not
+/// `prop`. `domains` gives the `∀` domain of bound variables; the premises of
+/// an implication `P ⇒ Q` are moved into the condition, as `install_fact`
+/// does. The condition is simplified under `theory`, so it may use its rules
+/// and predicate sets; `prop` is only evaluated.
+pub fn check_fact(
+    prop: &Term,
+    condition: Option<&Term>,
+    domains: &[(Symbol, Term)],
+    theory: &Theory,
+) -> CheckOutcome {
+    let mut prop = prop;
+    let mut conds: Vec<Term> = condition.into_iter().cloned().collect();
+    while let Term::App(head, args) = prop {
+        if head.as_ref() != "⇒" || args.len() != 2 {
+            break;
+        }
+        conds.push(args[0].clone());
+        prop = &args[1];
+    }
+    let condition = conds.into_iter().reduce(|a, b| Term::App(sym("∧"), vec![a, b]));
+
+    let mut vars = Vec::new();
+    variables(prop, theory, &mut vars);
+    if let Some(c) = &condition {
+        variables(c, theory, &mut vars);
+    }
+    // Without variables a single instance decides the fact.
+    let trials = if vars.is_empty() { 1 } else { TRIALS };
+
+    let mut rng = Rng(SEED);
+    let mut passed = 0;
+    for size in 0..MAX_DRAWS as u64 {
+        if passed == trials {
+            break;
+        }
+        let instance: Vec<(Symbol, Term)> = vars
+            .iter()
+            .map(|v| {
+                let domain = domains.iter().find(|(d, _)| d == v).map(|(_, s)| s);
+                (v.clone(), draw(domain, size, theory, &mut rng))
+            })
+            .collect();
+        let sigma: HashMap<Symbol, Term> = instance.iter().cloned().collect();
+        if let Some(c) = &condition {
+            if as_truth(&simplify(&subst(c, &sigma), theory)) != Some(true) {
+                continue;
+            }
+        }
+        match decide(&subst(prop, &sigma)) {
+            Verdict::Holds => passed += 1,
+            Verdict::Fails(f) => return CheckOutcome::Counterexample(instance, f),
+            Verdict::Undecided(t) => return CheckOutcome::Undecided(instance, t),
+        }
+    }
+    if passed == 0 {
+        CheckOutcome::Vacuous
+    } else {
+        CheckOutcome::Passed(passed)
+    }
+}
+
+fn decide(p: &Term) -> Verdict {
+    if let Term::App(head, args) = p {
+        if head.as_ref() == "=" && args.len() == 2 {
+            let (a, b) = match (evaluate(&args[0]), evaluate(&args[1])) {
+                (Ok(a), Ok(b)) => (a, b),
+                (Err(e), _) | (_, Err(e)) => return Verdict::Fails(Failure::Error(e)),
+            };
+            return match (term_to_rat(&a), term_to_rat(&b)) {
+                (Some(x), Some(y)) if x == y => Verdict::Holds,
+                (Some(_), Some(_)) => Verdict::Fails(Failure::Unequal(a, b)),
+                _ if a == b => Verdict::Holds,
+                _ => Verdict::Undecided(Term::App(head.clone(), vec![a, b])),
+            };
+        }
+    }
+    match evaluate(p) {
+        Ok(t) => match as_truth(&t) {
+            Some(true) => Verdict::Holds,
+            Some(false) => Verdict::Fails(Failure::False),
+            None => Verdict::Undecided(t),
+        },
+        Err(e) => Verdict::Fails(Failure::Error(e)),
+    }
+}
+
+/// Collect the variables of `t` that stand for numbers, skipping the set
+/// operand of `∈`, names of predicate-defined sets and the bound variables
+/// of sums and products.
+fn variables(t: &Term, theory: &Theory, out: &mut Vec<Symbol>) {
+    if let Some((_, var, lo, hi, body)) = as_big_op(t) {
+        variables(lo, theory, out);
+        variables(hi, theory, out);
+        let before = out.len();
+        variables(body, theory, out);
+        let found = out.split_off(before);
+        out.extend(found.into_iter().filter(|v| v != var));
+        return;
+    }
+    match t {
+        Term::Var(s) if !out.contains(s) && !theory.predicate_sets.contains_key(s) => {
+            out.push(s.clone());
+        }
+        Term::App(head, args) => {
+            let args = match head.as_ref() {
+                "∈" if args.len() == 2 => &args[..1],
+                "⊆" => &[],
+                _ => &args[..],
+            };
+            for a in args {
+                variables(a, theory, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A literal drawn from `domain`, with magnitude at most about `size`.
+fn draw(domain: Option<&Term>, size: u64, theory: &Theory, rng: &mut Rng) -> Term {
+    let name = match domain {
+        Some(Term::Var(s)) => s.as_ref(),
+        _ => "ℚ",
+    };
+    let size = size.min(MAX_SIZE);
+    match name {
+        "ℕ" => Term::Nat(rng.below(size + 1).into()),
+        "ℤ" => rat_to_term(BigRational::from(BigInt::from(rng.integer(size)))),
+        _ => match theory.predicate_sets.get(name) {
+            Some(set) if set.domain != Term::Var(sym(name)) => {
+                draw(Some(&set.domain), size, theory, rng)
+            }
+            _ => {
+                let numer = BigInt::from(rng.integer(size));
+                // Whole numbers half the time; fractions otherwise.
+                let denom = if rng.below(2) == 0 { 1 } else { 2 + rng.below(size.min(10) + 1) };
+                rat_to_term(BigRational::new(numer, BigInt::from(denom)))
+            }
+        },
+    }
+}
+
+/// SplitMix64: small, fast, and good enough to spread test instances.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// An integer in `-size ..= size`.
+    fn integer(&mut self, size: u64) -> i64 {
+        let m = self.below(size + 1) as i64;
+        if self.below(2) == 0 {
+            m
+        } else {
+            -m
+        }
+    }
+}
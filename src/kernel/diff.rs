@@ -0,0 +1,268 @@
+//! Symbolic differentiation, `diff(e, x)`.
+//!
+//! `derivative` differentiates a term with respect to a variable by the sum,
+//! product, quotient, power and chain rules, and simplifies the result with
+//! the current theory. Other variables are constants. `+`, `-`, `·`, `/`,
+//! `^` and `sqrt` are built in; a piecewise term is differentiated branch by
+//! branch, and a sum `∑` term by term when its bounds do not depend on the
+//! variable.
+//!
+//! Any other function of one argument needs an entry in the theory's table
+//! of derivatives, which facts of the form `D(sin) = cos` fill in (see
+//! `Theory::derivative`). The right side of such a fact is a function built
+//! pointwise: every name in it is a function applied to the argument, `id`
+//! being the argument itself, so `D(tan) = 1 + tan ^ 2` and `D(log) = 1 / id`
+//! give `1 + tan(u) ^ 2` and `1 / u` at `u`. The chain rule multiplies by the
+//! derivative of `u`.
+//!
+//! Intermediate terms are built with `0` and `1` absorbed and literals
+//! folded, so that the rules leave no `0 · x` behind for the theory to
+//! remove.
+
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+use crate::kernel::eval::{rat_to_term, term_to_rat};
+use crate::kernel::rewrite::simplify;
+use crate::kernel::term::{as_big_op, as_cond, big_op, cond, sym, Symbol, Term};
+use crate::kernel::theory::Theory;
+
+#[derive(Debug)]
+pub struct DiffError(pub String);
+
+/// The derivative of `t` with respect to `x`, simplified with `theory`.
+pub fn derivative(t: &Term, x: &Symbol, theory: &Theory) -> Result<Term, DiffError> {
+    Ok(simplify(&diff(t, x, theory)?, theory))
+}
+
+/// The derivative of `t` with respect to `x`, unsimplified.
+pub fn diff(t: &Term, x: &Symbol, theory: &Theory) -> Result<Term, DiffError> {
+    if !depends(t, x) {
+        return Ok(zero());
+    }
+    let (head, args) = match t {
+        Term::App(head, args) => (head.as_ref(), args),
+        _ => return Ok(one()),
+    };
+    if let Some((c, a, b)) = as_cond(t) {
+        return Ok(cond(c.clone(), diff(a, x, theory)?, diff(b, x, theory)?));
+    }
+    if let Some((head, var, lo, hi, body)) = as_big_op(t) {
+        if head.as_ref() == "∏" || depends(lo, x) || depends(hi, x) {
+            return Err(DiffError(format!(
+                "cannot differentiate `{head}` with respect to `{x}` unless it is a sum with bounds free of `{x}`"
+            )));
+        }
+        return Ok(big_op(
+            head,
+            var.clone(),
+            lo.clone(),
+            hi.clone(),
+            diff(body, x, theory)?,
+        ));
+    }
+    let d = |u: &Term| diff(u, x, theory);
+    match (head, args.as_slice()) {
+        ("+", _) => {
+            let mut sum = zero();
+            for a in args {
+                sum = add(sum, d(a)?);
+            }
+            Ok(sum)
+        }
+        ("-", [a]) => Ok(neg(d(a)?)),
+        ("-", [a, b]) => Ok(sub(d(a)?, d(b)?)),
+        // (f1 ⋯ fn)' = f1' · f2 ⋯ fn + … + f1 ⋯ fn'
+        ("·", _) => {
+            let mut sum = zero();
+            for (i, a) in args.iter().enumerate() {
+                let mut term = one();
+                for (j, b) in args.iter().enumerate() {
+                    term = mul(term, if i == j { d(a)? } else { b.clone() });
+                }
+                sum = add(sum, term);
+            }
+            Ok(sum)
+        }
+        ("/", [f, g]) if !depends(g, x) => Ok(div(d(f)?, g.clone())),
+        ("/", [f, g]) => Ok(div(
+            sub(mul(d(f)?, g.clone()), mul(f.clone(), d(g)?)),
+            pow(g.clone(), Term::Nat(2u32.into())),
+        )),
+        // (f ^ g)' = g · f' · f ^ (g - 1) + f ^ g · log(f) · g', where the
+        // first term is the power rule and the second drops out for a
+        // constant exponent.
+        ("^", [f, g]) => {
+            let lower = match term_to_rat(g) {
+                Some(r) => rat_to_term(r - BigRational::one()),
+                None => sub(g.clone(), one()),
+            };
+            let power = mul(mul(g.clone(), d(f)?), pow(f.clone(), lower));
+            if !depends(g, x) {
+                return Ok(power);
+            }
+            let exponential = mul(mul(t.clone(), log(f.clone())), d(g)?);
+            Ok(add(power, exponential))
+        }
+        ("sqrt", [u]) => Ok(div(d(u)?, mul(Term::Nat(2u32.into()), t.clone()))),
+        (f, [u]) => match theory.derivative(&sym(f)) {
+            Some(g) => Ok(mul(d(u)?, pointwise(g, u))),
+            None => Err(DiffError(format!(
+                "no derivative known for `{f}`; state one with `fact D({f}) = …`"
+            ))),
+        },
+        (f, _) => Err(DiffError(format!(
+            "cannot differentiate `{f}` with respect to `{x}`"
+        ))),
+    }
+}
+
+/// Whether `x` occurs free in `t`.
+fn depends(t: &Term, x: &Symbol) -> bool {
+    if let Some((_, var, lo, hi, body)) = as_big_op(t) {
+        return depends(lo, x) || depends(hi, x) || (var != x && depends(body, x));
+    }
+    match t {
+        Term::Var(y) => x == y,
+        Term::App(_, args) => args.iter().any(|a| depends(a, x)),
+        _ => false,
+    }
+}
+
+/// The function `g` of a derivative table entry, applied to `u`.
+fn pointwise(g: &Term, u: &Term) -> Term {
+    match g {
+        Term::Var(h) if h.as_ref() == "id" => u.clone(),
+        Term::Var(h) => Term::App(h.clone(), vec![u.clone()]),
+        Term::App(head, args) => {
+            Term::App(head.clone(), args.iter().map(|a| pointwise(a, u)).collect())
+        }
+        _ => g.clone(),
+    }
+}
+
+fn zero() -> Term {
+    Term::Nat(Zero::zero())
+}
+
+fn one() -> Term {
+    Term::Nat(One::one())
+}
+
+fn is(t: &Term, n: i32) -> bool {
+    term_to_rat(t) == Some(BigRational::from_integer(n.into()))
+}
+
+fn log(a: Term) -> Term {
+    Term::App(sym("log"), vec![a])
+}
+
+fn add(a: Term, b: Term) -> Term {
+    match (term_to_rat(&a), term_to_rat(&b)) {
+        (Some(p), Some(q)) => rat_to_term(p + q),
+        _ if is(&a, 0) => b,
+        _ if is(&b, 0) => a,
+        _ => match as_neg(&b) {
+            Some(b) => Term::App(sym("-"), vec![a, b.clone()]),
+            None => Term::App(sym("+"), vec![a, b]),
+        },
+    }
+}
+
+/// `-a`, with double negations removed, the sign of a quotient moved to its
+/// numerator and that of a product taken by its literal coefficient.
+fn neg(a: Term) -> Term {
+    if let Some(p) = term_to_rat(&a) {
+        return rat_to_term(-p);
+    }
+    if let Some(b) = as_neg(&a) {
+        return b.clone();
+    }
+    if let Some((n, d)) = as_quotient(&a) {
+        return Term::App(sym("/"), vec![neg(n.clone()), d.clone()]);
+    }
+    match a {
+        Term::App(head, mut args) if head.as_ref() == "·" && term_to_rat(&args[0]).is_some() => {
+            args[0] = neg(args[0].clone());
+            Term::App(head, args)
+        }
+        a => Term::App(sym("-"), vec![a]),
+    }
+}
+
+fn sub(a: Term, b: Term) -> Term {
+    match (term_to_rat(&a), term_to_rat(&b)) {
+        (Some(p), Some(q)) => rat_to_term(p - q),
+        _ if is(&a, 0) => neg(b),
+        _ if is(&b, 0) => a,
+        _ => Term::App(sym("-"), vec![a, b]),
+    }
+}
+
+/// `a · b`, left-nested: a product on the right is taken apart, so chain
+/// rule factors read `2 · x · cos(x ^ 2)`. Signs and quotients come out of
+/// the product, so `x · -sin(x)` is `-(x · sin(x))` and `1 / x · (1 /
+/// log(x))` is `1 / (x · log(x))`.
+fn mul(a: Term, b: Term) -> Term {
+    match (term_to_rat(&a), term_to_rat(&b)) {
+        (Some(p), Some(q)) => return rat_to_term(p * q),
+        _ if is(&a, 0) || is(&b, 0) => return zero(),
+        _ if is(&a, 1) => return b,
+        _ if is(&b, 1) => return a,
+        _ => {}
+    }
+    if let Some(a) = as_neg(&a) {
+        return neg(mul(a.clone(), b));
+    }
+    if let Some(b) = as_neg(&b) {
+        return neg(mul(a, b.clone()));
+    }
+    if let Some((n, d)) = as_quotient(&b) {
+        return div(mul(a, n.clone()), d.clone());
+    }
+    if let Some((n, d)) = as_quotient(&a) {
+        return div(mul(n.clone(), b), d.clone());
+    }
+    match b {
+        Term::App(head, args) if head.as_ref() == "·" => args.into_iter().fold(a, mul),
+        b => Term::App(sym("·"), vec![a, b]),
+    }
+}
+
+fn div(a: Term, b: Term) -> Term {
+    match (term_to_rat(&a), term_to_rat(&b)) {
+        (Some(p), Some(q)) if !q.is_zero() => rat_to_term(p / q),
+        _ if is(&a, 0) => zero(),
+        _ if is(&b, 1) => a,
+        _ => match as_quotient(&a) {
+            Some((n, d)) => Term::App(sym("/"), vec![n.clone(), mul(d.clone(), b)]),
+            None => Term::App(sym("/"), vec![a, b]),
+        },
+    }
+}
+
+fn as_neg(t: &Term) -> Option<&Term> {
+    match t {
+        Term::App(head, args) if head.as_ref() == "-" && args.len() == 1 => Some(&args[0]),
+        _ => None,
+    }
+}
+
+fn as_quotient(t: &Term) -> Option<(&Term, &Term)> {
+    match t {
+        Term::App(head, args) if head.as_ref() == "/" && args.len() == 2 => {
+            Some((&args[0], &args[1]))
+        }
+        _ => None,
+    }
+}
+
+fn pow(a: Term, n: Term) -> Term {
+    if is(&n, 0) {
+        one()
+    } else if is(&n, 1) {
+        a
+    } else {
+        Term::App(sym("^"), vec![a, n])
+    }
+}
@@ -1,18 +1,39 @@
-use num_bigint::{BigInt, Sign};
+use num_bigint::{BigInt, BigUint, Sign};
 use num_rational::BigRational;
-use num_traits::{One, ToPrimitive, Zero};
+use num_traits::{One, Signed, ToPrimitive, Zero};
 
-use crate::kernel::term::{sym, Term};
+use crate::kernel::algebraic::{self, Algebraic};
+use crate::kernel::approx;
+use crate::kernel::bigop;
+use crate::kernel::modular;
+use crate::kernel::numtheory;
+use crate::kernel::term::{self, as_big_op, as_truth, sym, truth, Term};
 
 #[derive(Debug)]
 pub struct EvalError(pub String);
 
 /// Reduce `t` to a normal form by recursively evaluating arguments and then
-/// folding literal arithmetic on ℕ, ℤ, and ℚ via `reduce`. Non-numeric
-/// applications and free variables are returned unchanged.
+/// folding literal arithmetic on ℕ, ℤ, and ℚ via `reduce`. Comparisons of two
+/// literals become `True`/`False`, as do connectives whose operands are all
+/// truth constants. Finite sums and products with integer literal bounds
+/// are expanded. Non-numeric applications and free variables are returned
+/// unchanged.
 pub fn evaluate(t: &Term) -> Result<Term, EvalError> {
     match t {
-        Term::Nat(_) | Term::Var(_) | Term::Int(_) | Term::Rat(_) => Ok(t.clone()),
+        Term::Nat(_) | Term::Var(_) | Term::Int(_) | Term::Rat(_) | Term::Complex(..) | Term::Residue(..) => {
+            Ok(t.clone())
+        }
+        // Only the selected branch is evaluated, so `if x = 0 then 0 else 1/x`
+        // is fine at `x = 0`.
+        Term::App(head, args) if head.as_ref() == "if" && args.len() == 3 => {
+            let c = evaluate(&args[0])?;
+            match as_truth(&c) {
+                Some(true) => evaluate(&args[1]),
+                Some(false) => evaluate(&args[2]),
+                None => Ok(Term::App(head.clone(), vec![c, args[1].clone(), args[2].clone()])),
+            }
+        }
+        Term::App(..) if as_big_op(t).is_some() => big_op(t),
         Term::App(head, args) => {
             let args: Vec<Term> = args.iter().map(evaluate).collect::<Result<_, _>>()?;
             reduce(head, args)
@@ -20,16 +41,50 @@ pub fn evaluate(t: &Term) -> Result<Term, EvalError> {
     }
 }
 
+/// Evaluate a finite sum or product, term by term when its bounds evaluate
+/// to integer literals.
+fn big_op(t: &Term) -> Result<Term, EvalError> {
+    let (head, var, lo, hi, body) = as_big_op(t).expect("a sum or product");
+    let (lo, hi) = (evaluate(lo)?, evaluate(hi)?);
+    let terms = match bigop::instances(var, &lo, &hi, body)? {
+        Some(terms) => terms,
+        None => return Ok(term::big_op(head, var.clone(), lo, hi, evaluate(body)?)),
+    };
+    let (op, unit) = bigop::operator(head);
+    let mut acc = None;
+    for term in &terms {
+        let x = evaluate(term)?;
+        acc = Some(match acc {
+            Some(a) => reduce(op, vec![a, x])?,
+            None => x,
+        });
+    }
+    Ok(acc.unwrap_or(unit))
+}
+
 /// Apply built-in literal arithmetic. For `+`, `-`, `·`, `/`: when both
 /// arguments are numeric, fold them — promoting to the widest type needed.
-/// For `^`: fold only when base and exponent are both `Nat`.
+/// For `^`: fold numeric powers with `fold_power`, and apply the power laws
+/// `(a ^ r) ^ s = a ^ (r · s)` and `a ^ r · a ^ s = a ^ (r + s)` to radicals
+/// of a positive literal `a`. Integer functions such as `mod` and `gcd` fold
+/// through `numtheory::fold`.
 fn reduce(head: &str, args: Vec<Term>) -> Result<Term, EvalError> {
     if args.len() == 1 && head == "-" {
         if let Some(a) = term_to_rat(&args[0]) {
             return Ok(rat_to_term(-a));
         }
     }
+    if let Some(b) = fold_connective(head, &args) {
+        return Ok(truth(b));
+    }
+    // Arithmetic in ℤ/nℤ comes first: a residue is not a rational.
+    if let Some(t) = modular::fold(head, &args)? {
+        return Ok(t);
+    }
     if args.len() == 2 {
+        if let Some(b) = compare_literals(head, &args[0], &args[1]) {
+            return Ok(truth(b));
+        }
         match head {
             "+" | "-" | "·" | "/" => {
                 if let (Some(a), Some(b)) = (term_to_rat(&args[0]), term_to_rat(&args[1])) {
@@ -49,21 +104,251 @@ fn reduce(head: &str, args: Vec<Term>) -> Result<Term, EvalError> {
                 }
             }
             "^" => {
-                if let (Term::Nat(a), Term::Nat(b)) = (&args[0], &args[1]) {
-                    return match b.to_u32() {
-                        Some(e) => Ok(Term::Nat(a.pow(e))),
-                        None => Err(EvalError(format!(
-                            "exponent {b} too large to evaluate (must fit in u32)"
-                        ))),
-                    };
+                if let Some(t) = fold_literal_power(&args[0], &args[1])? {
+                    return Ok(t);
                 }
             }
             _ => {}
         }
+        if head == "·" {
+            if let Some(t) = fold_radical_product(&args[0], &args[1])? {
+                return Ok(t);
+            }
+        }
+    }
+    if let Some(t) = fold_complex(head, &args)? {
+        return Ok(t);
+    }
+    if let Some(t) = algebraic::fold(head, &args)? {
+        return Ok(t);
+    }
+    if let Some(t) = numtheory::fold(head, &args)? {
+        return Ok(t);
     }
     Ok(Term::App(sym(head), args))
 }
 
+/// Largest exponent, numerator or root index, that `fold_power` expands.
+const MAX_EXPONENT: u32 = 1 << 16;
+
+/// Fold `a ^ e` for rational `a` and `e`. An integer exponent gives a
+/// rational, as does a fractional one when the root is exact
+/// (`8 ^ (2/3) = 4`). Otherwise the result is a normalized radical
+/// `c · m ^ (1/q)`: `c` rational, `m > 1` an integer free of `q`-th powers,
+/// and `q` as small as possible, with square roots written `sqrt(m)`
+/// (`12 ^ (1/2) = 2 · sqrt(3)`,
+/// `4 ^ (1/6) = 2 ^ (1/3)`). `Ok(None)` leaves the power alone: an even root
+/// of a negative number, or an exponent beyond `MAX_EXPONENT`. A negative
+/// power of zero is an error.
+pub(crate) fn fold_power(a: &BigRational, e: &BigRational) -> Result<Option<Term>, EvalError> {
+    let (p, q) = match (e.numer().to_i64(), e.denom().to_u32()) {
+        (Some(p), Some(q)) if p.unsigned_abs() <= u64::from(MAX_EXPONENT) && q <= MAX_EXPONENT => (p, q),
+        _ if !e.is_integer() => return Ok(None),
+        // Huge powers of 0 and ±1 only depend on the exponent's sign and parity.
+        _ if a.is_zero() || a.abs().is_one() => {
+            let parity = if (e.numer() % BigInt::from(2)).is_zero() { 2 } else { 1 };
+            (if e.is_negative() { -parity } else { parity }, 1)
+        }
+        _ => {
+            return Err(EvalError(format!(
+                "exponent {e} too large to evaluate (must be at most {MAX_EXPONENT})"
+            )))
+        }
+    };
+    if a.is_zero() {
+        return match p.signum() {
+            -1 => Err(EvalError("division by zero: negative power of 0".into())),
+            0 => Ok(Some(Term::Nat(1u32.into()))),
+            _ => Ok(Some(Term::Nat(0u32.into()))),
+        };
+    }
+    if a.is_negative() && q % 2 == 0 {
+        return Ok(None);
+    }
+    let sign = if a.is_negative() && p % 2 != 0 { -BigRational::one() } else { BigRational::one() };
+    let a = a.abs();
+    // a ^ (p/q) = a ^ k · a ^ (s/q) with 0 ≤ s < q.
+    let (k, s) = (p.div_euclid(i64::from(q)), p.rem_euclid(i64::from(q)) as u32);
+    let power = |base: &BigRational, k: i64| {
+        let r = BigRational::new(base.numer().pow(k.unsigned_abs() as u32), base.denom().pow(k.unsigned_abs() as u32));
+        if k < 0 { r.recip() } else { r }
+    };
+    let whole = sign * power(&a, k);
+    if s == 0 {
+        return Ok(Some(rat_to_term(whole)));
+    }
+    // (n/d) ^ (s/q) = (n ^ s · d ^ (q - s)) ^ (1/q) / d.
+    let (n, d) = (a.numer().magnitude(), a.denom().magnitude());
+    let m = n.pow(s) * d.pow(q - s);
+    let (root, rest, q) = radical(m, q);
+    let c = whole * BigRational::new(BigInt::from(root), BigInt::from(d.clone()));
+    if rest.is_one() {
+        return Ok(Some(rat_to_term(c)));
+    }
+    let r = if q == 2 {
+        Term::App(sym("sqrt"), vec![Term::Nat(rest)])
+    } else {
+        Term::App(
+            sym("^"),
+            vec![Term::Nat(rest), Term::Rat(BigRational::new(BigInt::one(), BigInt::from(q)))],
+        )
+    };
+    Ok(Some(if c.is_one() {
+        r
+    } else if (-&c).is_one() {
+        Term::App(sym("-"), vec![r])
+    } else {
+        Term::App(sym("·"), vec![rat_to_term(c), r])
+    }))
+}
+
+/// Write `m ^ (1/q)` as `root · rest ^ (1/q')` with `rest` free of `q'`-th
+/// powers and `q'` the smallest index dividing `q`. Prime factors are found
+/// by trial division; a cofactor left over is only taken out when it is
+/// itself a perfect power.
+pub(crate) fn radical(m: BigUint, q: u32) -> (BigUint, BigUint, u32) {
+    let mut factors: Vec<(BigUint, u32)> = Vec::new();
+    let mut rest = m;
+    let mut p = BigUint::from(2u32);
+    let limit = BigUint::from(TRIAL_LIMIT);
+    while p <= limit && &p * &p <= rest {
+        let mut e = 0;
+        while (&rest % &p).is_zero() {
+            rest /= &p;
+            e += 1;
+        }
+        if e > 0 {
+            factors.push((p.clone(), e));
+        }
+        p += 1u32;
+    }
+    if !rest.is_one() {
+        // The cofactor as a perfect power `b ^ j`, with `j` as large as possible.
+        let (b, j) = (2..=q.min(rest.bits() as u32))
+            .rev()
+            .find_map(|j| {
+                let b = rest.nth_root(j);
+                (b.pow(j) == rest).then_some((b, j))
+            })
+            .unwrap_or((rest, 1));
+        factors.push((b, j));
+    }
+    let mut root = BigUint::one();
+    let mut left = Vec::new();
+    for (f, e) in factors {
+        root *= f.pow(e / q);
+        if e % q != 0 {
+            left.push((f, e % q));
+        }
+    }
+    let g = left.iter().fold(q, |g, (_, e)| gcd_u32(g, *e));
+    let rest = left.iter().fold(BigUint::one(), |acc, (f, e)| acc * f.pow(e / g));
+    (root, rest, q / g)
+}
+
+/// Trial divisors tried by `radical` before falling back to perfect-power
+/// checks on the cofactor.
+const TRIAL_LIMIT: u32 = 10_000;
+
+fn gcd_u32(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd_u32(b, a % b)
+    }
+}
+
+/// Fold `base ^ e` for a literal `e` and a literal or radical `base`, using
+/// `(a ^ r) ^ s = a ^ (r · s)`.
+pub(crate) fn fold_literal_power(base: &Term, e: &Term) -> Result<Option<Term>, EvalError> {
+    match (literal_power(base), term_to_rat(e)) {
+        (Some((a, r)), Some(s)) => fold_power(&a, &(r * s)),
+        _ => Ok(None),
+    }
+}
+
+/// Fold a product of literals and radicals by `a ^ r · a ^ s = a ^ (r + s)`
+/// and `a ^ r · b ^ r = (a · b) ^ r`. Both laws need positive bases unless the
+/// exponents are integers.
+pub(crate) fn fold_radical_product(x: &Term, y: &Term) -> Result<Option<Term>, EvalError> {
+    let ((a, r), (b, s)) = match (literal_power(x), literal_power(y)) {
+        (Some(p), Some(q)) if !(p.1.is_integer() && q.1.is_integer()) => (p, q),
+        _ => return Ok(None),
+    };
+    if a == b && a.is_positive() {
+        fold_power(&a, &(r + s))
+    } else if r == s && a.is_positive() && b.is_positive() {
+        fold_power(&(a * b), &r)
+    } else {
+        Ok(None)
+    }
+}
+
+/// `t` as a power `a ^ r` of a literal: a literal `a` itself (with `r = 1`),
+/// or `a ^ r` with a positive literal `a` and a literal `r`, where the power
+/// laws hold.
+fn literal_power(t: &Term) -> Option<(BigRational, BigRational)> {
+    if let Some(a) = term_to_rat(t) {
+        return Some((a, BigRational::one()));
+    }
+    match t {
+        Term::App(f, args) if f.as_ref() == "^" && args.len() == 2 => {
+            let (a, r) = (term_to_rat(&args[0])?, term_to_rat(&args[1])?);
+            a.is_positive().then_some((a, r))
+        }
+        _ => None,
+    }
+}
+
+/// Decide a comparison (`=`, `≠`, `<`, `≤`, `>`, `≥`) between two numeric
+/// literals or algebraic numbers (see `kernel::algebraic`). Other closed real
+/// terms, such as `log(3)` or `sin(1)`, are compared by enclosing them (see
+/// `kernel::approx`), which decides only between different values. Residues
+/// (see `kernel::modular`) only have equality. `None` if `head` is not a
+/// comparison or the sides cannot be compared.
+pub(crate) fn compare_literals(head: &str, a: &Term, b: &Term) -> Option<bool> {
+    if modular::modulus(a).is_some() || modular::modulus(b).is_some() {
+        return modular::compare(head, a, b);
+    }
+    if matches!(a, Term::Complex(..)) || matches!(b, Term::Complex(..)) {
+        // ℂ is not ordered: only equality is decided.
+        let equal = term_to_complex(a)? == term_to_complex(b)?;
+        return match head {
+            "=" => Some(equal),
+            "≠" => Some(!equal),
+            _ => None,
+        };
+    }
+    let order = match (term_to_rat(a), term_to_rat(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => match (Algebraic::from_term(a), Algebraic::from_term(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => approx::compare(a, b)?,
+        },
+    };
+    match head {
+        "=" => Some(order.is_eq()),
+        "≠" => Some(order.is_ne()),
+        "<" => Some(order.is_lt()),
+        "≤" => Some(order.is_le()),
+        ">" => Some(order.is_gt()),
+        "≥" => Some(order.is_ge()),
+        _ => None,
+    }
+}
+
+/// Fold `¬`, `∧`, `∨`, `⇒` when every operand is a truth constant.
+fn fold_connective(head: &str, args: &[Term]) -> Option<bool> {
+    let bs: Vec<bool> = args.iter().map(as_truth).collect::<Option<_>>()?;
+    match (head, bs.as_slice()) {
+        ("¬", [p]) => Some(!p),
+        ("∧", [_, _, ..]) => Some(bs.iter().all(|b| *b)),
+        ("∨", [_, _, ..]) => Some(bs.iter().any(|b| *b)),
+        ("⇒", [p, q]) => Some(!p || *q),
+        _ => None,
+    }
+}
+
 /// Convert a numeric `Term` to `BigRational`. Returns `None` for non-numeric terms.
 pub(crate) fn term_to_rat(t: &Term) -> Option<BigRational> {
     match t {
@@ -74,6 +359,88 @@ pub(crate) fn term_to_rat(t: &Term) -> Option<BigRational> {
     }
 }
 
+/// A Gaussian rational `(re, im)` for `re + im · i`. Rational literals lie on
+/// the real axis.
+pub(crate) type Complex = (BigRational, BigRational);
+
+/// Convert a numeric `Term`, real or complex, to `Complex`.
+pub(crate) fn term_to_complex(t: &Term) -> Option<Complex> {
+    match t {
+        Term::Complex(c) => Some(c.as_ref().clone()),
+        _ => Some((term_to_rat(t)?, BigRational::zero())),
+    }
+}
+
+/// Convert a `Complex` back to a `Term`: a real value becomes a rational
+/// literal via `rat_to_term`.
+pub(crate) fn complex_to_term((re, im): Complex) -> Term {
+    if im.is_zero() {
+        rat_to_term(re)
+    } else {
+        Term::Complex(Box::new((re, im)))
+    }
+}
+
+pub(crate) fn complex_add(a: &Complex, b: &Complex) -> Complex {
+    (&a.0 + &b.0, &a.1 + &b.1)
+}
+
+pub(crate) fn complex_mul(a: &Complex, b: &Complex) -> Complex {
+    (&a.0 * &b.0 - &a.1 * &b.1, &a.0 * &b.1 + &a.1 * &b.0)
+}
+
+/// `1 / a`, `None` for zero.
+fn complex_recip(a: &Complex) -> Option<Complex> {
+    let norm = &a.0 * &a.0 + &a.1 * &a.1;
+    if norm.is_zero() {
+        return None;
+    }
+    Some((&a.0 / &norm, -&a.1 / &norm))
+}
+
+/// Fold `+`, `-`, `·`, `/` and `^` with an integer exponent when some
+/// argument is a complex literal and all are numeric, with `i ^ 2 = -1`.
+pub(crate) fn fold_complex(head: &str, args: &[Term]) -> Result<Option<Term>, EvalError> {
+    if !args.iter().any(|a| matches!(a, Term::Complex(..))) {
+        return Ok(None);
+    }
+    let values: Vec<Complex> = match args.iter().map(term_to_complex).collect() {
+        Some(values) => values,
+        None => return Ok(None),
+    };
+    let zero = (BigRational::zero(), BigRational::zero());
+    let one = (BigRational::one(), BigRational::zero());
+    let result = match (head, values.as_slice()) {
+        ("-", [a]) => (-&a.0, -&a.1),
+        ("-", [a, b]) => complex_add(a, &(-&b.0, -&b.1)),
+        ("+", [_, _, ..]) => values.iter().fold(zero, |acc, v| complex_add(&acc, v)),
+        ("·", [_, _, ..]) => values.iter().fold(one, |acc, v| complex_mul(&acc, v)),
+        ("/", [a, b]) => match complex_recip(b) {
+            Some(r) => complex_mul(a, &r),
+            None => return Err(EvalError("division by zero".into())),
+        },
+        ("^", [a, (e, im)]) if im.is_zero() && e.is_integer() => {
+            let n = match e.to_integer().to_i64() {
+                Some(n) if n.unsigned_abs() <= u64::from(MAX_EXPONENT) => n,
+                _ => return Ok(None),
+            };
+            // `a` is a complex literal, so nonzero.
+            let base = if n < 0 { complex_recip(a).expect("nonzero") } else { a.clone() };
+            let (mut acc, mut sq, mut n) = (one, base, n.unsigned_abs());
+            while n > 0 {
+                if n & 1 == 1 {
+                    acc = complex_mul(&acc, &sq);
+                }
+                sq = complex_mul(&sq, &sq);
+                n >>= 1;
+            }
+            acc
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(complex_to_term(result)))
+}
+
 /// Convert a `BigRational` back to the most specific numeric `Term`:
 /// integer-valued rationals become `Nat` (if non-negative) or `Int`;
 /// non-integer rationals stay as `Rat`.
@@ -0,0 +1,619 @@
+//! Factorization of polynomials over ℤ and ℚ, the inverse of `expand`.
+//!
+//! A polynomial is taken apart in stages. Its rational content comes out
+//! first, then the largest monomial dividing every term. What is left is
+//! split into parts primitive in every indeterminate, by dividing out the
+//! content with respect to each in turn, so `a·x + a·y` gives `a · (x + y)`.
+//! Each part is made square-free with Yun's algorithm.
+//!
+//! A univariate square-free part first loses its rational roots. The rest is
+//! factored over ℤ by the big-prime variant of Zassenhaus' algorithm: modulo a
+//! prime larger than twice any coefficient a factor over ℤ can have
+//! (Mignotte's bound), it factors with Cantor–Zassenhaus, and products of the
+//! modular factors are tried as true factors, fewest first. Multivariate parts
+//! are only split into square-free factors.
+
+use std::collections::BTreeSet;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::kernel::eval::{rat_to_term, term_to_rat};
+use crate::kernel::numtheory::factor_rational;
+use crate::kernel::poly::{grlex, int_gcd, is_ring_head, Monomial, Poly};
+use crate::kernel::ratfun::RatFun;
+use crate::kernel::term::{sym, Term};
+
+/// A polynomial as a constant times powers of nonconstant factors, each with
+/// coprime integer coefficients and a positive leading coefficient.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factorization {
+    pub unit: BigRational,
+    pub factors: Vec<(Poly, u32)>,
+}
+
+impl Factorization {
+    /// `f · g ^ k · c`, the constant last and left out when it is ±1, and the
+    /// whole product negated when it is negative.
+    pub fn to_term(&self) -> Term {
+        if self.factors.is_empty() {
+            return rat_to_term(self.unit.clone());
+        }
+        let mut parts: Vec<Term> = self
+            .factors
+            .iter()
+            .map(|(f, k)| match k {
+                1 => f.to_term(),
+                _ => Term::App(sym("^"), vec![f.to_term(), Term::Nat((*k).into())]),
+            })
+            .collect();
+        if !self.unit.abs().is_one() {
+            parts.push(rat_to_term(self.unit.abs()));
+        }
+        let product = match parts.len() {
+            1 => parts.pop().unwrap(),
+            _ => Term::App(sym("·"), parts),
+        };
+        if self.unit.is_negative() {
+            Term::App(sym("-"), vec![product])
+        } else {
+            product
+        }
+    }
+}
+
+/// Factor `p`; see the module notes for how far each kind of part goes.
+/// Factors are listed by degree, monomials before sums.
+pub fn factor_poly(p: &Poly) -> Factorization {
+    if let Some(c) = p.as_constant() {
+        return Factorization {
+            unit: c,
+            factors: Vec::new(),
+        };
+    }
+    let mut found: Vec<(Poly, u32)> = Vec::new();
+    let (monomial, rest) = split_monomial(&p.primitive().1);
+    for (x, e) in monomial {
+        found.push((Poly::var(x), e));
+    }
+    for part in primitive_parts(rest) {
+        for (s, k) in square_free(&part) {
+            for f in irreducible(&s.primitive().1) {
+                found.push((f, k));
+            }
+        }
+    }
+    let mut factors: Vec<(Poly, u32)> = Vec::new();
+    for (f, k) in found {
+        match factors.iter_mut().find(|(g, _)| *g == f) {
+            Some((_, j)) => *j += k,
+            None => factors.push((f, k)),
+        }
+    }
+    factors.sort_by_cached_key(|(f, _)| order_key(f));
+    let product = factors
+        .iter()
+        .fold(Poly::constant(BigRational::one()), |acc, (f, k)| {
+            &acc * &f.pow(*k)
+        });
+    let unit = p
+        .div_exact(&product)
+        .and_then(|u| u.as_constant())
+        .expect("factors divide");
+    Factorization { unit, factors }
+}
+
+/// `t` with every field subterm factored: a polynomial as by `factor_poly`,
+/// a fraction as the quotient of its factored numerator and denominator (see
+/// `kernel::ratfun`). Arguments of other heads are factored in place. When
+/// the whole of `t` comes out as a rational number, that number is written
+/// as a product of prime powers (`numtheory::factor_rational`).
+pub fn factor(t: &Term) -> Term {
+    let f = factor_term(t);
+    match term_to_rat(&f) {
+        Some(r) => factor_rational(&r),
+        None => f,
+    }
+}
+
+fn factor_term(t: &Term) -> Term {
+    match t {
+        Term::App(head, _) if is_ring_head(head) => {
+            let q = RatFun::from_term(&factor_inside(t));
+            match q.denominator().as_constant() {
+                _ if q.numerator().is_zero() => q.to_term(),
+                Some(c) => factor_poly(&q.numerator().scale(&c.recip())).to_term(),
+                None => Term::App(
+                    sym("/"),
+                    vec![
+                        factor_poly(q.numerator()).to_term(),
+                        factor_poly(q.denominator()).to_term(),
+                    ],
+                ),
+            }
+        }
+        Term::App(head, args) => Term::App(head.clone(), args.iter().map(factor_term).collect()),
+        _ => t.clone(),
+    }
+}
+
+/// Factor the arguments of the non-field subterms of a field term.
+fn factor_inside(t: &Term) -> Term {
+    match t {
+        Term::App(head, args) if is_ring_head(head) => {
+            Term::App(head.clone(), args.iter().map(factor_inside).collect())
+        }
+        _ => factor_term(t),
+    }
+}
+
+/// Monomials before sums, then by degree and coefficients, so `x`, `x - 1`,
+/// `x + 1` come out in that order.
+fn order_key(f: &Poly) -> (u32, usize, Vec<(Monomial, BigRational)>) {
+    let mut terms: Vec<_> = f.terms().map(|(m, c)| (m.clone(), c.clone())).collect();
+    terms.sort_by(|a, b| grlex(&a.0, &b.0));
+    (f.total_degree(), terms.len(), terms)
+}
+
+/// The largest monomial dividing every term of `p`, and the quotient.
+fn split_monomial(p: &Poly) -> (Monomial, Poly) {
+    let mut common: Option<Monomial> = None;
+    for (m, _) in p.terms() {
+        common = Some(match common {
+            None => m.clone(),
+            Some(c) => c
+                .into_iter()
+                .filter_map(|(x, e)| m.get(&x).map(|f| (x, e.min(*f))))
+                .collect(),
+        });
+    }
+    let common = common.unwrap_or_default();
+    let mut divisor = Poly::zero();
+    divisor.add_term(common.clone(), BigRational::one());
+    (common, p.div_exact(&divisor).expect("monomial divides"))
+}
+
+/// Split `p` into factors that are primitive with respect to every
+/// indeterminate.
+fn primitive_parts(p: Poly) -> Vec<Poly> {
+    let mut todo = vec![p];
+    let mut out = Vec::new();
+    while let Some(w) = todo.pop() {
+        let vars: BTreeSet<Term> = w.indeterminates().cloned().collect();
+        let content = vars
+            .iter()
+            .map(|x| w.content_in(x))
+            .find(|c| c.as_constant().is_none());
+        match content {
+            Some(c) => {
+                todo.push(w.div_exact(&c).expect("content divides"));
+                todo.push(c);
+            }
+            None if w.as_constant().is_none() => out.push(w),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Yun's algorithm: `f` as a product of `a ^ i` with the `a` square-free and
+/// pairwise coprime, up to a constant. `f` is primitive in every
+/// indeterminate, so differentiating by one of them loses nothing.
+fn square_free(f: &Poly) -> Vec<(Poly, u32)> {
+    let x = match f.indeterminates().min() {
+        Some(x) => x.clone(),
+        None => return Vec::new(),
+    };
+    let df = f.derivative(&x);
+    let b = f.gcd(&df);
+    let mut c = f.div_exact(&b).expect("gcd divides");
+    let mut d = &df.div_exact(&b).expect("gcd divides") - &c.derivative(&x);
+    let mut out = Vec::new();
+    let mut i = 1;
+    while c.as_constant().is_none() {
+        let a = c.gcd(&d);
+        c = c.div_exact(&a).expect("gcd divides");
+        d = &d.div_exact(&a).expect("gcd divides") - &c.derivative(&x);
+        if a.as_constant().is_none() {
+            out.push((a, i));
+        }
+        i += 1;
+    }
+    out
+}
+
+/// The irreducible factors of a square-free primitive polynomial, as far as
+/// they are found: all of them for one indeterminate, `f` itself otherwise.
+fn irreducible(f: &Poly) -> Vec<Poly> {
+    let vars: BTreeSet<&Term> = f.indeterminates().collect();
+    let x = match vars.into_iter().collect::<Vec<_>>().as_slice() {
+        [x] => (*x).clone(),
+        _ => return vec![f.clone()],
+    };
+    let mut g = dense(f, &x);
+    let mut out = Vec::new();
+    for r in rational_roots(&g) {
+        let linear = vec![-r.numer(), r.denom().clone()];
+        g = div_dense(&g, &linear).expect("root divides");
+        out.push(linear);
+    }
+    if g.len() > 1 {
+        out.extend(zassenhaus(g));
+    }
+    out.iter().map(|d| sparse(d, &x)).collect()
+}
+
+/// Coefficients of `f` in `x`, constant term first. `f` has integer
+/// coefficients and no other indeterminate.
+fn dense(f: &Poly, x: &Term) -> Vec<BigInt> {
+    let mut out = vec![BigInt::zero(); f.total_degree() as usize + 1];
+    for (m, c) in f.terms() {
+        out[m.get(x).copied().unwrap_or(0) as usize] = c.to_integer();
+    }
+    out
+}
+
+fn sparse(f: &[BigInt], x: &Term) -> Poly {
+    let mut out = Poly::zero();
+    for (i, c) in f.iter().enumerate() {
+        let mut m = Monomial::new();
+        if i > 0 {
+            m.insert(x.clone(), i as u32);
+        }
+        out.add_term(m, BigRational::from_integer(c.clone()));
+    }
+    out.primitive().1
+}
+
+/// `a / b` over ℤ, if `b` divides `a` there.
+fn div_dense(a: &[BigInt], b: &[BigInt]) -> Option<Vec<BigInt>> {
+    let (n, m) = (a.len().checked_sub(1)?, b.len() - 1);
+    if n < m {
+        return a.iter().all(Zero::is_zero).then(Vec::new);
+    }
+    let mut r = a.to_vec();
+    let mut q = vec![BigInt::zero(); n - m + 1];
+    for i in (0..=n - m).rev() {
+        if (&r[i + m] % &b[m]).is_zero() {
+            q[i] = &r[i + m] / &b[m];
+            for (j, bj) in b.iter().enumerate() {
+                r[i + j] -= &q[i] * bj;
+            }
+        } else {
+            return None;
+        }
+    }
+    r.iter().all(Zero::is_zero).then_some(q)
+}
+
+/// The rational roots of `f`, found among `±p / q` with `p` dividing the
+/// constant term and `q` the leading coefficient. Skipped (`[]`) when either
+/// is too large to enumerate its divisors.
+fn rational_roots(f: &[BigInt]) -> Vec<BigRational> {
+    let (ps, qs) = match (divisors(&f[0]), divisors(&f[f.len() - 1])) {
+        (Some(ps), Some(qs)) => (ps, qs),
+        _ => return Vec::new(),
+    };
+    let mut candidates = BTreeSet::new();
+    for p in &ps {
+        for q in &qs {
+            let r = BigRational::new((*p).into(), (*q).into());
+            candidates.insert(-r.clone());
+            candidates.insert(r);
+        }
+    }
+    candidates
+        .into_iter()
+        .filter(|r| eval_dense(f, r).is_zero())
+        .collect()
+}
+
+fn eval_dense(f: &[BigInt], x: &BigRational) -> BigRational {
+    f.iter().rev().fold(BigRational::zero(), |acc, c| {
+        acc * x + BigRational::from_integer(c.clone())
+    })
+}
+
+/// Divisors of `n` are only listed up to this magnitude.
+const MAX_DIVISORS_OF: u64 = 1_000_000_000_000;
+
+fn divisors(n: &BigInt) -> Option<Vec<u64>> {
+    let n = n
+        .abs()
+        .to_u64()
+        .filter(|n| (1..=MAX_DIVISORS_OF).contains(n))?;
+    let mut small = Vec::new();
+    let mut large = Vec::new();
+    let mut d = 1;
+    while d * d <= n {
+        if n % d == 0 {
+            small.push(d);
+            if d * d != n {
+                large.push(n / d);
+            }
+        }
+        d += 1;
+    }
+    small.extend(large.into_iter().rev());
+    Some(small)
+}
+
+/// The irreducible factors over ℤ of a square-free primitive `f` of degree at
+/// least one, with positive leading coefficient.
+fn zassenhaus(mut f: Vec<BigInt>) -> Vec<Vec<BigInt>> {
+    let n = f.len() - 1;
+    if n == 1 {
+        return vec![f];
+    }
+    // Mignotte: a factor's coefficients are below 2^n · ‖f‖₂ in magnitude,
+    // and the leading coefficient scales modular products.
+    let norm = f.iter().map(|c| c.abs()).max().unwrap_or_default();
+    let bound: BigInt = BigInt::from(2) * &f[n] * (BigInt::one() << n) * (n + 1) * norm;
+    let mut candidate = bound + 1;
+    let field = loop {
+        let p = next_prime(candidate);
+        let field = Field { p: p.clone() };
+        let fp = field.reduce(&f);
+        if fp.len() == f.len() && field.gcd(&fp, &field.derivative(&fp)).len() == 1 {
+            break field;
+        }
+        candidate = p + 1;
+    };
+    let mut modular = field.factor(&field.monic(&field.reduce(&f)));
+    let mut out = Vec::new();
+    let mut size = 1;
+    while 2 * size <= modular.len() {
+        let lead = f[f.len() - 1].clone();
+        let hit = subsets(modular.len(), size).find_map(|s| {
+            let product = s
+                .iter()
+                .fold(vec![lead.clone()], |acc, &i| field.mul(&acc, &modular[i]));
+            let g = primitive_dense(&field.symmetric(&product));
+            div_dense(&f, &g).map(|q| (s, g, q))
+        });
+        match hit {
+            Some((s, g, q)) => {
+                out.push(g);
+                f = q;
+                for i in s.into_iter().rev() {
+                    modular.remove(i);
+                }
+            }
+            None => size += 1,
+        }
+    }
+    out.push(f);
+    out
+}
+
+fn primitive_dense(f: &[BigInt]) -> Vec<BigInt> {
+    let g = f.iter().fold(BigInt::zero(), |g, c| int_gcd(g, c.clone()));
+    let g = if f[f.len() - 1].is_negative() { -g } else { g };
+    f.iter().map(|c| c / &g).collect()
+}
+
+/// The `k`-element subsets of `0..n`, in lexicographic order.
+fn subsets(n: usize, k: usize) -> impl Iterator<Item = Vec<usize>> {
+    let mut next = Some((0..k).collect::<Vec<_>>());
+    std::iter::from_fn(move || {
+        let current = next.take()?;
+        let mut s = current.clone();
+        if let Some(i) = (0..k).rev().find(|&i| s[i] < n - k + i) {
+            s[i] += 1;
+            for j in i + 1..k {
+                s[j] = s[j - 1] + 1;
+            }
+            next = Some(s);
+        }
+        Some(current)
+    })
+}
+
+/// The least prime at least `n`.
+fn next_prime(mut n: BigInt) -> BigInt {
+    while !is_prime(&n) {
+        n += 1;
+    }
+    n
+}
+
+/// Miller–Rabin with the first twelve primes as bases, which is exact below
+/// 3.3 · 10^24 and a very strong test above.
+pub(crate) fn is_prime(n: &BigInt) -> bool {
+    const BASES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < &BigInt::from(2) {
+        return false;
+    }
+    for b in BASES {
+        if (n % b).is_zero() {
+            return n == &BigInt::from(b);
+        }
+    }
+    let m: BigInt = n - 1;
+    let s = m.trailing_zeros().unwrap_or(0);
+    let d = &m >> s;
+    BASES.iter().all(|&b| {
+        let mut x = BigInt::from(b).modpow(&d, n);
+        if x.is_one() || x == m {
+            return true;
+        }
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == m {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Dense polynomials over ℤ/p for an odd prime `p`, constant term first and
+/// without trailing zeros.
+struct Field {
+    p: BigInt,
+}
+
+impl Field {
+    fn modp(&self, c: &BigInt) -> BigInt {
+        let r = c % &self.p;
+        if r.is_negative() {
+            r + &self.p
+        } else {
+            r
+        }
+    }
+
+    fn reduce(&self, a: &[BigInt]) -> Vec<BigInt> {
+        let mut out: Vec<BigInt> = a.iter().map(|c| self.modp(c)).collect();
+        while out.last().is_some_and(Zero::is_zero) {
+            out.pop();
+        }
+        out
+    }
+
+    /// Coefficients in `(-p/2, p/2]`.
+    fn symmetric(&self, a: &[BigInt]) -> Vec<BigInt> {
+        let half: BigInt = &self.p >> 1;
+        a.iter()
+            .map(|c| if c > &half { c - &self.p } else { c.clone() })
+            .collect()
+    }
+
+    fn inverse(&self, c: &BigInt) -> BigInt {
+        c.modpow(&(&self.p - 2), &self.p)
+    }
+
+    fn monic(&self, a: &[BigInt]) -> Vec<BigInt> {
+        match a.last() {
+            Some(lead) => {
+                let inv = self.inverse(lead);
+                self.reduce(&a.iter().map(|c| c * &inv).collect::<Vec<_>>())
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn sub(&self, a: &[BigInt], b: &[BigInt]) -> Vec<BigInt> {
+        let mut out = vec![BigInt::zero(); a.len().max(b.len())];
+        for (i, c) in a.iter().enumerate() {
+            out[i] += c;
+        }
+        for (i, c) in b.iter().enumerate() {
+            out[i] -= c;
+        }
+        self.reduce(&out)
+    }
+
+    fn mul(&self, a: &[BigInt], b: &[BigInt]) -> Vec<BigInt> {
+        if a.is_empty() || b.is_empty() {
+            return Vec::new();
+        }
+        let mut out = vec![BigInt::zero(); a.len() + b.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                out[i + j] += x * y;
+            }
+        }
+        self.reduce(&out)
+    }
+
+    fn derivative(&self, a: &[BigInt]) -> Vec<BigInt> {
+        self.reduce(
+            &a.iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, c)| c * i)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Quotient and remainder of `a` by a nonzero `b`.
+    fn div_rem(&self, a: &[BigInt], b: &[BigInt]) -> (Vec<BigInt>, Vec<BigInt>) {
+        let m = b.len() - 1;
+        let inv = self.inverse(&b[m]);
+        let mut r = a.to_vec();
+        if r.len() <= m {
+            return (Vec::new(), r);
+        }
+        let mut q = vec![BigInt::zero(); r.len() - m];
+        for i in (0..q.len()).rev() {
+            let c = self.modp(&(&r[i + m] * &inv));
+            for (j, bj) in b.iter().enumerate() {
+                r[i + j] = self.modp(&(&r[i + j] - &c * bj));
+            }
+            q[i] = c;
+        }
+        (self.reduce(&q), self.reduce(&r))
+    }
+
+    fn gcd(&self, a: &[BigInt], b: &[BigInt]) -> Vec<BigInt> {
+        let (mut a, mut b) = (a.to_vec(), b.to_vec());
+        while !b.is_empty() {
+            let r = self.div_rem(&a, &b).1;
+            a = b;
+            b = r;
+        }
+        self.monic(&a)
+    }
+
+    /// `a ^ e` modulo `m`.
+    fn pow_mod(&self, a: &[BigInt], e: &BigInt, m: &[BigInt]) -> Vec<BigInt> {
+        let a = self.div_rem(a, m).1;
+        let mut out = vec![BigInt::one()];
+        for i in (0..e.bits()).rev() {
+            out = self.div_rem(&self.mul(&out, &out), m).1;
+            if e.bit(i) {
+                out = self.div_rem(&self.mul(&out, &a), m).1;
+            }
+        }
+        out
+    }
+
+    /// The monic irreducible factors of a monic square-free `f`: distinct-
+    /// degree factorization, then Cantor–Zassenhaus equal-degree splitting.
+    fn factor(&self, f: &[BigInt]) -> Vec<Vec<BigInt>> {
+        let x = vec![BigInt::zero(), BigInt::one()];
+        let mut f = f.to_vec();
+        let mut h = x.clone();
+        let mut out = Vec::new();
+        let mut d = 0;
+        while f.len() > 2 * (d + 1) {
+            d += 1;
+            h = self.pow_mod(&h, &self.p, &f);
+            let g = self.gcd(&self.sub(&h, &x), &f);
+            if g.len() > 1 {
+                f = self.div_rem(&f, &g).0;
+                h = self.div_rem(&h, &f).1;
+                out.extend(self.split(g, d));
+            }
+        }
+        if f.len() > 1 {
+            out.push(f);
+        }
+        out
+    }
+
+    /// Split a product of irreducible factors of degree `d`: for
+    /// `a = x + c`, `gcd(a ^ ((p^d - 1)/2) - 1, g)` is a proper factor for
+    /// about half of all `c`.
+    fn split(&self, g: Vec<BigInt>, d: usize) -> Vec<Vec<BigInt>> {
+        if g.len() - 1 == d {
+            return vec![g];
+        }
+        let e: BigInt = (num_traits::pow(self.p.clone(), d) - 1) >> 1;
+        let mut c = BigInt::zero();
+        loop {
+            let a = vec![c.clone(), BigInt::one()];
+            let b = self.sub(&self.pow_mod(&a, &e, &g), &[BigInt::one()]);
+            let h = self.gcd(&b, &g);
+            if h.len() > 1 && h.len() < g.len() {
+                let rest = self.div_rem(&g, &h).0;
+                let mut out = self.split(h, d);
+                out.extend(self.split(rest, d));
+                return out;
+            }
+            c += 1;
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Gröbner bases over ℚ, for deciding whether a polynomial equation follows
+//! from polynomial hypotheses: `prove p = q assuming h1 = 0, …`.
+//!
+//! `p - q` follows from the hypotheses when it lies in the ideal they
+//! generate, i.e. `p - q = c1 · h1 + … + cn · hn` for some polynomials `ci`.
+//! Division by a Gröbner basis of the ideal decides this: the remainder is
+//! zero exactly for members. The basis comes from Buchberger's algorithm,
+//! which reduces the pair with the smallest leading lcm first and skips pairs
+//! whose leading monomials are coprime. Every basis element carries its
+//! expression in the hypotheses, so a member comes with the cofactors `ci` as
+//! a certificate.
+//!
+//! Three monomial orders are available: `lex`, `grevlex`, and `kbo`. The
+//! `kbo` order weighs each indeterminate by its KBO symbol count and breaks
+//! ties lexicographically, ranking indeterminates as KBO would: heavier ones
+//! first, then those with higher-precedence heads, then in term order.
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+use num_rational::BigRational;
+use num_traits::One;
+
+use crate::ast::MonomialOrder;
+use crate::kernel::kbo::{prec_cmp, weight};
+use crate::kernel::poly::{degree, lex, monomial_div, Monomial, Poly};
+use crate::kernel::term::Term;
+
+/// S-polynomials reduced at most, before Buchberger's algorithm gives up.
+pub const MAX_PAIRS: usize = 1000;
+
+/// Outcome of `ideal_membership`.
+#[derive(Debug)]
+pub enum Membership {
+    /// The goal is the sum of these cofactors times the hypotheses, in order.
+    Member(Vec<Poly>),
+    /// The goal is not in the ideal; this is its nonzero remainder on
+    /// division by a Gröbner basis.
+    NotMember(Poly),
+    /// The basis was not found within `MAX_PAIRS` reductions.
+    Unknown,
+}
+
+/// Compare monomials under `order`.
+pub fn compare(order: MonomialOrder, a: &Monomial, b: &Monomial) -> Ordering {
+    match order {
+        MonomialOrder::Lex => lex(a, b),
+        MonomialOrder::Grevlex => degree(a).cmp(&degree(b)).then_with(|| revlex(a, b)),
+        MonomialOrder::Kbo => kbo_weight(a)
+            .cmp(&kbo_weight(b))
+            .then_with(|| ranked_lex(a, b)),
+    }
+}
+
+/// The grevlex tie-break: at the last indeterminate, in term order, where
+/// the exponents differ, the monomial with the smaller exponent is larger.
+fn revlex(a: &Monomial, b: &Monomial) -> Ordering {
+    let vars: BTreeSet<&Term> = a.keys().chain(b.keys()).collect();
+    for x in vars.into_iter().rev() {
+        let (ea, eb) = (exponent(a, x), exponent(b, x));
+        if ea != eb {
+            return eb.cmp(&ea);
+        }
+    }
+    Ordering::Equal
+}
+
+fn kbo_weight(m: &Monomial) -> u64 {
+    m.iter().map(|(x, e)| weight(x) * u64::from(*e)).sum()
+}
+
+/// `lex`, with indeterminates ranked by `rank` rather than by term order.
+fn ranked_lex(a: &Monomial, b: &Monomial) -> Ordering {
+    let mut vars: Vec<&Term> = a.keys().chain(b.keys()).collect();
+    vars.sort_by(|x, y| rank(x, y));
+    vars.dedup();
+    for x in vars {
+        let (ea, eb) = (exponent(a, x), exponent(b, x));
+        if ea != eb {
+            return ea.cmp(&eb);
+        }
+    }
+    Ordering::Equal
+}
+
+/// `Less` when `x` is the more significant indeterminate.
+fn rank(x: &Term, y: &Term) -> Ordering {
+    weight(y)
+        .cmp(&weight(x))
+        .then_with(|| match (x, y) {
+            (Term::App(f, _), Term::App(g, _)) => prec_cmp(g, f),
+            (Term::App(..), _) => Ordering::Less,
+            (_, Term::App(..)) => Ordering::Greater,
+            _ => Ordering::Equal,
+        })
+        .then_with(|| x.cmp(y))
+}
+
+fn exponent(m: &Monomial, x: &Term) -> u32 {
+    m.get(x).copied().unwrap_or(0)
+}
+
+/// A basis polynomial, monic, with its expression in the hypotheses.
+struct Element {
+    poly: Poly,
+    lead: Monomial,
+    repr: Vec<Poly>,
+}
+
+impl Element {
+    fn new(poly: Poly, repr: Vec<Poly>, order: MonomialOrder) -> Element {
+        let (lead, c) = leading(&poly, order).expect("nonzero");
+        let inv = c.recip();
+        Element {
+            poly: poly.scale(&inv),
+            lead,
+            repr: repr.iter().map(|r| r.scale(&inv)).collect(),
+        }
+    }
+}
+
+fn leading(p: &Poly, order: MonomialOrder) -> Option<(Monomial, BigRational)> {
+    p.terms()
+        .max_by(|a, b| compare(order, a.0, b.0))
+        .map(|(m, c)| (m.clone(), c.clone()))
+}
+
+fn term(m: Monomial, c: BigRational) -> Poly {
+    let mut p = Poly::zero();
+    p.add_term(m, c);
+    p
+}
+
+fn lcm(a: &Monomial, b: &Monomial) -> Monomial {
+    let mut out = a.clone();
+    for (x, e) in b {
+        let slot = out.entry(x.clone()).or_insert(0);
+        *slot = (*slot).max(*e);
+    }
+    out
+}
+
+/// A Gröbner basis of the ideal generated by `hyps`, or `None` if
+/// Buchberger's algorithm runs out of budget.
+pub fn groebner_basis(hyps: &[Poly], order: MonomialOrder) -> Option<Vec<Poly>> {
+    Some(basis(hyps, order)?.into_iter().map(|e| e.poly).collect())
+}
+
+fn basis(hyps: &[Poly], order: MonomialOrder) -> Option<Vec<Element>> {
+    let mut elems = Vec::new();
+    for (i, h) in hyps.iter().enumerate() {
+        if !h.is_zero() {
+            let mut repr = vec![Poly::zero(); hyps.len()];
+            repr[i] = Poly::constant(BigRational::one());
+            elems.push(Element::new(h.clone(), repr, order));
+        }
+    }
+    let mut pairs: Vec<(usize, usize)> = (0..elems.len())
+        .flat_map(|j| (0..j).map(move |i| (i, j)))
+        .collect();
+    let mut budget = MAX_PAIRS;
+    while !pairs.is_empty() {
+        let k = (0..pairs.len())
+            .min_by(|&a, &b| {
+                let lcm_of = |(i, j): (usize, usize)| lcm(&elems[i].lead, &elems[j].lead);
+                compare(order, &lcm_of(pairs[a]), &lcm_of(pairs[b]))
+            })
+            .expect("nonempty");
+        let (i, j) = pairs.swap_remove(k);
+        let l = lcm(&elems[i].lead, &elems[j].lead);
+        if degree(&l) == degree(&elems[i].lead) + degree(&elems[j].lead) {
+            // Coprime leading monomials: the S-polynomial reduces to zero.
+            continue;
+        }
+        budget = budget.checked_sub(1)?;
+        let ti = term(
+            monomial_div(&l, &elems[i].lead).expect("lcm"),
+            BigRational::one(),
+        );
+        let tj = term(
+            monomial_div(&l, &elems[j].lead).expect("lcm"),
+            BigRational::one(),
+        );
+        let s = &(&ti * &elems[i].poly) - &(&tj * &elems[j].poly);
+        let mut repr: Vec<Poly> = elems[i]
+            .repr
+            .iter()
+            .zip(&elems[j].repr)
+            .map(|(a, b)| &(&ti * a) - &(&tj * b))
+            .collect();
+        let (quotients, r) = divide(&s, &elems, order);
+        if r.is_zero() {
+            continue;
+        }
+        for (q, e) in quotients.iter().zip(&elems) {
+            for (slot, ek) in repr.iter_mut().zip(&e.repr) {
+                *slot = &*slot - &(q * ek);
+            }
+        }
+        let m = elems.len();
+        pairs.extend((0..m).map(|k| (k, m)));
+        elems.push(Element::new(r, repr, order));
+    }
+    Some(elems)
+}
+
+/// Divide `f` by the basis: quotients, one per element, and the remainder,
+/// none of whose terms is divisible by a leading monomial.
+fn divide(f: &Poly, elems: &[Element], order: MonomialOrder) -> (Vec<Poly>, Poly) {
+    let mut quotients = vec![Poly::zero(); elems.len()];
+    let mut rest = f.clone();
+    let mut remainder = Poly::zero();
+    while let Some((m, c)) = leading(&rest, order) {
+        let step = elems
+            .iter()
+            .enumerate()
+            .find_map(|(k, e)| monomial_div(&m, &e.lead).map(|q| (k, term(q, c.clone()))));
+        match step {
+            Some((k, t)) => {
+                rest = &rest - &(&t * &elems[k].poly);
+                quotients[k] = &quotients[k] + &t;
+            }
+            None => {
+                let t = term(m, c);
+                rest = &rest - &t;
+                remainder = &remainder + &t;
+            }
+        }
+    }
+    (quotients, remainder)
+}
+
+/// Decide whether `goal` lies in the ideal generated by `hyps`.
+pub fn ideal_membership(goal: &Poly, hyps: &[Poly], order: MonomialOrder) -> Membership {
+    let elems = match basis(hyps, order) {
+        Some(elems) => elems,
+        None => return Membership::Unknown,
+    };
+    let (quotients, r) = divide(goal, &elems, order);
+    if !r.is_zero() {
+        return Membership::NotMember(r);
+    }
+    let mut cofactors = vec![Poly::zero(); hyps.len()];
+    for (q, e) in quotients.iter().zip(&elems) {
+        for (slot, ek) in cofactors.iter_mut().zip(&e.repr) {
+            *slot = &*slot + &(q * ek);
+        }
+    }
+    Membership::Member(cofactors)
+}
+
+impl Membership {
+    /// Whether the goal was shown to follow.
+    pub fn is_member(&self) -> bool {
+        matches!(self, Membership::Member(_))
+    }
+}
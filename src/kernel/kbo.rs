@@ -28,10 +28,12 @@
 //! built-ins as `= < + = - < · = / < ^` (mirroring surface precedence);
 //! other heads fall back to byte-wise string order and rank above the built-ins.
 
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
 use num_bigint::BigInt;
 use num_rational::BigRational;
+use num_traits::Zero;
 
 use crate::kernel::term::{Symbol, Term};
 
@@ -91,30 +93,36 @@ fn kbo_gt(s: &Term, t: &Term) -> bool {
         // App > all numeric literals
         (Term::App(_, _), _) => true,
         (_, Term::App(_, _)) => false,
-        // All remaining cases: both terms are numeric — compare by value
-        _ => num_to_rat(s) > num_to_rat(t),
+        // Residues above the other literals, then by modulus and value
+        (Term::Residue(a), Term::Residue(b)) => (&a.1, &a.0) > (&b.1, &b.0),
+        (Term::Residue(_), _) => true,
+        (_, Term::Residue(_)) => false,
+        // All remaining cases: both terms are numeric — compare by value,
+        // real part first, so that the order stays total on ℂ
+        _ => num_to_complex(s) > num_to_complex(t),
     }
 }
 
-fn num_to_rat(t: &Term) -> BigRational {
+fn num_to_complex(t: &Term) -> (BigRational, BigRational) {
     match t {
-        Term::Nat(n) => BigRational::from(BigInt::from(n.clone())),
-        Term::Int(n) => BigRational::from(n.clone()),
-        Term::Rat(r) => r.clone(),
+        Term::Nat(n) => (BigRational::from(BigInt::from(n.clone())), BigRational::zero()),
+        Term::Int(n) => (BigRational::from(n.clone()), BigRational::zero()),
+        Term::Rat(r) => (r.clone(), BigRational::zero()),
+        Term::Complex(c) => c.as_ref().clone(),
         _ => unreachable!(),
     }
 }
 
-fn weight(t: &Term) -> u64 {
+pub(crate) fn weight(t: &Term) -> u64 {
     match t {
-        Term::Nat(_) | Term::Var(_) | Term::Int(_) | Term::Rat(_) => 1,
+        Term::Nat(_) | Term::Var(_) | Term::Int(_) | Term::Rat(_) | Term::Complex(..) | Term::Residue(..) => 1,
         Term::App(_, args) => 1 + args.iter().map(weight).sum::<u64>(),
     }
 }
 
 fn var_count(t: &Term, x: &Symbol) -> u64 {
     match t {
-        Term::Nat(_) | Term::Int(_) | Term::Rat(_) => 0,
+        Term::Nat(_) | Term::Int(_) | Term::Rat(_) | Term::Complex(..) | Term::Residue(..) => 0,
         Term::Var(s) => {
             if s == x {
                 1
@@ -128,7 +136,7 @@ fn var_count(t: &Term, x: &Symbol) -> u64 {
 
 fn collect_vars(t: &Term, out: &mut HashSet<Symbol>) {
     match t {
-        Term::Nat(_) | Term::Int(_) | Term::Rat(_) => {}
+        Term::Nat(_) | Term::Int(_) | Term::Rat(_) | Term::Complex(..) | Term::Residue(..) => {}
         Term::Var(s) => {
             out.insert(s.clone());
         }
@@ -156,6 +164,18 @@ fn prec_gt(f: &Symbol, g: &Symbol) -> bool {
     }
 }
 
+/// `prec_gt` as a total preorder: heads at the same built-in level are
+/// `Equal`.
+pub(crate) fn prec_cmp(f: &Symbol, g: &Symbol) -> Ordering {
+    if prec_gt(f, g) {
+        Ordering::Greater
+    } else if prec_gt(g, f) {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
+}
+
 fn builtin_prec(s: &Symbol) -> Option<u8> {
     match s.as_ref() {
         "=" => Some(0),
@@ -0,0 +1,265 @@
+//! Propositional folding used by `simplify`. Propositions are ordinary terms:
+//! the truth constants are the nullary applications built by `term::truth`,
+//! and the connectives are applications of `¬`, `∧`, `∨` and `⇒`.
+//!
+//! The folds are structural and consult no facts, so they are sound in any
+//! theory: truth constants are absorbed, `∧`/`∨` are flattened with duplicates
+//! and absorbed operands dropped, `¬` is pushed inward to negation normal form
+//! (De Morgan), and relations between numeric literals, or between
+//! syntactically equal sides that are defined everywhere, are decided.
+//! Pushing `¬` inward can make a term heavier, so these steps live outside
+//! the KBO-oriented rule set; they terminate because each one moves a `¬`
+//! strictly closer to the leaves.
+//!
+//! Piecewise terms `if(c, a, b)` (`term::cond`) are folded here too: a decided
+//! condition selects its branch, and `piecewise` collapses equal branches.
+
+use num_traits::{Signed, Zero};
+
+use crate::kernel::eval::{compare_literals, term_to_rat};
+use crate::kernel::modular;
+use crate::kernel::term::{as_cond, as_truth, cond, sym, truth, Symbol, Term};
+
+/// Fold the application `head(args)`, whose arguments are already simplified.
+/// Heads that are not propositional are rebuilt unchanged.
+pub(crate) fn logic_fold(head: &Symbol, mut args: Vec<Term>) -> Term {
+    match (head.as_ref(), args.len()) {
+        ("¬", 1) => not(args.pop().unwrap()),
+        ("∧", n) | ("∨", n) if n >= 2 => junction(head.as_ref() == "∧", args),
+        ("⇒", 2) => {
+            let q = args.pop().unwrap();
+            let p = args.pop().unwrap();
+            implies(p, q)
+        }
+        ("if", 3) => {
+            let b = args.pop().unwrap();
+            let a = args.pop().unwrap();
+            let c = args.pop().unwrap();
+            match as_truth(&c) {
+                Some(true) => a,
+                Some(false) => b,
+                None => piecewise(c, a, b),
+            }
+        }
+        ("∈", 2) => match number_set_membership(&args[0], &args[1]) {
+            Some(b) => truth(b),
+            None => Term::App(head.clone(), args),
+        },
+        (_, 2) => {
+            let b = args.pop().unwrap();
+            let a = args.pop().unwrap();
+            relation(head, a, b)
+        }
+        _ => Term::App(head.clone(), args),
+    }
+}
+
+/// The atom that holds exactly when `atom` does not. Comparisons are taken to
+/// be total orders, so the complement of `x > 0` is `x ≤ 0`.
+pub(crate) fn complement(atom: &Term) -> Term {
+    if let Term::App(head, args) = atom {
+        let flipped = match head.as_ref() {
+            "=" => Some("≠"),
+            "≠" => Some("="),
+            "<" => Some("≥"),
+            "≥" => Some("<"),
+            ">" => Some("≤"),
+            "≤" => Some(">"),
+            _ => None,
+        };
+        if let (Some(f), 2) = (flipped, args.len()) {
+            return Term::App(sym(f), args.clone());
+        }
+    }
+    not(atom.clone())
+}
+
+/// `if atom then a else b`, collapsed where the branches allow: equal
+/// branches, truth-constant branches of a proposition, and a nested split
+/// that shares a branch with this one.
+pub(crate) fn piecewise(atom: Term, a: Term, b: Term) -> Term {
+    if a == b {
+        return a;
+    }
+    let and = |p: Term, q: Term| junction(true, vec![p, q]);
+    let or = |p: Term, q: Term| junction(false, vec![p, q]);
+    match (as_truth(&a), as_truth(&b)) {
+        (Some(true), _) => return or(atom, b),
+        (Some(false), _) => return and(complement(&atom), b),
+        (_, Some(true)) => return or(complement(&atom), a),
+        (_, Some(false)) => return and(atom, a),
+        _ => {}
+    }
+    // if p then (if q then x else y) else y  =  if p ∧ q then x else y
+    if let Some((q, x, y)) = as_cond(&a) {
+        if *y == b {
+            return cond(and(atom, q.clone()), x.clone(), b);
+        }
+    }
+    // if p then x else (if q then x else y)  =  if p ∨ q then x else y
+    if let Some((q, x, y)) = as_cond(&b) {
+        if *x == a {
+            return cond(or(atom, q.clone()), a, y.clone());
+        }
+    }
+    cond(atom, a, b)
+}
+
+/// Decide `elem ∈ set` for a numeric literal, real or complex, and one of
+/// the number sets `ℕ`, `ℤ`, `ℚ`, `ℝ`, `ℂ`, or a residue and a set `ℤ/nℤ`.
+pub(crate) fn number_set_membership(elem: &Term, set: &Term) -> Option<bool> {
+    if let Some(b) = modular::membership(elem, set) {
+        return Some(b);
+    }
+    let name = match set {
+        Term::Var(s) => s.as_ref(),
+        _ => return None,
+    };
+    // A complex literal has a nonzero imaginary part, so it is only in `ℂ`.
+    if let Term::Complex(_) = elem {
+        return matches!(name, "ℕ" | "ℤ" | "ℚ" | "ℝ" | "ℂ").then_some(name == "ℂ");
+    }
+    let r = term_to_rat(elem)?;
+    match name {
+        "ℕ" => Some(r.is_integer() && *r.numer() >= 0.into()),
+        "ℤ" => Some(r.is_integer()),
+        "ℚ" | "ℝ" | "ℂ" => Some(true),
+        _ => None,
+    }
+}
+
+/// `¬p`, pushed through constants, double negation, the connectives and
+/// (in)equality.
+fn not(p: Term) -> Term {
+    if let Some(b) = as_truth(&p) {
+        return truth(!b);
+    }
+    match p {
+        Term::App(head, mut args) => match (head.as_ref(), args.len()) {
+            ("¬", 1) => args.pop().unwrap(),
+            ("∧", n) | ("∨", n) if n >= 2 => {
+                let and = head.as_ref() == "∨";
+                junction(and, args.into_iter().map(not).collect())
+            }
+            ("⇒", 2) => {
+                let q = args.pop().unwrap();
+                let p = args.pop().unwrap();
+                junction(true, vec![p, not(q)])
+            }
+            ("=", 2) | ("≠", 2) => {
+                let flipped = if head.as_ref() == "=" { "≠" } else { "=" };
+                let b = args.pop().unwrap();
+                let a = args.pop().unwrap();
+                relation(&sym(flipped), a, b)
+            }
+            _ => Term::App(sym("¬"), vec![Term::App(head, args)]),
+        },
+        other => Term::App(sym("¬"), vec![other]),
+    }
+}
+
+/// An n-ary conjunction (`and`) or disjunction of `args`.
+fn junction(and: bool, args: Vec<Term>) -> Term {
+    let head = sym(if and { "∧" } else { "∨" });
+    let dual = if and { "∨" } else { "∧" };
+    let mut flat: Vec<Term> = Vec::with_capacity(args.len());
+    for a in args {
+        match a {
+            Term::App(h, sub) if h == head => flat.extend(sub),
+            other => flat.push(other),
+        }
+    }
+    let mut kept: Vec<Term> = Vec::with_capacity(flat.len());
+    for a in flat {
+        match as_truth(&a) {
+            // The unit (`True` for ∧, `False` for ∨) disappears …
+            Some(b) if b == and => continue,
+            // … and the zero decides the whole junction.
+            Some(_) => return truth(!and),
+            None => {}
+        }
+        if !kept.contains(&a) {
+            kept.push(a);
+        }
+    }
+    // Complement: p ∧ ¬p = False, p ∨ ¬p = True
+    if kept.iter().any(|a| kept.contains(&not(a.clone()))) {
+        return truth(!and);
+    }
+    // Absorption: p ∧ (p ∨ q) = p, p ∨ (p ∧ q) = p
+    let absorbed: Vec<bool> = kept
+        .iter()
+        .map(|a| match a {
+            Term::App(h, sub) if h.as_ref() == dual => {
+                sub.iter().any(|s| kept.iter().any(|k| k != a && k == s))
+            }
+            _ => false,
+        })
+        .collect();
+    let mut it = absorbed.into_iter();
+    kept.retain(|_| !it.next().unwrap());
+    match kept.len() {
+        0 => truth(and),
+        1 => kept.pop().unwrap(),
+        _ => Term::App(head, kept),
+    }
+}
+
+/// Whether some subterm of `t` may have no value: a division, `div` or `mod`
+/// whose divisor is not a nonzero literal, or a power with a negative or
+/// symbolic exponent whose base is not one. The check is syntactic, so
+/// `x / x` counts, while `f(1 / 2)` does not.
+fn may_be_undefined(t: &Term) -> bool {
+    let nonzero = |d: &Term| term_to_rat(d).is_some_and(|r| !r.is_zero());
+    match t {
+        Term::App(head, args) => {
+            let partial = match (head.as_ref(), args.as_slice()) {
+                ("/" | "div" | "mod", [_, d]) => !nonzero(d),
+                ("^", [b, e]) => {
+                    !nonzero(b) && term_to_rat(e).is_none_or(|r| r.is_negative())
+                }
+                _ => false,
+            };
+            partial || args.iter().any(may_be_undefined)
+        }
+        _ => false,
+    }
+}
+
+fn implies(p: Term, q: Term) -> Term {
+    match (as_truth(&p), as_truth(&q)) {
+        (Some(true), _) => q,
+        (Some(false), _) | (_, Some(true)) => truth(true),
+        (_, Some(false)) => not(p),
+        _ if p == q || operand_of("∧", &q, &p) || operand_of("∨", &p, &q) => truth(true),
+        _ => Term::App(sym("⇒"), vec![p, q]),
+    }
+}
+
+/// Whether `t` is an operand of the junction `j` with head `head`.
+fn operand_of(head: &str, t: &Term, j: &Term) -> bool {
+    matches!(j, Term::App(h, sub) if h.as_ref() == head && sub.contains(t))
+}
+
+/// A binary relation: decided for literals and for syntactically equal sides.
+/// Equal sides that may be undefined, such as `1/0 = 1/0` or `x/x = x/x`,
+/// may have no value to compare, so the relation is left alone.
+fn relation(head: &Symbol, a: Term, b: Term) -> Term {
+    if let Some(r) = compare_literals(head, &a, &b) {
+        return truth(r);
+    }
+    let same = a == b && !may_be_undefined(&a);
+    let decided = match head.as_ref() {
+        "=" | "≤" | "≥" if same => Some(true),
+        "≠" | "<" | ">" if same => Some(false),
+        "=" | "≠" => match (as_truth(&a), as_truth(&b)) {
+            (Some(x), Some(y)) => Some((x == y) == (head.as_ref() == "=")),
+            _ => None,
+        },
+        _ => None,
+    };
+    match decided {
+        Some(r) => truth(r),
+        None => Term::App(head.clone(), vec![a, b]),
+    }
+}
@@ -1,7 +1,12 @@
+use std::collections::HashMap;
+
 use num_bigint::Sign;
 
-use crate::ast::{Expr, UnaryOp};
-use crate::kernel::term::{sym, Term};
+use crate::ast::{Expr, Op, UnaryOp};
+use crate::kernel::eval::rat_to_term;
+use crate::kernel::modular::residue;
+use crate::kernel::subst::subst;
+use crate::kernel::term::{big_op, cond, imaginary_unit, sym, truth, Term};
 
 #[derive(Debug)]
 pub struct LowerError(pub String);
@@ -9,31 +14,100 @@ pub struct LowerError(pub String);
 /// Translate a surface AST into the kernel's uniform-prefix `Term`
 /// representation. Binary operators become applications keyed by the operator
 /// symbol; function application becomes `App`; non-negative integer literals
-/// become `Nat`, negative ones `Int`. `∀` binders are stripped — the body is
+/// become `Nat`, negative ones `Int`, decimals their exact rational value,
+/// `v (mod n)` the residue literal of `kernel::modular`;
+/// a free `i` becomes the complex literal `Complex(0, 1)` (see `lower_with`); `True`/`False` become the
+/// nullary truth constants, `¬p` the application `¬(p)`,
+/// `if c then a else b` the piecewise term `if(c, a, b)` and
+/// `∑ e for k from a to b` the term `∑(k, a, b, e)`. `∀` binders are stripped — the body is
 /// lowered directly, with variables remaining as `Term::Var` pattern variables.
+/// A local `let name = value in body` is lowered by substituting the lowered
+/// `value` for `name` in the lowered `body`; its type annotation is dropped.
 /// Domain annotations and set-builder expressions cannot appear as terms.
 pub fn lower(e: &Expr) -> Result<Term, LowerError> {
+    lower_with(e, &[])
+}
+
+/// Like `lower`, in the scope of the variables `bound`. `i` is the imaginary
+/// unit only where it is free: bound by `∀`, `∑`, `∏` or a local `let`
+/// inside `e`, or listed in `bound` (a name the caller has declared), it is
+/// an ordinary variable.
+pub fn lower_with(e: &Expr, bound: &[String]) -> Result<Term, LowerError> {
+    let mut scope = bound.to_vec();
+    lower_in(e, &mut scope)
+}
+
+/// The scope to lower a fact in: its `∀` variables `bound`, and `i` as well
+/// when the fact's pattern, the left side of an equation, mentions it free.
+/// Free variables of a fact are universally quantified, so `fact h(i) = 0`
+/// holds for every `i`; an `i` on the right side only, as in
+/// `fact root(-1) = i`, is still the imaginary unit.
+pub fn fact_scope(body: &Expr, bound: &[String]) -> Vec<String> {
+    let pattern = match body {
+        Expr::BinOp(Op::Eq, l, _) => l.as_ref(),
+        _ => body,
+    };
+    let mut scope = bound.to_vec();
+    let with_i = [bound, &["i".to_string()]].concat();
+    if lower_with(pattern, bound).ok() != lower_with(pattern, &with_i).ok() {
+        scope.push("i".into());
+    }
+    scope
+}
+
+fn lower_in(e: &Expr, scope: &mut Vec<String>) -> Result<Term, LowerError> {
     match e {
+        Expr::Ident(s) if s == "i" && !scope.contains(s) => Ok(imaginary_unit()),
         Expr::Ident(s) => Ok(Term::Var(sym(s))),
         Expr::Int(n) => match n.sign() {
             Sign::Minus => Ok(Term::Int(n.clone())),
             _ => Ok(Term::Nat(n.magnitude().clone())),
         },
+        Expr::Decimal(r) => Ok(rat_to_term(r.clone())),
+        Expr::Residue(v, n) => residue(v, n)
+            .ok_or_else(|| LowerError(format!("modulus must be positive, got {n}"))),
+        Expr::Bool(b) => Ok(truth(*b)),
         Expr::App(f, args) => {
-            let term_args: Result<Vec<_>, _> = args.iter().map(lower).collect();
+            let term_args: Result<Vec<_>, _> = args.iter().map(|a| lower_in(a, scope)).collect();
             Ok(Term::App(sym(f), term_args?))
         }
         Expr::BinOp(op, l, r) => {
-            let l = lower(l)?;
-            let r = lower(r)?;
+            let l = lower_in(l, scope)?;
+            let r = lower_in(r, scope)?;
             Ok(Term::App(sym(op.symbol()), vec![l, r]))
         }
+        Expr::Infix(op, l, r) => {
+            let l = lower_in(l, scope)?;
+            let r = lower_in(r, scope)?;
+            Ok(Term::App(sym(&op.symbol), vec![l, r]))
+        }
         Expr::UnaryOp(UnaryOp::Neg, e) => {
-            Ok(Term::App(sym("-"), vec![lower(e)?]))
+            Ok(Term::App(sym("-"), vec![lower_in(e, scope)?]))
+        }
+        Expr::UnaryOp(UnaryOp::Not, e) => {
+            Ok(Term::App(sym("¬"), vec![lower_in(e, scope)?]))
+        }
+        Expr::Forall(vars, _domain, body) => under(vars, body, scope),
+        Expr::Let(name, _ty, value, body) => {
+            let mut sigma = HashMap::new();
+            sigma.insert(sym(name), lower_in(value, scope)?);
+            Ok(subst(&under(std::slice::from_ref(name), body, scope)?, &sigma))
+        }
+        Expr::If(c, a, b) => Ok(cond(lower_in(c, scope)?, lower_in(a, scope)?, lower_in(b, scope)?)),
+        Expr::BigOp(op, var, lo, hi, body) => {
+            let (lo, hi) = (lower_in(lo, scope)?, lower_in(hi, scope)?);
+            Ok(big_op(op.symbol(), sym(var), lo, hi, under(std::slice::from_ref(var), body, scope)?))
         }
-        Expr::Forall(_vars, _domain, body) => lower(body),
         Expr::SetBuilder(_, _, _) => {
             Err(LowerError("set-builder expressions cannot be used as terms".into()))
         }
     }
 }
+
+/// Lower `body` with `names` bound as well.
+fn under(names: &[String], body: &Expr, scope: &mut Vec<String>) -> Result<Term, LowerError> {
+    scope.extend_from_slice(names);
+    let t = lower_in(body, scope);
+    scope.truncate(scope.len() - names.len());
+    t
+}
@@ -2,8 +2,23 @@ pub mod term;
 pub mod lower;
 pub mod subst;
 pub mod eval;
+pub mod poly;
+pub mod ratfun;
+pub mod factor;
+pub mod numtheory;
+pub mod modular;
+pub mod bigop;
+pub mod algebraic;
+pub mod approx;
+pub mod groebner;
+pub mod logic;
 pub mod print;
 pub mod kbo;
 pub mod pmatch;
 pub mod theory;
 pub mod rewrite;
+pub mod cases;
+pub mod diff;
+pub mod series;
+pub mod solve;
+pub mod check;
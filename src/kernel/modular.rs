@@ -0,0 +1,223 @@
+//! Residue classes, the elements of `ℤ/nℤ`.
+//!
+//! A residue is the literal `Term::Residue(value, n)` with `value < n`,
+//! printed `3 (mod 7)`. Arithmetic folds when some operand is a residue: the
+//! other operands must be residues with the same modulus or rational
+//! literals, which are read modulo `n` first, so `3 (mod 7) + 5` is
+//! `1 (mod 7)` and `1 / 2` becomes `4 (mod 7)`. Division and negative powers
+//! multiply by an inverse, which exists when the value is coprime to `n`.
+//! Residues with different moduli do not combine. Equality compares classes,
+//! so `3 (mod 7) = 10` is `True`; residues have no order.
+//!
+//! The set `ℤ/nℤ` is the term `ℤ / (n · ℤ)`, written `ℤ/nℤ`. A `let` whose
+//! type is bound to such a set reads its value as a residue.
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, Signed, Zero};
+
+use crate::kernel::eval::{evaluate, term_to_rat, EvalError};
+use crate::kernel::term::Term;
+
+/// The class of `value` modulo `modulus`, or `None` unless `modulus > 0`.
+pub fn residue(value: &BigInt, modulus: &BigInt) -> Option<Term> {
+    if !modulus.is_positive() {
+        return None;
+    }
+    let n = modulus.magnitude();
+    Some(Term::Residue(Box::new((reduce(value, n), n.clone()))))
+}
+
+/// The modulus of a residue literal.
+pub(crate) fn modulus(t: &Term) -> Option<&BigUint> {
+    match t {
+        Term::Residue(r) => Some(&r.1),
+        _ => None,
+    }
+}
+
+/// The modulus `n` when `set` is `ℤ/nℤ`, the term `ℤ / (n · ℤ)`.
+pub fn set_modulus(set: &Term) -> Option<BigUint> {
+    quotient_modulus(set)?.ok()
+}
+
+/// The modulus of a quotient `ℤ / d`, or `None` for any other set. `d`
+/// must be `n · ℤ` with `n` an integer literal greater than 1; anything
+/// else, such as `ℤ/0ℤ` or `ℤ/xℤ` (where `xℤ` is one name), is an error.
+pub fn quotient_modulus(set: &Term) -> Option<Result<BigUint, EvalError>> {
+    let integers = |t: &Term| matches!(t, Term::Var(s) if s.as_ref() == "ℤ");
+    let args = match set {
+        Term::App(head, args) if head.as_ref() == "/" && args.len() == 2 => args,
+        _ => return None,
+    };
+    if !integers(&args[0]) {
+        return None;
+    }
+    let m = match &args[1] {
+        Term::App(times, factors) if times.as_ref() == "·" => match factors.as_slice() {
+            [m, z] | [z, m] if integers(z) => Some(m),
+            _ => None,
+        },
+        _ => None,
+    };
+    Some(match m {
+        Some(Term::Nat(n)) if *n > BigUint::one() => Ok(n.clone()),
+        _ => Err(EvalError(
+            "the modulus of ℤ/nℤ must be an integer literal greater than 1".into(),
+        )),
+    })
+}
+
+/// `value` as an element of `ℤ/nℤ`: it must evaluate to a residue modulo
+/// `n` or to a rational literal with a denominator invertible modulo `n`.
+pub fn element(value: &Term, n: &BigUint) -> Result<Term, EvalError> {
+    match lift(&evaluate(value)?, n)? {
+        Some(v) => Ok(Term::Residue(Box::new((v, n.clone())))),
+        None => Err(EvalError(format!(
+            "not a number, so not an element of ℤ/{n}ℤ"
+        ))),
+    }
+}
+
+/// Decide `elem ∈ ℤ/nℤ` for a residue literal `elem`.
+pub(crate) fn membership(elem: &Term, set: &Term) -> Option<bool> {
+    let m = modulus(elem)?;
+    Some(set_modulus(set)? == *m)
+}
+
+/// Whether a residue occurs in `t`. Polynomial normal forms, with
+/// coefficients in ℚ, leave such terms alone.
+pub(crate) fn contains_residue(t: &Term) -> bool {
+    match t {
+        Term::Residue(..) => true,
+        Term::App(_, args) => args.iter().any(contains_residue),
+        _ => false,
+    }
+}
+
+fn reduce(value: &BigInt, n: &BigUint) -> BigUint {
+    let n = BigInt::from(n.clone());
+    let r = ((value % &n) + &n) % &n;
+    r.magnitude().clone()
+}
+
+/// The value modulo `n` of a numeric literal: a residue modulo `n`, or a
+/// rational read modulo `n`. `Ok(None)` for other terms; an error for a
+/// residue with another modulus or a denominator sharing a factor with `n`.
+fn lift(t: &Term, n: &BigUint) -> Result<Option<BigUint>, EvalError> {
+    if let Term::Residue(r) = t {
+        let (v, m) = r.as_ref();
+        if m != n {
+            return Err(EvalError(format!(
+                "residues modulo {n} and {m} do not combine"
+            )));
+        }
+        return Ok(Some(v.clone()));
+    }
+    let r = match term_to_rat(t) {
+        Some(r) => r,
+        None => return Ok(None),
+    };
+    let numer = reduce(r.numer(), n);
+    let denom = reduce(r.denom(), n);
+    Ok(Some(numer * inverse(&denom, n)? % n))
+}
+
+/// The inverse of `a` modulo `n`, by the extended Euclidean algorithm.
+fn inverse(a: &BigUint, n: &BigUint) -> Result<BigUint, EvalError> {
+    let (mut r0, mut r1) = (BigInt::from(n.clone()), BigInt::from(a.clone()));
+    let (mut s0, mut s1) = (BigInt::zero(), BigInt::one());
+    while !r1.is_zero() {
+        let q = &r0 / &r1;
+        (r0, r1) = (r1.clone(), r0 - &q * &r1);
+        (s0, s1) = (s1.clone(), s0 - &q * &s1);
+    }
+    if !r0.is_one() {
+        return Err(EvalError(format!("{a} has no inverse modulo {n}")));
+    }
+    Ok(reduce(&s0, n))
+}
+
+/// Fold `+`, `-`, `·`, `/` and `^` when some operand is a residue and the
+/// others are numeric. The exponent of `^` is an integer literal, not reduced.
+pub(crate) fn fold(head: &str, args: &[Term]) -> Result<Option<Term>, EvalError> {
+    let n = match args.iter().find_map(modulus) {
+        Some(n) => n.clone(),
+        None => return Ok(None),
+    };
+    let residue = |v: BigUint| -> Result<Option<Term>, EvalError> {
+        Ok(Some(Term::Residue(Box::new((v, n.clone())))))
+    };
+    if let ("^", [base, e]) = (head, args) {
+        let e = match term_to_rat(e) {
+            Some(e) if e.is_integer() => e.to_integer(),
+            _ => return Ok(None),
+        };
+        let a = match lift(base, &n)? {
+            Some(a) => a,
+            None => return Ok(None),
+        };
+        let a = if e.sign() == Sign::Minus {
+            inverse(&a, &n)?
+        } else {
+            a
+        };
+        return residue(a.modpow(e.magnitude(), &n));
+    }
+    if !matches!(head, "+" | "-" | "·" | "/") {
+        return Ok(None);
+    }
+    let mut values = Vec::with_capacity(args.len());
+    for a in args {
+        match lift(a, &n)? {
+            Some(v) => values.push(v),
+            None => return Ok(None),
+        }
+    }
+    let v = match (head, values.as_slice()) {
+        ("-", [a]) => (&n - a) % &n,
+        ("-", [a, b]) => (a + &n - b) % &n,
+        ("+", [_, _, ..]) => values.iter().fold(BigUint::zero(), |acc, v| (acc + v) % &n),
+        ("·", [_, _, ..]) => values.iter().fold(BigUint::one(), |acc, v| acc * v % &n),
+        ("/", [a, b]) => a * inverse(b, &n)? % &n,
+        _ => return Ok(None),
+    };
+    residue(v % &n)
+}
+
+/// Decide `a = b` or `a ≠ b` when one side is a residue and the other is
+/// numeric, comparing classes.
+pub(crate) fn compare(head: &str, a: &Term, b: &Term) -> Option<bool> {
+    if !matches!(head, "=" | "≠") {
+        return None;
+    }
+    let n = modulus(a).or_else(|| modulus(b))?;
+    let equal = lift(a, n).ok()?? == lift(b, n).ok()??;
+    Some(equal == (head == "="))
+}
+
+/// Combine the numeric operands of a flattened AC sum or product that
+/// contains a residue into one residue. Operands that do not combine with it
+/// are left alone.
+pub(crate) fn fold_literals(is_add: bool, flat: &mut Vec<Term>) {
+    let n = match flat.iter().find_map(modulus) {
+        Some(n) => n.clone(),
+        None => return,
+    };
+    let mut acc = if is_add {
+        BigUint::zero()
+    } else {
+        BigUint::one()
+    };
+    flat.retain(|x| match lift(x, &n) {
+        Ok(Some(v)) => {
+            acc = if is_add {
+                (&acc + v) % &n
+            } else {
+                &acc * v % &n
+            };
+            false
+        }
+        _ => true,
+    });
+    flat.push(Term::Residue(Box::new((acc, n))));
+}
@@ -0,0 +1,285 @@
+//! Integer functions on literals: `mod`, `div`, `gcd`, `lcm`, `factorial`,
+//! `binomial` and `isprime`, and the factorization of integers behind
+//! `factor 360`.
+//!
+//! `mod` and `div` are floored, so `a mod b` takes the sign of `b` and
+//! `a = b · (a div b) + a mod b` whenever `b ≠ 0`. All of them are defined on
+//! integers only, and a rational argument is an error. An integer is factored by trial division up to
+//! `TRIAL_LIMIT`, then split by Pollard's rho method with Brent's cycle
+//! detection. A factor passing the Miller–Rabin test of `kernel::factor` is
+//! taken as prime; a composite factor that resists rho within `RHO_BUDGET`
+//! steps is kept whole.
+
+use num_bigint::{BigInt, BigUint};
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::kernel::eval::{rat_to_term, term_to_rat, EvalError};
+use crate::kernel::factor::is_prime;
+use crate::kernel::poly::int_gcd;
+use crate::kernel::term::{sym, truth, Term};
+
+/// Largest `n` for which `factorial(n)` is expanded, and largest `k` for
+/// `binomial(n, k)`.
+pub const MAX_FACTORIAL: u32 = 10_000;
+
+/// Primes up to this bound are divided out before Pollard's rho.
+const TRIAL_LIMIT: u32 = 1_000;
+
+/// Iterations of the rho map spent on one composite before giving up on it.
+const RHO_BUDGET: u64 = 1 << 20;
+
+/// Fold an integer function whose arguments are all numeric literals.
+/// `Ok(None)` leaves the application alone: an unknown head or a wrong
+/// number of arguments. Non-integer arguments, division by zero, factorials
+/// of negative numbers and arguments beyond `MAX_FACTORIAL` are errors.
+pub(crate) fn fold(head: &str, args: &[Term]) -> Result<Option<Term>, EvalError> {
+    if !matches!(
+        head,
+        "mod" | "div" | "gcd" | "lcm" | "factorial" | "binomial" | "isprime"
+    ) {
+        return Ok(None);
+    }
+    let rats: Vec<BigRational> = match args.iter().map(term_to_rat).collect() {
+        Some(rats) => rats,
+        None => return Ok(None),
+    };
+    let ints: Vec<BigInt> = match rats
+        .iter()
+        .map(|r| r.is_integer().then(|| r.to_integer()))
+        .collect()
+    {
+        Some(ints) => ints,
+        None => {
+            return Err(EvalError(format!(
+                "`{head}` is only defined on integers"
+            )))
+        }
+    };
+    let t = match (head, ints.as_slice()) {
+        ("mod", [a, b]) => int_term(a - b * floor_div(a, b)?),
+        ("div", [a, b]) => int_term(floor_div(a, b)?),
+        ("gcd", [a, b]) => int_term(int_gcd(a.clone(), b.clone())),
+        ("lcm", [a, b]) => int_term(lcm(a, b)),
+        ("factorial", [n]) => int_term(factorial(n)?),
+        ("binomial", [n, k]) => int_term(binomial(n, k)?),
+        ("isprime", [n]) => truth(is_prime(n)),
+        _ => return Ok(None),
+    };
+    Ok(Some(t))
+}
+
+fn int_term(n: BigInt) -> Term {
+    rat_to_term(BigRational::from(n))
+}
+
+/// `⌊a / b⌋`.
+fn floor_div(a: &BigInt, b: &BigInt) -> Result<BigInt, EvalError> {
+    if b.is_zero() {
+        return Err(EvalError("division by zero".into()));
+    }
+    Ok(BigRational::new(a.clone(), b.clone()).floor().to_integer())
+}
+
+fn lcm(a: &BigInt, b: &BigInt) -> BigInt {
+    if a.is_zero() || b.is_zero() {
+        return BigInt::zero();
+    }
+    (a * b).abs() / int_gcd(a.clone(), b.clone())
+}
+
+/// The size of an argument of `factorial` or `binomial`, checked against
+/// `MAX_FACTORIAL`.
+fn small(n: &BigInt) -> Result<u32, EvalError> {
+    match n.to_u32() {
+        Some(k) if k <= MAX_FACTORIAL => Ok(k),
+        _ => Err(EvalError(format!(
+            "{n} too large to evaluate (must be at most {MAX_FACTORIAL})"
+        ))),
+    }
+}
+
+fn factorial(n: &BigInt) -> Result<BigInt, EvalError> {
+    if n.is_negative() {
+        return Err(EvalError("factorial of a negative number".into()));
+    }
+    Ok((2..=small(n)?).fold(BigInt::one(), |acc, j| acc * j))
+}
+
+/// `n · (n - 1) ⋯ (n - k + 1) / k!`, for any integer `n`, and 0 for `k < 0`.
+/// For `n ≥ 0` this is the number of `k`-subsets of an `n`-set; the smaller
+/// of `k` and `n - k` is used.
+fn binomial(n: &BigInt, k: &BigInt) -> Result<BigInt, EvalError> {
+    if k.is_negative() || (!n.is_negative() && k > n) {
+        return Ok(BigInt::zero());
+    }
+    let k = if n.is_negative() {
+        k.clone()
+    } else {
+        k.min(&(n - k)).clone()
+    };
+    let mut acc = BigInt::one();
+    for j in 0..small(&k)? {
+        // Each partial product is itself a binomial coefficient.
+        acc = acc * (n - j) / (j + 1);
+    }
+    Ok(acc)
+}
+
+/// The prime factors of `n` in increasing order, with multiplicities. Empty
+/// for 0 and 1. A composite that Pollard's rho cannot split within its
+/// budget is listed as if it were prime.
+pub fn factorize(n: &BigUint) -> Vec<(BigUint, u32)> {
+    let mut found: Vec<BigUint> = Vec::new();
+    let mut rest = n.clone();
+    if rest.is_zero() {
+        return Vec::new();
+    }
+    let mut p = 2u32;
+    while p <= TRIAL_LIMIT && BigUint::from(p) * p <= rest {
+        while (&rest % p).is_zero() {
+            rest /= p;
+            found.push(p.into());
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    split(rest, &mut found);
+    found.sort();
+    let mut factors: Vec<(BigUint, u32)> = Vec::new();
+    for q in found {
+        match factors.last_mut() {
+            Some((last, k)) if *last == q => *k += 1,
+            _ => factors.push((q, 1)),
+        }
+    }
+    factors
+}
+
+/// Push the prime factors of `n`, which has none below `TRIAL_LIMIT`.
+fn split(n: BigUint, out: &mut Vec<BigUint>) {
+    if n.is_one() {
+        return;
+    }
+    if is_prime(&BigInt::from(n.clone())) {
+        out.push(n);
+        return;
+    }
+    match rho(&n) {
+        Some(d) => {
+            split(&n / &d, out);
+            split(d, out);
+        }
+        None => out.push(n),
+    }
+}
+
+/// A proper divisor of the composite `n`, trying the maps `x² + c` for
+/// `c = 1, 2, …` until the budget runs out.
+fn rho(n: &BigUint) -> Option<BigUint> {
+    let mut steps = 0;
+    let mut c = BigUint::one();
+    while steps < RHO_BUDGET {
+        if let Some(d) = brent(n, &c, &mut steps) {
+            return Some(d);
+        }
+        c += 1u32;
+    }
+    None
+}
+
+/// One run of Brent's variant of Pollard's rho with the map `x² + c`,
+/// batching the differences into products so that one gcd covers `BATCH`
+/// steps. `None` when the run only finds `n` itself or exhausts the budget.
+fn brent(n: &BigUint, c: &BigUint, steps: &mut u64) -> Option<BigUint> {
+    const BATCH: u64 = 128;
+    let f = |x: &BigUint| (x * x + c) % n;
+    let mut y = BigUint::from(2u32);
+    let mut x = y.clone();
+    let mut ys = y.clone();
+    let mut q = BigUint::one();
+    let mut g = BigUint::one();
+    let mut r = 1u64;
+    while g.is_one() {
+        if *steps >= RHO_BUDGET {
+            return None;
+        }
+        x = y.clone();
+        for _ in 0..r {
+            y = f(&y);
+        }
+        let mut k = 0;
+        while k < r && g.is_one() {
+            ys = y.clone();
+            for _ in 0..BATCH.min(r - k) {
+                y = f(&y);
+                q = q * abs_diff(&x, &y) % n;
+            }
+            g = gcd(q.clone(), n.clone());
+            k += BATCH;
+        }
+        *steps += 2 * r;
+        r *= 2;
+    }
+    if &g == n {
+        // The batch overshot: retrace it one step at a time.
+        loop {
+            ys = f(&ys);
+            g = gcd(abs_diff(&x, &ys), n.clone());
+            if !g.is_one() {
+                break;
+            }
+        }
+    }
+    (&g != n).then_some(g)
+}
+
+fn abs_diff(a: &BigUint, b: &BigUint) -> BigUint {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+fn gcd(mut a: BigUint, mut b: BigUint) -> BigUint {
+    while !b.is_zero() {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// A rational literal as a product of prime powers, over another when it is
+/// not an integer: `360` as `2 ^ 3 · 3 ^ 2 · 5`, `-12 / 35` as
+/// `-(2 ^ 2 · 3 / (5 · 7))`. Zero, units and primes are left as they are.
+pub fn factor_rational(r: &BigRational) -> Term {
+    let product = |n: &BigInt| -> Option<Term> {
+        let mut parts: Vec<Term> = factorize(n.magnitude())
+            .into_iter()
+            .map(|(p, k)| match k {
+                1 => Term::Nat(p),
+                _ => Term::App(sym("^"), vec![Term::Nat(p), Term::Nat(k.into())]),
+            })
+            .collect();
+        match parts.len() {
+            0 => None,
+            1 => parts.pop(),
+            _ => Some(Term::App(sym("·"), parts)),
+        }
+    };
+    let (num, den) = (product(r.numer()), product(r.denom()));
+    let body = match (num, den) {
+        (Some(Term::Nat(_)), None) | (None, None) => return rat_to_term(r.clone()),
+        (Some(n), None) => n,
+        (n, Some(d)) => Term::App(
+            sym("/"),
+            vec![n.unwrap_or_else(|| Term::Nat(BigUint::one())), d],
+        ),
+    };
+    if r.is_negative() {
+        Term::App(sym("-"), vec![body])
+    } else {
+        body
+    }
+}
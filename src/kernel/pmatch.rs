@@ -36,6 +36,7 @@ fn pmatch_into(
             Term::Rat(b) if a == b => Some(sigma),
             _ => None,
         },
+        Term::Complex(..) | Term::Residue(..) => (pat == t).then_some(sigma),
         Term::App(f, args) => match t {
             Term::App(g, args2) if f == g && args.len() == args2.len() => {
                 let mut s = sigma;
@@ -0,0 +1,514 @@
+//! Multivariate polynomials over ℚ, the normal form for ring expressions.
+//!
+//! A term built from `+`, `-`, `·`, `^` with ℕ exponents, and division by a
+//! nonzero literal, is read as a polynomial whose indeterminates are its
+//! other subterms: variables, and applications such as `log(x)` or `x ^ y`.
+//! A polynomial is a sparse map from monomials to nonzero `BigRational`
+//! coefficients, and a monomial maps indeterminates to positive exponents.
+//! Both maps are keyed by the kernel term order, so equal polynomials have
+//! equal representations and `to_term` gives a canonical term back.
+//!
+//! `to_term` lists monomials by descending total degree, then
+//! lexicographically, and puts the coefficient last as in AC-sorted products:
+//! `a ^ 2 - a · b · 2 + b ^ 2`. Reading a term assumes the ring laws, so
+//! `simplify` uses this only when the theory has made `+` and `·` AC with
+//! identities (`Theory::is_ring`); `expand` uses it unconditionally.
+//!
+//! Exact division and greatest common divisors, which `kernel::ratfun` needs
+//! to cancel fractions, work recursively: a polynomial is viewed as one in
+//! its first indeterminate with polynomial coefficients in the rest, and the
+//! GCD is the GCD of the contents times that of the primitive parts, the
+//! latter from a primitive pseudo-remainder sequence.
+
+use std::cmp::Ordering;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::kernel::eval::{rat_to_term, term_to_rat};
+use crate::kernel::term::{imaginary_unit, sym, Term};
+
+/// Indeterminates with their (positive) exponents.
+pub type Monomial = BTreeMap<Term, u32>;
+
+/// Powers of a sum above this are left unexpanded, as an indeterminate.
+pub(crate) const MAX_POWER: u32 = 64;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Poly {
+    terms: BTreeMap<Monomial, BigRational>,
+}
+
+impl Poly {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn constant(c: BigRational) -> Self {
+        let mut p = Self::zero();
+        p.add_term(Monomial::new(), c);
+        p
+    }
+
+    /// The polynomial consisting of the indeterminate `t`.
+    pub fn var(t: Term) -> Self {
+        let mut m = Monomial::new();
+        m.insert(t, 1);
+        let mut p = Self::zero();
+        p.add_term(m, BigRational::one());
+        p
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The value of a constant polynomial.
+    pub fn as_constant(&self) -> Option<BigRational> {
+        match self.terms.len() {
+            0 => Some(BigRational::zero()),
+            1 => self.terms.get(&Monomial::new()).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Monomials with their coefficients, in the map's key order.
+    pub fn terms(&self) -> impl Iterator<Item = (&Monomial, &BigRational)> {
+        self.terms.iter()
+    }
+
+    pub fn total_degree(&self) -> u32 {
+        self.terms.keys().map(degree).max().unwrap_or(0)
+    }
+
+    /// Add `c · m`, dropping the monomial if its coefficient cancels.
+    pub fn add_term(&mut self, m: Monomial, c: BigRational) {
+        if c.is_zero() {
+            return;
+        }
+        match self.terms.entry(m) {
+            Entry::Vacant(v) => {
+                v.insert(c);
+            }
+            Entry::Occupied(mut o) => {
+                *o.get_mut() += c;
+                if o.get().is_zero() {
+                    o.remove();
+                }
+            }
+        }
+    }
+
+    pub fn scale(&self, c: &BigRational) -> Poly {
+        let mut out = Poly::zero();
+        for (m, k) in &self.terms {
+            out.add_term(m.clone(), k * c);
+        }
+        out
+    }
+
+    pub fn pow(&self, mut e: u32) -> Poly {
+        let mut out = Poly::constant(BigRational::one());
+        let mut base = self.clone();
+        while e > 0 {
+            if e & 1 == 1 {
+                out = &out * &base;
+            }
+            e >>= 1;
+            if e > 0 {
+                base = &base * &base;
+            }
+        }
+        out
+    }
+
+    /// Read `t` as a polynomial. Subterms that are not ring operations become
+    /// indeterminates. A complex literal `a + b · i` is read with `i` as an
+    /// indeterminate, and products reduce `i ^ 2` to `-1`.
+    pub fn from_term(t: &Term) -> Poly {
+        if let Some(r) = term_to_rat(t) {
+            return Poly::constant(r);
+        }
+        if let Term::Complex(c) = t {
+            let (re, im) = c.as_ref();
+            return &Poly::constant(re.clone()) + &Poly::var(imaginary_unit()).scale(im);
+        }
+        if let Term::App(head, args) = t {
+            match (head.as_ref(), args.as_slice()) {
+                ("+", [_, ..]) => {
+                    return args.iter().fold(Poly::zero(), |acc, a| &acc + &Poly::from_term(a))
+                }
+                ("·", [_, ..]) => {
+                    let one = Poly::constant(BigRational::one());
+                    let p = args.iter().fold(one, |acc, a| &acc * &Poly::from_term(a));
+                    return p.reduce_imaginary();
+                }
+                ("-", [a]) => return -&Poly::from_term(a),
+                ("-", [a, b]) => return &Poly::from_term(a) - &Poly::from_term(b),
+                ("/", [a, b]) => {
+                    if let Some(c) = term_to_rat(b).filter(|c| !c.is_zero()) {
+                        return Poly::from_term(a).scale(&c.recip());
+                    }
+                }
+                ("^", [b, Term::Nat(n)]) => {
+                    if let Some(e) = n.to_u32() {
+                        let base = Poly::from_term(b);
+                        let fits = base.total_degree().checked_mul(e).is_some();
+                        if fits && (e <= MAX_POWER || base.is_unit_monomial()) {
+                            return base.pow(e).reduce_imaginary();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Poly::var(t.clone())
+    }
+
+    /// Replace `i ^ k` by `1`, `i`, `-1` or `-i` according to `k mod 4`.
+    fn reduce_imaginary(self) -> Poly {
+        let i = imaginary_unit();
+        if !self.terms.keys().any(|m| m.get(&i).is_some_and(|&k| k >= 2)) {
+            return self;
+        }
+        let mut out = Poly::zero();
+        for (mut m, c) in self.terms {
+            let k = m.remove(&i).unwrap_or(0);
+            if k % 2 == 1 {
+                m.insert(i.clone(), 1);
+            }
+            out.add_term(m, if k % 4 >= 2 { -c } else { c });
+        }
+        out
+    }
+
+    /// The partial derivative with respect to the indeterminate `x`.
+    pub fn derivative(&self, x: &Term) -> Poly {
+        let mut out = Poly::zero();
+        for (m, c) in &self.terms {
+            if let Some(&e) = m.get(x) {
+                let mut m = m.clone();
+                match e {
+                    1 => m.remove(x),
+                    _ => m.insert(x.clone(), e - 1),
+                };
+                out.add_term(m, c * BigRational::from_integer(e.into()));
+            }
+        }
+        out
+    }
+
+    /// `self` as `c · q`, where `q` has coprime integer coefficients and a
+    /// positive leading coefficient in `grlex` order.
+    pub fn primitive(&self) -> (BigRational, Poly) {
+        let mut c = content(self.terms.values());
+        if self.terms.iter().max_by(|a, b| grlex(a.0, b.0)).is_some_and(|(_, k)| k.is_negative()) {
+            c = -c;
+        }
+        (c.clone(), self.scale(&c.recip()))
+    }
+
+    /// `self / d`, when `d` divides `self` exactly.
+    pub fn div_exact(&self, d: &Poly) -> Option<Poly> {
+        let (dm, dc) = d.leading()?;
+        let (dm, dc) = (dm.clone(), dc.clone());
+        let mut rest = self.clone();
+        let mut quotient = Poly::zero();
+        while let Some((m, c)) = rest.leading() {
+            let mut step = Poly::zero();
+            step.add_term(monomial_div(m, &dm)?, c / &dc);
+            rest = &rest - &(&step * d);
+            quotient = &quotient + &step;
+        }
+        Some(quotient)
+    }
+
+    /// A greatest common divisor, with leading coefficient 1. Nonzero
+    /// constants are units, so their GCD with anything is 1.
+    pub fn gcd(&self, other: &Poly) -> Poly {
+        if self.is_zero() {
+            return other.monic();
+        }
+        if other.is_zero() {
+            return self.monic();
+        }
+        let x = match self.indeterminates().chain(other.indeterminates()).min() {
+            Some(x) if self.as_constant().is_none() && other.as_constant().is_none() => x.clone(),
+            _ => return Poly::constant(BigRational::one()),
+        };
+        // If `x` occurs on one side only, it divides the other's coefficients.
+        if self.degree_in(&x) == 0 {
+            return self.gcd(&other.content_in(&x));
+        }
+        if other.degree_in(&x) == 0 {
+            return other.gcd(&self.content_in(&x));
+        }
+        let content = self.content_in(&x).gcd(&other.content_in(&x));
+        let (mut a, mut b) = (self.primitive_in(&x), other.primitive_in(&x));
+        if a.degree_in(&x) < b.degree_in(&x) {
+            std::mem::swap(&mut a, &mut b);
+        }
+        while !b.is_zero() {
+            let r = a.pseudo_rem(&b, &x);
+            a = b;
+            b = if r.is_zero() { r } else { r.primitive_in(&x).primitive().1 };
+        }
+        (&content * &a.primitive_in(&x)).monic()
+    }
+
+    /// The leading monomial and coefficient in `lex` order.
+    fn leading(&self) -> Option<(&Monomial, &BigRational)> {
+        self.terms.iter().max_by(|a, b| lex(a.0, b.0))
+    }
+
+    fn monic(&self) -> Poly {
+        match self.leading() {
+            Some((_, c)) => self.scale(&c.recip()),
+            None => Poly::zero(),
+        }
+    }
+
+    pub(crate) fn indeterminates(&self) -> impl Iterator<Item = &Term> {
+        self.terms.keys().flat_map(|m| m.keys())
+    }
+
+    pub(crate) fn degree_in(&self, x: &Term) -> u32 {
+        self.terms.keys().filter_map(|m| m.get(x).copied()).max().unwrap_or(0)
+    }
+
+    /// The coefficient of `x ^ k`, a polynomial in the other indeterminates.
+    fn coefficient_in(&self, x: &Term, k: u32) -> Poly {
+        let mut out = Poly::zero();
+        for (m, c) in &self.terms {
+            if m.get(x).copied().unwrap_or(0) == k {
+                let mut m = m.clone();
+                m.remove(x);
+                out.add_term(m, c.clone());
+            }
+        }
+        out
+    }
+
+    /// The GCD of the coefficients of `self` as a polynomial in `x`.
+    pub(crate) fn content_in(&self, x: &Term) -> Poly {
+        let degrees: BTreeSet<u32> = self.terms.keys().map(|m| m.get(x).copied().unwrap_or(0)).collect();
+        degrees.iter().fold(Poly::zero(), |g, &k| g.gcd(&self.coefficient_in(x, k)))
+    }
+
+    fn primitive_in(&self, x: &Term) -> Poly {
+        self.div_exact(&self.content_in(x)).unwrap_or_else(|| self.clone())
+    }
+
+    /// The pseudo-remainder of `self` by `b` as polynomials in `x`: the
+    /// remainder of `lc(b) ^ k · self`, which needs no division.
+    fn pseudo_rem(&self, b: &Poly, x: &Term) -> Poly {
+        let n = b.degree_in(x);
+        let lead = b.coefficient_in(x, n);
+        let mut r = self.clone();
+        while !r.is_zero() && r.degree_in(x) >= n {
+            let m = r.degree_in(x);
+            let mut shift = Monomial::new();
+            if m > n {
+                shift.insert(x.clone(), m - n);
+            }
+            let mut step = Poly::zero();
+            step.add_term(shift, BigRational::one());
+            r = &(&lead * &r) - &(&(&r.coefficient_in(x, m) * &step) * b);
+        }
+        r
+    }
+
+    /// A single monomial with coefficient ±1, whose powers stay small.
+    fn is_unit_monomial(&self) -> bool {
+        self.terms.len() == 1 && self.terms.values().all(|c| c.abs().is_one())
+    }
+
+    /// The canonical term for this polynomial: a left-nested chain of `+` and
+    /// `-` over its monomials, largest first.
+    pub fn to_term(&self) -> Term {
+        let mut monos: Vec<_> = self.terms.iter().collect();
+        monos.sort_by(|a, b| grlex(b.0, a.0));
+        let mut acc: Option<Term> = None;
+        for (m, c) in monos {
+            let magnitude = monomial_term(m, &c.abs());
+            acc = Some(match acc {
+                None if c.is_negative() => {
+                    if m.is_empty() {
+                        rat_to_term(c.clone())
+                    } else {
+                        Term::App(sym("-"), vec![magnitude])
+                    }
+                }
+                None => magnitude,
+                Some(a) => {
+                    let op = if c.is_negative() { "-" } else { "+" };
+                    Term::App(sym(op), vec![a, magnitude])
+                }
+            });
+        }
+        acc.unwrap_or_else(|| rat_to_term(BigRational::zero()))
+    }
+}
+
+impl Add for &Poly {
+    type Output = Poly;
+
+    fn add(self, other: &Poly) -> Poly {
+        let mut out = self.clone();
+        for (m, c) in &other.terms {
+            out.add_term(m.clone(), c.clone());
+        }
+        out
+    }
+}
+
+impl Sub for &Poly {
+    type Output = Poly;
+
+    fn sub(self, other: &Poly) -> Poly {
+        self + &-other
+    }
+}
+
+impl Neg for &Poly {
+    type Output = Poly;
+
+    fn neg(self) -> Poly {
+        self.scale(&-BigRational::one())
+    }
+}
+
+impl Mul for &Poly {
+    type Output = Poly;
+
+    fn mul(self, other: &Poly) -> Poly {
+        let mut out = Poly::zero();
+        for (m1, c1) in &self.terms {
+            for (m2, c2) in &other.terms {
+                let mut m = m1.clone();
+                for (x, e) in m2 {
+                    *m.entry(x.clone()).or_insert(0) += e;
+                }
+                out.add_term(m, c1 * c2);
+            }
+        }
+        out
+    }
+}
+
+/// `t` with every ring subterm in polynomial normal form, assuming the ring
+/// laws whatever the theory says. Arguments of other heads are expanded in
+/// place, so `f((a + 1)^2)` becomes `f(a ^ 2 + a · 2 + 1)`.
+pub fn expand(t: &Term) -> Term {
+    match t {
+        Term::App(head, args) => {
+            let t = Term::App(head.clone(), args.iter().map(expand).collect());
+            if is_ring_head(head) {
+                Poly::from_term(&t).to_term()
+            } else {
+                t
+            }
+        }
+        _ => t.clone(),
+    }
+}
+
+/// Heads that `Poly::from_term` can read (given suitable arguments).
+pub(crate) fn is_ring_head(head: &str) -> bool {
+    matches!(head, "+" | "-" | "·" | "/" | "^")
+}
+
+pub fn degree(m: &Monomial) -> u32 {
+    m.values().sum()
+}
+
+/// Graded lexicographic order: total degree first, then the exponent of each
+/// indeterminate in term order, so `a ^ 2 > a · b > b ^ 2 > a`.
+pub fn grlex(a: &Monomial, b: &Monomial) -> Ordering {
+    degree(a).cmp(&degree(b)).then_with(|| lex(a, b))
+}
+
+/// Lexicographic order on exponent vectors, with indeterminates earlier in
+/// the term order more significant.
+pub fn lex(a: &Monomial, b: &Monomial) -> Ordering {
+    let (mut ia, mut ib) = (a.iter().peekable(), b.iter().peekable());
+    loop {
+        match (ia.peek(), ib.peek()) {
+            (None, None) => return Ordering::Equal,
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (Some((x, ex)), Some((y, ey))) => match x.cmp(y) {
+                // `a` has a positive exponent where `b` has none.
+                Ordering::Less => return Ordering::Greater,
+                Ordering::Greater => return Ordering::Less,
+                Ordering::Equal => match ex.cmp(ey) {
+                    Ordering::Equal => {
+                        ia.next();
+                        ib.next();
+                    }
+                    o => return o,
+                },
+            },
+        }
+    }
+}
+
+/// `m / d`, if every exponent of `d` is at most that in `m`.
+pub(crate) fn monomial_div(m: &Monomial, d: &Monomial) -> Option<Monomial> {
+    let mut out = m.clone();
+    for (x, e) in d {
+        let have = out.get_mut(x).filter(|have| **have >= *e)?;
+        *have -= e;
+        if *have == 0 {
+            out.remove(x);
+        }
+    }
+    Some(out)
+}
+
+/// `x ^ i · y ^ j · c`, without the coefficient when it is 1.
+fn monomial_term(m: &Monomial, c: &BigRational) -> Term {
+    let mut factors: Vec<Term> = m
+        .iter()
+        .map(|(x, e)| match e {
+            1 => x.clone(),
+            _ => Term::App(sym("^"), vec![x.clone(), Term::Nat((*e).into())]),
+        })
+        .collect();
+    if !c.is_one() || factors.is_empty() {
+        factors.push(rat_to_term(c.clone()));
+    }
+    match factors.len() {
+        1 => factors.pop().unwrap(),
+        _ => Term::App(sym("·"), factors),
+    }
+}
+
+/// The positive rational whose quotient with each coefficient is an integer,
+/// and the largest such: the GCD of the numerators over the LCM of the
+/// denominators. 1 when there are no coefficients.
+pub(crate) fn content<'a>(coefficients: impl Iterator<Item = &'a BigRational>) -> BigRational {
+    let (mut numer, mut denom) = (BigInt::zero(), BigInt::one());
+    for c in coefficients {
+        numer = int_gcd(numer, c.numer().clone());
+        let g = int_gcd(denom.clone(), c.denom().clone());
+        denom = &denom / &g * c.denom();
+    }
+    if numer.is_zero() {
+        return BigRational::one();
+    }
+    BigRational::new(numer, denom)
+}
+
+pub(crate) fn int_gcd(mut a: BigInt, mut b: BigInt) -> BigInt {
+    while !b.is_zero() {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+    a.abs()
+}
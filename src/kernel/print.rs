@@ -1,61 +1,163 @@
 use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
 
-use crate::ast::{Expr, Op, UnaryOp};
-use crate::kernel::term::Term;
+use crate::ast::{BigOp, Expr, InfixTable, Op, UnaryOp};
+use crate::kernel::term::{as_big_op, as_cond, as_truth, Term};
+use crate::printer::decimal;
 
 #[derive(Debug)]
 pub struct UnprintableError(pub String);
 
+/// Longest repeating period `Numbers::Decimals` writes out.
+const MAX_PERIOD: usize = 32;
+
+/// How `to_surface_in` writes rational literals that are not integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Numbers {
+    /// `numerator / denominator`
+    #[default]
+    Fractions,
+    /// Decimals: `0.25`, or `0.1(6)` with the repeating period in
+    /// parentheses. Fractions with periods longer than 32 digits stay
+    /// fractions.
+    Decimals,
+}
+
 /// Lift a kernel term back into the surface AST. Binary applications whose
-/// head matches a known infix operator become `BinOp`; n-ary AC applications
+/// head matches a known infix operator become `BinOp`; truth constants become
+/// `Bool`, `¬(p)` becomes `¬p`, a piecewise term `if c then a else b` and
+/// `∑(k, a, b, e)` the sum `∑ e for k from a to b`;
+/// n-ary AC applications
 /// are unfolded into left-nested `BinOp`; all other applications become
 /// `Expr::App` (function call notation).
 pub fn to_surface(t: &Term) -> Result<Expr, UnprintableError> {
+    to_surface_with(t, &InfixTable::new())
+}
+
+/// Like `to_surface`, but binary applications whose head is declared in `ops`
+/// become `Expr::Infix` (n-ary ones unfold like the built-in operators).
+pub fn to_surface_with(t: &Term, ops: &InfixTable) -> Result<Expr, UnprintableError> {
+    to_surface_in(t, ops, Numbers::Fractions)
+}
+
+/// Like `to_surface_with`, writing rational literals as `numbers` says.
+pub fn to_surface_in(
+    t: &Term,
+    ops: &InfixTable,
+    numbers: Numbers,
+) -> Result<Expr, UnprintableError> {
     match t {
         Term::Nat(n) => Ok(Expr::Int(BigInt::from(n.clone()))),
         Term::Int(n) => Ok(Expr::Int(n.clone())),
-        Term::Rat(r) => Ok(Expr::BinOp(
-            Op::Div,
-            Box::new(Expr::Int(r.numer().clone())),
-            Box::new(Expr::Int(r.denom().clone())),
-        )),
+        Term::Rat(r) => Ok(rat_expr(r, numbers)),
+        Term::Complex(c) => {
+            let (re, im) = c.as_ref();
+            let i = Expr::Ident("i".into());
+            let b = if im.abs().is_one() {
+                i
+            } else {
+                Expr::BinOp(Op::Mul, Box::new(rat_expr(&im.abs(), numbers)), Box::new(i))
+            };
+            Ok(match (re.is_zero(), im.is_negative()) {
+                (true, false) => b,
+                (true, true) => Expr::UnaryOp(UnaryOp::Neg, Box::new(b)),
+                (false, false) => Expr::BinOp(Op::Add, Box::new(rat_expr(re, numbers)), Box::new(b)),
+                (false, true) => Expr::BinOp(Op::Sub, Box::new(rat_expr(re, numbers)), Box::new(b)),
+            })
+        }
+        Term::Residue(r) => Ok(Expr::Residue(BigInt::from(r.0.clone()), BigInt::from(r.1.clone()))),
         Term::Var(s) => Ok(Expr::Ident(s.to_string())),
         Term::App(head, args) => {
-            // Unary negation
-            if head.as_ref() == "-" && args.len() == 1 {
-                return Ok(Expr::UnaryOp(
-                    UnaryOp::Neg,
-                    Box::new(to_surface(&args[0])?),
+            if let Some(b) = as_truth(t) {
+                return Ok(Expr::Bool(b));
+            }
+            if let Some((c, a, b)) = as_cond(t) {
+                return Ok(Expr::If(
+                    Box::new(to_surface_in(c, ops, numbers)?),
+                    Box::new(to_surface_in(a, ops, numbers)?),
+                    Box::new(to_surface_in(b, ops, numbers)?),
                 ));
             }
+            if let Some((head, var, lo, hi, body)) = as_big_op(t) {
+                let op = if head.as_ref() == "∏" { BigOp::Product } else { BigOp::Sum };
+                return Ok(Expr::BigOp(
+                    op,
+                    var.to_string(),
+                    Box::new(to_surface_in(lo, ops, numbers)?),
+                    Box::new(to_surface_in(hi, ops, numbers)?),
+                    Box::new(to_surface_in(body, ops, numbers)?),
+                ));
+            }
+            // Unary negation, arithmetic and logical
+            if args.len() == 1 {
+                let op = match head.as_ref() {
+                    "-" => Some(UnaryOp::Neg),
+                    "¬" => Some(UnaryOp::Not),
+                    _ => None,
+                };
+                if let Some(op) = op {
+                    let operand = to_surface_in(&args[0], ops, numbers)?;
+                    return Ok(Expr::UnaryOp(op, Box::new(operand)));
+                }
+            }
             // Known infix operators
             if let Some(op) = op_for(head) {
                 match args.len() {
                     2 => {
                         return Ok(Expr::BinOp(
                             op,
-                            Box::new(to_surface(&args[0])?),
-                            Box::new(to_surface(&args[1])?),
+                            Box::new(to_surface_in(&args[0], ops, numbers)?),
+                            Box::new(to_surface_in(&args[1], ops, numbers)?),
                         ))
                     }
                     n if n > 2 => {
                         let mut it = args.iter();
-                        let mut acc = to_surface(it.next().unwrap())?;
+                        let mut acc = to_surface_in(it.next().unwrap(), ops, numbers)?;
                         for a in it {
-                            acc = Expr::BinOp(op, Box::new(acc), Box::new(to_surface(a)?));
+                            let rhs = to_surface_in(a, ops, numbers)?;
+                            acc = Expr::BinOp(op, Box::new(acc), Box::new(rhs));
                         }
                         return Ok(acc);
                     }
                     _ => {}
                 }
             }
+            // User-declared infix operators
+            if let Some(op) = ops.get(head) {
+                if args.len() >= 2 {
+                    let mut it = args.iter();
+                    let mut acc = to_surface_in(it.next().unwrap(), ops, numbers)?;
+                    for a in it {
+                        let rhs = to_surface_in(a, ops, numbers)?;
+                        acc = Expr::Infix(op.clone(), Box::new(acc), Box::new(rhs));
+                    }
+                    return Ok(acc);
+                }
+            }
             // Function application: f(a, b, ...)
-            let surf_args: Result<Vec<_>, _> = args.iter().map(to_surface).collect();
+            let surf_args: Result<Vec<_>, _> =
+                args.iter().map(|a| to_surface_in(a, ops, numbers)).collect();
             Ok(Expr::App(head.to_string(), surf_args?))
         }
     }
 }
 
+/// A rational literal: an integer, a decimal, or `numerator / denominator`.
+fn rat_expr(r: &BigRational, numbers: Numbers) -> Expr {
+    if r.is_integer() {
+        Expr::Int(r.to_integer())
+    } else if numbers == Numbers::Decimals && decimal(r, MAX_PERIOD).is_some() {
+        Expr::Decimal(r.clone())
+    } else {
+        Expr::BinOp(
+            Op::Div,
+            Box::new(Expr::Int(r.numer().clone())),
+            Box::new(Expr::Int(r.denom().clone())),
+        )
+    }
+}
+
 fn op_for(head: &str) -> Option<Op> {
     match head {
         "+" => Some(Op::Add),
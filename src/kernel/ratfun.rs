@@ -0,0 +1,237 @@
+//! Rational functions: quotients of polynomials, the normal form for field
+//! expressions.
+//!
+//! A term built from `+`, `-`, `·`, `/` and `^` with integer exponents is read
+//! as a numerator and a denominator polynomial (see `kernel::poly`). Sums get
+//! the least common multiple of their denominators, and nothing else is
+//! cancelled, so the denominator of the result vanishes exactly where one in
+//! the term does: `together` changes the form of a term but not where it is
+//! defined. Both polynomials are scaled to coprime integer coefficients, with
+//! the denominator's leading coefficient positive. Even a zero numerator
+//! keeps its denominator: `1/x - 1/x` is `0 / x`.
+//!
+//! `cancel` divides numerator and denominator by their GCD. That extends the
+//! function to points where the cancelled factor vanishes, `(x^2 - 1)/(x - 1)`
+//! to `x + 1` at `x = 1`, so it is only sound where the original denominator
+//! is nonzero, and that side condition is returned with the result:
+//! `1/x - 1/x` cancels to `0` if `x ≠ 0`. A division by zero is an error.
+
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::kernel::algebraic::reduce_radicals;
+use crate::kernel::eval::{rat_to_term, term_to_rat, EvalError};
+use crate::kernel::poly::{content, grlex, is_ring_head, Poly, MAX_POWER};
+use crate::kernel::term::{sym, Term};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RatFun {
+    num: Poly,
+    den: Poly,
+}
+
+impl RatFun {
+    pub fn from_poly(p: Poly) -> RatFun {
+        RatFun::new(p, Poly::constant(BigRational::one()))
+    }
+
+    /// `num / den` scaled to its normal form, without cancelling.
+    fn new(num: Poly, den: Poly) -> RatFun {
+        let mut k = content(num.terms().chain(den.terms()).map(|(_, c)| c));
+        let leading = den.terms().max_by(|a, b| grlex(a.0, b.0)).map(|(_, c)| c);
+        if leading.is_some_and(|c| c.is_negative()) {
+            k = -k;
+        }
+        let k = k.recip();
+        RatFun {
+            num: num.scale(&k),
+            den: den.scale(&k),
+        }
+    }
+
+    pub fn numerator(&self) -> &Poly {
+        &self.num
+    }
+
+    pub fn denominator(&self) -> &Poly {
+        &self.den
+    }
+
+    /// Read `t` as a rational function. Subterms that are not field
+    /// operations, and divisions by zero, become indeterminates.
+    pub fn from_term(t: &Term) -> RatFun {
+        if let Some(r) = term_to_rat(t) {
+            return RatFun::from_poly(Poly::constant(r));
+        }
+        if let Term::App(head, args) = t {
+            match (head.as_ref(), args.as_slice()) {
+                ("+", [first, rest @ ..]) => {
+                    return rest.iter().fold(RatFun::from_term(first), |acc, a| {
+                        acc.add(&RatFun::from_term(a))
+                    })
+                }
+                ("·", [first, rest @ ..]) => {
+                    return rest.iter().fold(RatFun::from_term(first), |acc, a| {
+                        acc.mul(&RatFun::from_term(a))
+                    })
+                }
+                ("-", [a]) => return RatFun::from_term(a).neg(),
+                ("-", [a, b]) => return RatFun::from_term(a).add(&RatFun::from_term(b).neg()),
+                ("/", [a, b]) => {
+                    if let Some(q) = RatFun::from_term(a).div(&RatFun::from_term(b)) {
+                        return q;
+                    }
+                }
+                ("^", [b, e]) => {
+                    let e = term_to_rat(e)
+                        .filter(|e| e.is_integer())
+                        .and_then(|e| e.to_integer().to_i64());
+                    let base = RatFun::from_term(b);
+                    if let Some(p) = e.and_then(|e| base.pow(e)) {
+                        return p;
+                    }
+                }
+                _ => {}
+            }
+        }
+        RatFun::from_poly(Poly::var(t.clone()))
+    }
+
+    fn add(&self, other: &RatFun) -> RatFun {
+        // Over the least common multiple of the denominators.
+        let g = self.den.gcd(&other.den);
+        let a = self.den.div_exact(&g).expect("gcd divides");
+        let b = other.den.div_exact(&g).expect("gcd divides");
+        RatFun::new(&(&self.num * &b) + &(&other.num * &a), &self.den * &b)
+    }
+
+    fn mul(&self, other: &RatFun) -> RatFun {
+        RatFun::new(&self.num * &other.num, &self.den * &other.den)
+    }
+
+    fn neg(&self) -> RatFun {
+        RatFun {
+            num: -&self.num,
+            den: self.den.clone(),
+        }
+    }
+
+    /// `self / other`, unless `other` is zero. The denominators of `other`
+    /// stay in the result, which is undefined where they vanish.
+    fn div(&self, other: &RatFun) -> Option<RatFun> {
+        if other.num.is_zero() {
+            return None;
+        }
+        let den = &(&self.den * &other.num) * &other.den;
+        Some(RatFun::new(&(&self.num * &other.den) * &other.den, den))
+    }
+
+    fn pow(&self, e: i64) -> Option<RatFun> {
+        let n = u32::try_from(e.unsigned_abs())
+            .ok()
+            .filter(|n| *n <= MAX_POWER)?;
+        let p = RatFun::new(self.num.pow(n), self.den.pow(n));
+        match e {
+            0.. => Some(p),
+            _ => RatFun::from_poly(Poly::constant(BigRational::one())).div(&p),
+        }
+    }
+
+    /// Numerator and denominator with square roots of integers multiplied
+    /// out (see `algebraic::reduce_radicals`).
+    pub(crate) fn reduce_radicals(&self) -> RatFun {
+        RatFun::new(reduce_radicals(&self.num), reduce_radicals(&self.den))
+    }
+
+    /// Divide out the GCD of numerator and denominator. Returns the reduced
+    /// function and, if a nonconstant factor was cancelled, the condition
+    /// under which the two agree: the original denominator is nonzero.
+    pub fn cancel(&self) -> (RatFun, Option<Term>) {
+        let g = self.num.gcd(&self.den);
+        if g.as_constant().is_some() {
+            return (self.clone(), None);
+        }
+        let num = self.num.div_exact(&g).expect("gcd divides");
+        let den = self.den.div_exact(&g).expect("gcd divides");
+        (RatFun::new(num, den), Some(nonzero(&self.den)))
+    }
+
+    /// `num / den`, or the polynomial itself when the denominator is a
+    /// constant, as `Poly::to_term` writes it.
+    pub fn to_term(&self) -> Term {
+        match self.den.as_constant() {
+            Some(c) => self.num.scale(&c.recip()).to_term(),
+            None => Term::App(sym("/"), vec![self.num.to_term(), self.den.to_term()]),
+        }
+    }
+}
+
+/// `p ≠ 0`, solved for the indeterminate when `p` is linear in a single one:
+/// `x - 1 ≠ 0` is written `x ≠ 1`.
+pub fn nonzero(p: &Poly) -> Term {
+    let zero = Term::Nat(0u32.into());
+    let mut linear = None;
+    let mut constant = BigRational::zero();
+    for (m, c) in p.terms() {
+        match m.iter().collect::<Vec<_>>().as_slice() {
+            [] => constant = c.clone(),
+            [(x, 1)] if linear.is_none() => linear = Some(((*x).clone(), c.clone())),
+            _ => return Term::App(sym("≠"), vec![p.to_term(), zero]),
+        }
+    }
+    match linear {
+        Some((x, a)) => Term::App(sym("≠"), vec![x, rat_to_term(-constant / a)]),
+        None => Term::App(sym("≠"), vec![p.to_term(), zero]),
+    }
+}
+
+/// `t` with every field subterm as a single fraction; see the module notes.
+/// Arguments of other heads are combined in place.
+pub fn together(t: &Term) -> Term {
+    match t {
+        Term::App(head, args) => {
+            let t = Term::App(head.clone(), args.iter().map(together).collect());
+            if is_ring_head(head) {
+                RatFun::from_term(&t).to_term()
+            } else {
+                t
+            }
+        }
+        _ => t.clone(),
+    }
+}
+
+/// `together`, with common factors cancelled from every fraction. Returns the
+/// side conditions that make the result equal to `t`, one per cancellation.
+/// A division by zero is an error.
+pub fn cancel(t: &Term) -> Result<(Term, Vec<Term>), EvalError> {
+    let mut conditions = Vec::new();
+    let t = cancel_into(t, &mut conditions)?;
+    Ok((t, conditions))
+}
+
+fn cancel_into(t: &Term, conditions: &mut Vec<Term>) -> Result<Term, EvalError> {
+    match t {
+        Term::App(head, args) => {
+            let args = args
+                .iter()
+                .map(|a| cancel_into(a, conditions))
+                .collect::<Result<Vec<_>, _>>()?;
+            if head.as_ref() == "/" && args.len() == 2 && RatFun::from_term(&args[1]).num.is_zero() {
+                return Err(EvalError("division by zero".into()));
+            }
+            let t = Term::App(head.clone(), args);
+            if !is_ring_head(head) {
+                return Ok(t);
+            }
+            let (q, condition) = RatFun::from_term(&t).cancel();
+            if let Some(c) = condition {
+                if !conditions.contains(&c) {
+                    conditions.push(c);
+                }
+            }
+            Ok(q.to_term())
+        }
+        _ => Ok(t.clone()),
+    }
+}
@@ -1,12 +1,23 @@
 use std::collections::HashMap;
 
 use num_rational::BigRational;
-use num_traits::{One, ToPrimitive, Zero};
+use num_traits::{One, Zero};
 
-use crate::kernel::eval::{rat_to_term, term_to_rat};
+use crate::kernel::algebraic;
+use crate::kernel::bigop;
+use crate::kernel::eval::{
+    complex_add, complex_mul, complex_to_term, fold_complex, term_to_complex, Complex,
+};
+use crate::kernel::eval::{compare_literals, fold_literal_power, fold_radical_product, rat_to_term, term_to_rat};
+use crate::kernel::kbo::{kbo, KboOrd};
+use crate::kernel::logic::{complement, logic_fold, piecewise};
+use crate::kernel::modular;
+use crate::kernel::numtheory;
 use crate::kernel::pmatch::pmatch;
-use crate::kernel::subst::subst;
-use crate::kernel::term::{Symbol, Term};
+use crate::kernel::poly::{expand, is_ring_head, Poly};
+use crate::kernel::ratfun::RatFun;
+use crate::kernel::subst::{subst, subst_match};
+use crate::kernel::term::{as_big_op, as_cond, as_truth, big_op, sym, truth, Symbol, Term};
 use crate::kernel::theory::Theory;
 
 pub use crate::kernel::theory::{orient, Orient, Rule};
@@ -18,48 +29,197 @@ pub use crate::kernel::theory::{orient, Orient, Rule};
 ///    This allows rules like `log(a·b) = log(a) + log(b)` to fire before
 ///    `a·b` is collapsed to a single literal.
 /// 2. If no top-level rule fires, simplify children bottom-up, then fold
-///    literal arithmetic, AC-normalize, and absorb identity elements.
-/// 3. Try user rules again on the bottom-up simplified result.
+///    literal arithmetic and propositional structure (see `kernel::logic`),
+///    decide memberships in predicate sets and atoms implied by the theory's
+///    `Implication`s, AC-normalize, and absorb identity elements. When
+///    `Theory::is_ring` holds, ring subterms are then put in polynomial
+///    normal form (see `kernel::poly`). Finite sums and products with
+///    literal bounds are expanded (see `kernel::bigop`).
+/// 3. Replace a result that a local hypothesis states or refutes by `True`
+///    or `False` (see `simplify_assuming`), or else try user rules again on
+///    the bottom-up simplified result.
 ///
 /// KBO orientation guarantees that every rule strictly decreases term weight,
-/// so the loop terminates.
+/// so the loop terminates. Side conditions are themselves discharged by
+/// `simplify`, within a nesting budget (see `condition_holds`). Once a
+/// conditional rule fires, its instantiated condition is assumed while the
+/// result is simplified further.
 pub fn simplify(t: &Term, theory: &Theory) -> Term {
-    // Pass 1: try rules before recursing (top-down)
-    for r in &theory.rules {
-        if let Some(sigma) = pmatch(&r.lhs, t) {
-            if condition_ok(r.condition.as_ref(), &sigma, theory) {
-                let t2 = subst(&r.rhs, &sigma);
-                return simplify(&t2, theory);
+    simplify_at(t, theory, &[], CONDITION_DEPTH)
+}
+
+/// Simplify `t` with every ring subterm in polynomial normal form, whether or
+/// not the theory has made `+` and `·` AC. The term is expanded before
+/// simplifying, so rules see expanded arguments, and again after, so their
+/// results are expanded too.
+pub fn normalize(t: &Term, theory: &Theory) -> Term {
+    expand(&simplify(&expand(t), theory))
+}
+
+/// Simplify `t` assuming the proposition `assumption`. Its conjuncts become
+/// local hypotheses that decide matching atoms — in side conditions as well
+/// as in `t` itself — before the theory is consulted. A conjunct `x = e`
+/// whose left side is a variable not occurring in `e` is also used to replace
+/// `x` by `e` in `t`.
+pub fn simplify_assuming(t: &Term, assumption: &Term, theory: &Theory) -> Term {
+    let (t, hyps) = assume(t, assumption, theory);
+    simplify_at(&t, theory, &hyps, CONDITION_DEPTH)
+}
+
+/// The hypotheses of `assumption`, and `t` with its variable equations
+/// substituted; see `simplify_assuming`.
+pub(crate) fn assume(t: &Term, assumption: &Term, theory: &Theory) -> (Term, Vec<Term>) {
+    let hyps = hypotheses(&simplify(assumption, theory));
+    let mut sigma = HashMap::new();
+    for h in &hyps {
+        if let Term::App(head, args) = h {
+            if let ("=", [Term::Var(x), e]) = (head.as_ref(), args.as_slice()) {
+                if !occurs(x, e) {
+                    sigma.entry(x.clone()).or_insert_with(|| e.clone());
+                }
             }
         }
     }
+    (subst(t, &sigma), hyps)
+}
+
+/// `simplify` under local hypotheses `hyps`, with `depth` levels of
+/// side-condition nesting left.
+pub(crate) fn simplify_at(t: &Term, theory: &Theory, hyps: &[Term], depth: usize) -> Term {
+    // Pass 1: try rules before recursing (top-down)
+    if let Some(t2) = rewrite_root(t, theory, hyps, depth) {
+        return t2;
+    }
     // Pass 2: bottom-up — simplify children, arithmetic, AC, identities
     let t1 = match t {
-        Term::Nat(_) | Term::Var(_) | Term::Int(_) | Term::Rat(_) => t.clone(),
+        Term::Nat(_) | Term::Var(_) | Term::Int(_) | Term::Rat(_) | Term::Complex(..) | Term::Residue(..) => {
+            t.clone()
+        }
+        Term::App(head, args) if head.as_ref() == "if" && args.len() == 3 => {
+            conditional(&args[0], &args[1], &args[2], theory, hyps, depth)
+        }
+        Term::App(..) if as_big_op(t).is_some() => big_op_at(t, theory, hyps, depth),
         Term::App(head, args) => {
-            let new_args: Vec<Term> = args.iter().map(|a| simplify(a, theory)).collect();
+            let new_args: Vec<Term> =
+                args.iter().map(|a| simplify_at(a, theory, hyps, depth)).collect();
             let folded = arith_fold(head, new_args);
-            normalize_app(folded, theory)
+            let folded = fold_atom(folded, theory, hyps, depth);
+            let t1 = ring_normalize(normalize_app(folded, theory), theory, hyps, depth);
+            into_branches(&t1, theory, hyps, depth).unwrap_or(t1)
         }
     };
+    if let Some(b) = assumed(&t1, hyps) {
+        return truth(b);
+    }
     // Pass 3: try rules on the bottom-up simplified result
+    rewrite_root(&t1, theory, hyps, depth).unwrap_or(t1)
+}
+
+/// Simplify `if c then a else b`: the condition first, then only the branch
+/// it selects, or, while it is undecided, each branch under the condition or
+/// its complement as an extra hypothesis.
+fn conditional(
+    c: &Term,
+    a: &Term,
+    b: &Term,
+    theory: &Theory,
+    hyps: &[Term],
+    depth: usize,
+) -> Term {
+    let c = simplify_at(c, theory, hyps, depth);
+    match as_truth(&c) {
+        Some(true) => simplify_at(a, theory, hyps, depth),
+        Some(false) => simplify_at(b, theory, hyps, depth),
+        None => {
+            let mut yes = hyps.to_vec();
+            yes.extend(hypotheses(&c));
+            let mut no = hyps.to_vec();
+            no.extend(hypotheses(&complement(&c)));
+            let a = simplify_at(a, theory, &yes, depth);
+            let b = simplify_at(b, theory, &no, depth);
+            piecewise(c, a, b)
+        }
+    }
+}
+
+/// Simplify a finite sum or product: its bounds and body, then its terms
+/// when the bounds are integer literals (combined with literal arithmetic
+/// and AC normalization only), or its one term when they are equal. A sum
+/// too long to expand is left whole.
+fn big_op_at(t: &Term, theory: &Theory, hyps: &[Term], depth: usize) -> Term {
+    let (head, var, lo, hi, body) = as_big_op(t).expect("a sum or product");
+    let lo = simplify_at(lo, theory, hyps, depth);
+    let hi = simplify_at(hi, theory, hyps, depth);
+    if let Ok(Some(terms)) = bigop::instances(var, &lo, &hi, body) {
+        let (op, unit) = bigop::operator(head);
+        let op = sym(op);
+        return terms
+            .iter()
+            .map(|term| simplify_at(term, theory, hyps, depth))
+            .reduce(|acc, term| normalize_app(arith_fold(&op, vec![acc, term]), theory))
+            .unwrap_or(unit);
+    }
+    if let Some(one) = bigop::single(var, &lo, &hi, body) {
+        return simplify_at(&one, theory, hyps, depth);
+    }
+    let body = simplify_at(body, theory, hyps, depth);
+    big_op(head, var.clone(), lo, hi, body)
+}
+
+/// Push an arithmetic operation applied to a piecewise argument into its
+/// branches, `(if c then a else b) + k` to `if c then a + k else b + k`, when
+/// the simplified result is smaller in the term order. Duplicating the other
+/// arguments usually makes the term heavier, so this mostly fires when the
+/// branches fold to literals.
+fn into_branches(t: &Term, theory: &Theory, hyps: &[Term], depth: usize) -> Option<Term> {
+    let (head, args) = match t {
+        Term::App(head, args) if matches!(head.as_ref(), "+" | "-" | "·" | "/" | "^") => {
+            (head, args)
+        }
+        _ => return None,
+    };
+    let i = args.iter().position(|a| as_cond(a).is_some())?;
+    let (c, a, b) = as_cond(&args[i])?;
+    let with = |branch: &Term| {
+        let mut new_args = args.clone();
+        new_args[i] = branch.clone();
+        Term::App(head.clone(), new_args)
+    };
+    let candidate = conditional(c, &with(a), &with(b), theory, hyps, depth);
+    (kbo(t, &candidate) == KboOrd::Gt).then_some(candidate)
+}
+
+/// Fire the first rule whose pattern matches `t` at the root and whose
+/// condition holds, and simplify the result with the instantiated condition
+/// added to the hypotheses.
+fn rewrite_root(t: &Term, theory: &Theory, hyps: &[Term], depth: usize) -> Option<Term> {
     for r in &theory.rules {
-        if let Some(sigma) = pmatch(&r.lhs, &t1) {
-            if condition_ok(r.condition.as_ref(), &sigma, theory) {
-                let t2 = subst(&r.rhs, &sigma);
-                return simplify(&t2, theory);
+        if let Some(sigma) = pmatch(&r.lhs, t) {
+            if condition_ok(r.condition.as_ref(), &sigma, theory, hyps, depth) {
+                let t2 = match bigop::instantiate(&r.lhs, &r.rhs, &sigma) {
+                    Some(t2) => t2,
+                    None => continue,
+                };
+                return Some(match &r.condition {
+                    Some(c) => {
+                        let mut inner = hyps.to_vec();
+                        inner.extend(hypotheses(&subst_match(c, &sigma)));
+                        simplify_at(&t2, theory, &inner, depth)
+                    }
+                    None => simplify_at(&t2, theory, hyps, depth),
+                });
             }
         }
     }
-    t1
+    None
 }
 
 /// Apply the equality `lhs = rhs` as a single rewrite step to `target`,
 /// trying the top level first, then leftmost-outermost. Returns `None` if
 /// no subterm matches.
 pub fn apply_eq(lhs: &Term, rhs: &Term, target: &Term) -> Option<Term> {
-    if let Some(sigma) = pmatch(lhs, target) {
-        return Some(subst(rhs, &sigma));
+    if let Some(t) = pmatch(lhs, target).and_then(|sigma| bigop::instantiate(lhs, rhs, &sigma)) {
+        return Some(t);
     }
     match target {
         Term::App(head, args) => {
@@ -88,8 +248,10 @@ pub fn apply_eq_conditional(
     theory: &Theory,
 ) -> Option<Term> {
     if let Some(sigma) = pmatch(lhs, target) {
-        if condition_ok(cond, &sigma, theory) {
-            return Some(subst(rhs, &sigma));
+        if condition_ok(cond, &sigma, theory, &[], CONDITION_DEPTH) {
+            if let Some(t) = bigop::instantiate(lhs, rhs, &sigma) {
+                return Some(t);
+            }
         }
     }
     match target {
@@ -109,58 +271,210 @@ pub fn apply_eq_conditional(
 
 /// Check whether a condition (before or after substitution) holds.
 /// `cond` is `Option<&Term>` so callers can pass `r.condition.as_ref()`.
-fn condition_ok(cond: Option<&Term>, sigma: &HashMap<Symbol, Term>, theory: &Theory) -> bool {
+fn condition_ok(
+    cond: Option<&Term>,
+    sigma: &HashMap<Symbol, Term>,
+    theory: &Theory,
+    hyps: &[Term],
+    depth: usize,
+) -> bool {
     match cond {
         None => true,
         Some(c) => {
-            let c_inst = subst(c, sigma);
-            condition_holds(&c_inst, theory)
+            let c_inst = subst_match(c, sigma);
+            condition_holds(&c_inst, theory, hyps, depth)
         }
     }
 }
 
-/// Evaluate a condition term and return whether it is verifiably true.
-/// Handles: `∧`, `∨`, `∈` (with predicate-set lookup), and numeric
-/// comparisons (`=`, `≠`, `<`, `≤`, `>`, `≥`) on closed rational literals.
-/// Returns `false` conservatively when any part cannot be decided.
-fn condition_holds(t: &Term, theory: &Theory) -> bool {
-    match t {
-        Term::App(head, args) if args.len() == 2 => match head.as_ref() {
-            "∧" => condition_holds(&args[0], theory) && condition_holds(&args[1], theory),
-            "∨" => condition_holds(&args[0], theory) || condition_holds(&args[1], theory),
-            "∈" => check_membership(&args[0], &args[1], theory),
-            _ => {
-                let a = term_to_rat(&args[0]);
-                let b = term_to_rat(&args[1]);
-                match (head.as_ref(), a, b) {
-                    ("=",  Some(a), Some(b)) => a == b,
-                    ("≠",  Some(a), Some(b)) => a != b,
-                    (">",  Some(a), Some(b)) => a > b,
-                    ("<",  Some(a), Some(b)) => a < b,
-                    ("≥",  Some(a), Some(b)) => a >= b,
-                    ("≤",  Some(a), Some(b)) => a <= b,
-                    _ => false,
-                }
-            }
+/// How deeply side conditions may nest: discharging a condition simplifies
+/// it, which may fire further conditional rules or implications, each of
+/// which spends one level. Bounds the search when rules and implications feed
+/// each other (`x > 0 ⇒ x ≠ 0`, …) or loop.
+pub(crate) const CONDITION_DEPTH: usize = 8;
+
+/// Whether a condition is verifiably true: it is simplified under `theory`
+/// and accepted only if it reduces to `True`. Anything else — `False`, or a
+/// residual the simplifier cannot decide — blocks the rule. Out of budget,
+/// every condition blocks.
+fn condition_holds(t: &Term, theory: &Theory, hyps: &[Term], depth: usize) -> bool {
+    depth > 0 && as_truth(&simplify_at(t, theory, hyps, depth - 1)) == Some(true)
+}
+
+/// Forward reasoning through Horn-clause facts: `atom` holds if it is an
+/// instance of some implication's conclusion whose premise, instantiated by
+/// the same match, holds in turn.
+fn implied(atom: &Term, theory: &Theory, hyps: &[Term], depth: usize) -> bool {
+    theory.implications.iter().any(|imp| match pmatch(&imp.conclusion, atom) {
+        Some(sigma) => condition_ok(imp.premise.as_ref(), &sigma, theory, hyps, depth),
+        None => false,
+    })
+}
+
+/// Rewrite a membership or comparison atom to `True` when the theory's
+/// implications establish it, and `e ∈ S` for a predicate-defined set
+/// `S = {x ∈ D | P}` to the truth value of `P[x := e]` when the simplifier
+/// decides it. The domain `D` is only checked for a refutation: membership in
+/// an opaque domain is informational, as for `∀` binders. Undecided atoms are
+/// left as they are rather than unfolded.
+fn fold_atom(t: Term, theory: &Theory, hyps: &[Term], depth: usize) -> Term {
+    let is_atom = matches!(&t, Term::App(head, args) if args.len() == 2
+        && matches!(head.as_ref(), "∈" | "≠" | "<" | "≤" | ">" | "≥"));
+    if !is_atom {
+        return t;
+    }
+    if implied(&t, theory, hyps, depth) {
+        return truth(true);
+    }
+    let ps = match &t {
+        Term::App(head, args) if head.as_ref() == "∈" => match &args[1] {
+            Term::Var(s) => theory.predicate_sets.get(s),
+            _ => None,
         },
-        _ => false,
+        _ => None,
+    };
+    let (ps, elem) = match (ps, &t) {
+        (Some(ps), Term::App(_, args)) => (ps, &args[0]),
+        _ => return t,
+    };
+    if depth == 0 {
+        return t;
+    }
+    let in_domain = Term::App(sym("∈"), vec![elem.clone(), ps.domain.clone()]);
+    if as_truth(&simplify_at(&in_domain, theory, hyps, depth - 1)) == Some(false) {
+        return truth(false);
+    }
+    let mut sigma = HashMap::new();
+    sigma.insert(ps.var.clone(), elem.clone());
+    match as_truth(&simplify_at(&subst(&ps.pred, &sigma), theory, hyps, depth - 1)) {
+        Some(b) => truth(b),
+        None => t,
+    }
+}
+
+/// Split a proposition into the hypotheses it asserts: its conjuncts, leaving
+/// out any `True`.
+pub(crate) fn hypotheses(c: &Term) -> Vec<Term> {
+    match c {
+        Term::App(head, args) if head.as_ref() == "∧" => args.iter().flat_map(hypotheses).collect(),
+        _ if as_truth(c) == Some(true) => Vec::new(),
+        _ => vec![c.clone()],
+    }
+}
+
+/// Decide `atom` from the local hypotheses alone: a hypothesis may state it
+/// or its negation outright (up to mirroring `a < b` as `b > a`), or bound
+/// the same term by a literal tightly enough to settle it (`x > 2` gives
+/// `x ≠ 0` and `x ≥ 1`, and refutes `x < 1`).
+fn assumed(atom: &Term, hyps: &[Term]) -> Option<bool> {
+    if hyps.is_empty() {
+        return None;
     }
+    let negated = logic_fold(&sym("¬"), vec![atom.clone()]);
+    for h in hyps {
+        let mirrored = mirror(h);
+        if h == atom || mirrored.as_ref() == Some(atom) {
+            return Some(true);
+        }
+        if h == &negated || mirrored.as_ref() == Some(&negated) {
+            return Some(false);
+        }
+    }
+    let (t, op, c) = literal_bound(atom)?;
+    hyps.iter().find_map(|h| match literal_bound(h) {
+        Some((ht, hop, hc)) if ht == t => settle(hop, &hc, op, &c),
+        _ => None,
+    })
 }
 
-/// Check `elem ∈ set` by looking up the set's predicate definition and
-/// evaluating it at `elem`.
-fn check_membership(elem: &Term, set: &Term, theory: &Theory) -> bool {
-    let set_name = match set {
-        Term::Var(s) => s,
-        _ => return false,
+/// `a op b` with its sides swapped and the relation flipped to match.
+fn mirror(t: &Term) -> Option<Term> {
+    let (head, args) = match t {
+        Term::App(head, args) if args.len() == 2 => (head, args),
+        _ => return None,
     };
-    if let Some(ps) = theory.predicate_sets.get(set_name) {
-        let mut sigma = HashMap::new();
-        sigma.insert(ps.var.clone(), elem.clone());
-        let pred_inst = subst(&ps.pred, &sigma);
-        condition_holds(&pred_inst, theory)
-    } else {
-        false
+    let flipped = match head.as_ref() {
+        "=" | "≠" => head.as_ref(),
+        "<" => ">",
+        ">" => "<",
+        "≤" => "≥",
+        "≥" => "≤",
+        _ => return None,
+    };
+    Some(Term::App(sym(flipped), vec![args[1].clone(), args[0].clone()]))
+}
+
+/// A comparison of a non-literal term with a numeric literal, oriented as
+/// `term op literal`.
+fn literal_bound(atom: &Term) -> Option<(Term, &'static str, BigRational)> {
+    let oriented = match atom {
+        Term::App(_, args) if args.len() == 2 && term_to_rat(&args[0]).is_some() => mirror(atom)?,
+        _ => atom.clone(),
+    };
+    let (head, mut args) = match oriented {
+        Term::App(head, args) if args.len() == 2 => (head, args),
+        _ => return None,
+    };
+    let op = match head.as_ref() {
+        "=" => "=",
+        "≠" => "≠",
+        "<" => "<",
+        ">" => ">",
+        "≤" => "≤",
+        "≥" => "≥",
+        _ => return None,
+    };
+    let c = term_to_rat(&args[1])?;
+    let t = args.swap_remove(0);
+    if term_to_rat(&t).is_some() {
+        return None;
+    }
+    Some((t, op, c))
+}
+
+/// Given `t hop h`, what is known about `t op c`? `None` if it is not settled.
+fn settle(hop: &str, h: &BigRational, op: &str, c: &BigRational) -> Option<bool> {
+    match hop {
+        "=" => compare_literals(op, &rat_to_term(h.clone()), &rat_to_term(c.clone())),
+        "≠" if h == c => match op {
+            "=" => Some(false),
+            "≠" => Some(true),
+            _ => None,
+        },
+        ">" | "≥" => {
+            // t lies above h: strictly above c, or at least at c
+            let above = c < h || (c == h && hop == ">");
+            let at_least = c <= h;
+            match op {
+                ">" if above => Some(true),
+                "≥" if at_least => Some(true),
+                "<" if at_least => Some(false),
+                "≤" | "=" if above => Some(false),
+                "≠" if above => Some(true),
+                _ => None,
+            }
+        }
+        "<" | "≤" => {
+            let below = c > h || (c == h && hop == "<");
+            let at_most = c >= h;
+            match op {
+                "<" if below => Some(true),
+                "≤" if at_most => Some(true),
+                ">" if at_most => Some(false),
+                "≥" | "=" if below => Some(false),
+                "≠" if below => Some(true),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn occurs(x: &Symbol, t: &Term) -> bool {
+    match t {
+        Term::Var(y) => x == y,
+        Term::App(_, args) => args.iter().any(|a| occurs(x, a)),
+        _ => false,
     }
 }
 
@@ -176,6 +490,30 @@ fn normalize_app(t: Term, theory: &Theory) -> Term {
     }
 }
 
+/// The polynomial normal form of a ring subterm, when the theory has made
+/// `+` and `·` AC with identities; `t` unchanged otherwise. When it also has
+/// inverses, a field subterm becomes a single fraction instead, reduced by
+/// the GCD of numerator and denominator only if the denominator it had
+/// provably does not vanish. Terms with residues keep their AC form.
+fn ring_normalize(t: Term, theory: &Theory, hyps: &[Term], depth: usize) -> Term {
+    if modular::contains_residue(&t) {
+        return t;
+    }
+    match &t {
+        Term::App(head, _) if is_ring_head(head) && theory.is_field() => {
+            let q = RatFun::from_term(&t).reduce_radicals();
+            match q.cancel() {
+                (reduced, Some(c)) if condition_holds(&c, theory, hyps, depth) => reduced.to_term(),
+                _ => q.to_term(),
+            }
+        }
+        Term::App(head, _) if is_ring_head(head) && theory.is_ring() => {
+            algebraic::reduce_radicals(&Poly::from_term(&t)).to_term()
+        }
+        _ => t,
+    }
+}
+
 fn ac_normalize(head: &Symbol, args: Vec<Term>, theory: &Theory) -> Term {
     let mut flat: Vec<Term> = Vec::with_capacity(args.len());
     for a in args {
@@ -207,17 +545,21 @@ fn fold_literals(head: &Symbol, flat: &mut Vec<Term>) {
     if !is_add && !is_mul {
         return;
     }
-    let identity: BigRational = if is_add {
-        BigRational::zero()
+    if flat.iter().any(|x| matches!(x, Term::Residue(..))) {
+        modular::fold_literals(is_add, flat);
+        return;
+    }
+    let identity: Complex = if is_add {
+        (BigRational::zero(), BigRational::zero())
     } else {
-        BigRational::one()
+        (BigRational::one(), BigRational::zero())
     };
 
-    let mut acc: Option<BigRational> = None;
+    let mut acc: Option<Complex> = None;
     flat.retain(|x| {
-        if let Some(r) = term_to_rat(x) {
+        if let Some(r) = term_to_complex(x) {
             acc = Some(match acc.take() {
-                Some(a) => if is_add { a + r } else { a * r },
+                Some(a) => if is_add { complex_add(&a, &r) } else { complex_mul(&a, &r) },
                 None => r,
             });
             false
@@ -227,7 +569,7 @@ fn fold_literals(head: &Symbol, flat: &mut Vec<Term>) {
     });
     if let Some(r) = acc {
         if r != identity || flat.is_empty() {
-            flat.push(rat_to_term(r));
+            flat.push(complex_to_term(r));
         }
     }
 }
@@ -249,6 +591,14 @@ fn identity_drop_binary(head: Symbol, args: Vec<Term>, theory: &Theory) -> Term
 }
 
 fn arith_fold(head: &Symbol, args: Vec<Term>) -> Term {
+    if let Ok(Some(t)) = modular::fold(head, &args) {
+        return t;
+    }
+    if args.len() == 1 && head.as_ref() == "-" {
+        if let Some(a) = term_to_rat(&args[0]) {
+            return rat_to_term(-a);
+        }
+    }
     if args.len() == 2 {
         match head.as_ref() {
             "+" | "-" | "·" | "/" => {
@@ -264,14 +614,26 @@ fn arith_fold(head: &Symbol, args: Vec<Term>) -> Term {
                 }
             }
             "^" => {
-                if let (Term::Nat(a), Term::Nat(b)) = (&args[0], &args[1]) {
-                    if let Some(e) = b.to_u32() {
-                        return Term::Nat(a.pow(e));
-                    }
+                if let Ok(Some(t)) = fold_literal_power(&args[0], &args[1]) {
+                    return t;
                 }
             }
             _ => {}
         }
+        if head.as_ref() == "·" {
+            if let Ok(Some(t)) = fold_radical_product(&args[0], &args[1]) {
+                return t;
+            }
+        }
+    }
+    if let Ok(Some(t)) = fold_complex(head, &args) {
+        return t;
+    }
+    if let Ok(Some(t)) = algebraic::fold(head, &args) {
+        return t;
+    }
+    if let Ok(Some(t)) = numtheory::fold(head, &args) {
+        return t;
     }
-    Term::App(head.clone(), args)
+    logic_fold(head, args)
 }
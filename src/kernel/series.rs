@@ -0,0 +1,560 @@
+//! Truncated power series with exact rational coefficients, for
+//! `series e about x = a to order n`.
+//!
+//! A `Series` is `c0 + c1 · (x - a) + … + c(n-1) · (x - a) ^ (n - 1) +
+//! O((x - a) ^ n)`, kept as the vector of its `n` coefficients. Sums,
+//! differences, products and quotients of series are series to the smaller
+//! of the two orders; `compose` substitutes one series into another, and
+//! `reversion` gives the series of the inverse function. Two series are
+//! equal when they expand about the same point to the same order with the
+//! same coefficients.
+//!
+//! `series` expands a term. Literals, the variable and `+`, `-`, `·`, `/`
+//! and `^` are taken apart; a quotient by a series vanishing at `a` is
+//! expanded to a higher order first, so that `sin(x) / x` loses nothing.
+//! `exp`, `log`, `sin`, `cos` and `sqrt` are built in, each composed with
+//! the series of its argument, and need an argument whose value at `a`
+//! gives them a rational value (`0`, or `1` for `log`). Any other function
+//! of one argument is expanded by Taylor's formula: its derivatives come
+//! from `kernel::diff` and their values at the argument's value from
+//! `simplify`, so the theory must know them, as with `D(f) = f` and
+//! `f(0) = 1`.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::kernel::diff::derivative;
+use crate::kernel::eval::{evaluate, fold_power, rat_to_term, term_to_rat};
+use crate::kernel::poly::{Monomial, Poly};
+use crate::kernel::rewrite::simplify;
+use crate::kernel::subst::subst;
+use crate::kernel::term::{sym, Symbol, Term};
+use crate::kernel::theory::Theory;
+
+/// Largest order `series` expands to.
+pub const MAX_ORDER: u32 = 256;
+
+#[derive(Debug)]
+pub struct SeriesError(pub String);
+
+/// A power series in `var` about `point`, truncated after its coefficients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Series {
+    var: Symbol,
+    point: BigRational,
+    coeffs: Vec<BigRational>,
+}
+
+impl Series {
+    /// The constant `c` to order `order`.
+    pub fn constant(var: Symbol, point: BigRational, c: BigRational, order: u32) -> Self {
+        let mut coeffs = vec![BigRational::zero(); order as usize];
+        if let Some(c0) = coeffs.first_mut() {
+            *c0 = c;
+        }
+        Series { var, point, coeffs }
+    }
+
+    /// The variable itself, `a + (x - a)`, to order `order`.
+    pub fn variable(var: Symbol, point: BigRational, order: u32) -> Self {
+        let mut s = Series::constant(var, point.clone(), point, order);
+        if let Some(c1) = s.coeffs.get_mut(1) {
+            *c1 = BigRational::one();
+        }
+        s
+    }
+
+    pub fn var(&self) -> &Symbol {
+        &self.var
+    }
+
+    pub fn point(&self) -> &BigRational {
+        &self.point
+    }
+
+    /// The coefficients of `(x - a) ^ k` for `k` below the order.
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coeffs
+    }
+
+    /// The `n` of `O((x - a) ^ n)`.
+    pub fn order(&self) -> u32 {
+        self.coeffs.len() as u32
+    }
+
+    /// The exponent of the first nonzero coefficient; `None` when the series
+    /// is zero to its order.
+    pub fn valuation(&self) -> Option<u32> {
+        self.coeffs
+            .iter()
+            .position(|c| !c.is_zero())
+            .map(|k| k as u32)
+    }
+
+    /// Panics unless `other` is a series in the same variable about the same
+    /// point, the condition for adding or multiplying the two.
+    fn assert_compatible(&self, other: &Series) {
+        assert!(
+            self.var == other.var && self.point == other.point,
+            "series in `{}` about {} and in `{}` about {} do not combine",
+            self.var,
+            self.point,
+            other.var,
+            other.point
+        );
+    }
+
+    fn with(&self, coeffs: Vec<BigRational>) -> Series {
+        Series {
+            var: self.var.clone(),
+            point: self.point.clone(),
+            coeffs,
+        }
+    }
+
+    /// `self` to the lower order `order`.
+    pub fn truncate(&self, order: u32) -> Series {
+        self.with(self.coeffs.iter().take(order as usize).cloned().collect())
+    }
+
+    pub fn scale(&self, c: &BigRational) -> Series {
+        self.with(self.coeffs.iter().map(|x| x * c).collect())
+    }
+
+    /// `1 / self`; `None` when the constant term is zero.
+    pub fn recip(&self) -> Option<Series> {
+        let c0 = self.coeffs.first()?;
+        if c0.is_zero() {
+            return None;
+        }
+        let n = self.coeffs.len();
+        let mut out: Vec<BigRational> = Vec::with_capacity(n);
+        out.push(c0.recip());
+        for k in 1..n {
+            let mut s = BigRational::zero();
+            for j in 1..=k {
+                s += &self.coeffs[j] * &out[k - j];
+            }
+            out.push(-s / c0);
+        }
+        Some(self.with(out))
+    }
+
+    /// `self / other`. A common factor `(x - a) ^ v` of both is cancelled,
+    /// which lowers the order by `v`; `None` when `other` vanishes to a
+    /// higher power than `self`, or entirely.
+    pub fn div(&self, other: &Series) -> Option<Series> {
+        let order = self.order().min(other.order());
+        let v = other.truncate(order).valuation()?;
+        if self.coeffs.iter().take(v as usize).any(|c| !c.is_zero()) {
+            return None;
+        }
+        let shift = |s: &Series| s.with(s.coeffs[v as usize..order as usize].to_vec());
+        Some(&shift(self) * &shift(other).recip()?)
+    }
+
+    /// `self ^ k` for an integer `k`; `None` for a negative power of a
+    /// series with zero constant term.
+    pub fn powi(&self, k: i64) -> Option<Series> {
+        let base = if k < 0 { self.recip()? } else { self.clone() };
+        let mut out = Series::constant(
+            self.var.clone(),
+            self.point.clone(),
+            BigRational::one(),
+            self.order(),
+        );
+        let mut square = base;
+        let mut e = k.unsigned_abs();
+        while e > 0 {
+            if e & 1 == 1 {
+                out = &out * &square;
+            }
+            e >>= 1;
+            if e > 0 {
+                square = &square * &square;
+            }
+        }
+        Some(out)
+    }
+
+    /// `outer` evaluated at `self`: `outer` is a series about the constant
+    /// term of `self`, and the result expands about `self`'s point. `None`
+    /// when the points do not agree.
+    pub fn compose(&self, outer: &Series) -> Option<Series> {
+        let c0 = self.coeffs.first()?;
+        if *c0 != outer.point {
+            return None;
+        }
+        let order = self.order().min(outer.order());
+        let mut w = self.truncate(order);
+        w.coeffs[0] = BigRational::zero();
+        let mut acc = Series::constant(
+            self.var.clone(),
+            self.point.clone(),
+            BigRational::zero(),
+            order,
+        );
+        for c in outer.coeffs.iter().take(order as usize).rev() {
+            acc = &acc * &w;
+            acc.coeffs[0] += c;
+        }
+        Some(acc)
+    }
+
+    /// The series of the inverse function: with `self` about `a` taking the
+    /// value `b` there, the series `g` about `b` with `g(self) = x`. `None`
+    /// when the derivative at `a` is zero or the order is below 2.
+    pub fn reversion(&self) -> Option<Series> {
+        let n = self.coeffs.len();
+        let f1 = self.coeffs.get(1).filter(|c| !c.is_zero())?;
+        // F(t) = f(a + t) - b, and G with F(G(s)) = s, coefficient by
+        // coefficient: the s ^ m coefficient of F(G) is f1 · g_m plus terms
+        // in the g_k already found.
+        let f = self.with({
+            let mut c = self.coeffs.clone();
+            c[0] = BigRational::zero();
+            c
+        });
+        let mut g = Series::constant(
+            self.var.clone(),
+            BigRational::zero(),
+            BigRational::zero(),
+            n as u32,
+        );
+        g.coeffs[1] = f1.recip();
+        for m in 2..n {
+            let composed = g.compose(&f.at_zero())?;
+            g.coeffs[m] = -&composed.coeffs[m] / f1;
+        }
+        g.coeffs[0] = self.point.clone();
+        g.point = self.coeffs[0].clone();
+        Some(g)
+    }
+
+    /// `self` moved to the point `0`, keeping its coefficients.
+    fn at_zero(&self) -> Series {
+        Series {
+            var: self.var.clone(),
+            point: BigRational::zero(),
+            coeffs: self.coeffs.clone(),
+        }
+    }
+
+    /// The polynomial part in the normal form of `Poly::to_term`, with `x - a`
+    /// as its indeterminate, or `x` alone for `a = 0`, followed by the order
+    /// term, an application of `O`: `x ^ 2 · (1 / 2) + x + 1 + O(x ^ 3)`.
+    pub fn to_term(&self) -> Term {
+        let x = Term::Var(self.var.clone());
+        let base = if self.point.is_zero() {
+            x
+        } else {
+            let head = if self.point.is_positive() { "-" } else { "+" };
+            Term::App(sym(head), vec![x, rat_to_term(self.point.abs())])
+        };
+        let power = |k: usize| match k {
+            1 => base.clone(),
+            _ => Term::App(sym("^"), vec![base.clone(), Term::Nat(k.into())]),
+        };
+        let mut poly = Poly::zero();
+        for (k, c) in self.coeffs.iter().enumerate().filter(|(_, c)| !c.is_zero()) {
+            let mut m = Monomial::new();
+            if k > 0 {
+                m.insert(base.clone(), k as u32);
+            }
+            poly.add_term(m, c.clone());
+        }
+        let big_o = Term::App(sym("O"), vec![power(self.coeffs.len())]);
+        if poly.is_zero() {
+            big_o
+        } else {
+            Term::App(sym("+"), vec![poly.to_term(), big_o])
+        }
+    }
+}
+
+/// Sums and products need series in the same variable about the same point,
+/// and panic otherwise.
+impl Add for &Series {
+    type Output = Series;
+
+    fn add(self, other: &Series) -> Series {
+        self.assert_compatible(other);
+        let coeffs = self
+            .coeffs
+            .iter()
+            .zip(&other.coeffs)
+            .map(|(a, b)| a + b)
+            .collect();
+        self.with(coeffs)
+    }
+}
+
+impl Sub for &Series {
+    type Output = Series;
+
+    fn sub(self, other: &Series) -> Series {
+        self + &-other
+    }
+}
+
+impl Neg for &Series {
+    type Output = Series;
+
+    fn neg(self) -> Series {
+        self.with(self.coeffs.iter().map(|c| -c).collect())
+    }
+}
+
+impl Mul for &Series {
+    type Output = Series;
+
+    fn mul(self, other: &Series) -> Series {
+        self.assert_compatible(other);
+        let n = self.coeffs.len().min(other.coeffs.len());
+        let mut coeffs = vec![BigRational::zero(); n];
+        for (i, a) in self
+            .coeffs
+            .iter()
+            .take(n)
+            .enumerate()
+            .filter(|(_, a)| !a.is_zero())
+        {
+            for (j, b) in other.coeffs.iter().take(n - i).enumerate() {
+                coeffs[i + j] += a * b;
+            }
+        }
+        self.with(coeffs)
+    }
+}
+
+/// The series of `t` in `x` about `point`, to order `order`.
+pub fn series(
+    t: &Term,
+    x: &Symbol,
+    point: &BigRational,
+    order: u32,
+    theory: &Theory,
+) -> Result<Series, SeriesError> {
+    if order == 0 || order > MAX_ORDER {
+        return Err(SeriesError(format!(
+            "the order must be between 1 and {MAX_ORDER}"
+        )));
+    }
+    Expansion { x, point, theory }.expand(t, order)
+}
+
+struct Expansion<'a> {
+    x: &'a Symbol,
+    point: &'a BigRational,
+    theory: &'a Theory,
+}
+
+impl Expansion<'_> {
+    fn constant(&self, c: BigRational, order: u32) -> Series {
+        Series::constant(self.x.clone(), self.point.clone(), c, order)
+    }
+
+    fn expand(&self, t: &Term, order: u32) -> Result<Series, SeriesError> {
+        if !mentions(t, self.x) {
+            return self.rational(t).map(|c| self.constant(c, order));
+        }
+        let (head, args) = match t {
+            Term::Var(_) => return Ok(Series::variable(self.x.clone(), self.point.clone(), order)),
+            Term::App(head, args) => (head.as_ref(), args.as_slice()),
+            _ => unreachable!("literals do not mention the variable"),
+        };
+        match (head, args) {
+            ("+", _) => {
+                let mut sum = self.constant(BigRational::zero(), order);
+                for a in args {
+                    sum = &sum + &self.expand(a, order)?;
+                }
+                Ok(sum)
+            }
+            ("-", [a]) => Ok(-&self.expand(a, order)?),
+            ("-", [a, b]) => Ok(&self.expand(a, order)? - &self.expand(b, order)?),
+            ("·", _) => {
+                let mut product = self.constant(BigRational::one(), order);
+                for a in args {
+                    product = &product * &self.expand(a, order)?;
+                }
+                Ok(product)
+            }
+            ("/", [a, b]) => {
+                let den = self.expand(b, order)?;
+                let extra = den.valuation().unwrap_or(0);
+                let (num, den) = if extra == 0 {
+                    (self.expand(a, order)?, den)
+                } else {
+                    (
+                        self.expand(a, order + extra)?,
+                        self.expand(b, order + extra)?,
+                    )
+                };
+                num.div(&den).ok_or_else(|| self.pole())
+            }
+            ("^", [a, e]) if !mentions(e, self.x) => {
+                let r = self.rational(e)?;
+                let base = self.expand(a, order)?;
+                match r.to_integer().to_i64() {
+                    Some(k) if r.is_integer() => base.powi(k).ok_or_else(|| self.pole()),
+                    _ => self.power(&base, &r),
+                }
+            }
+            // f ^ g = exp(g · log(f))
+            ("^", [a, e]) => {
+                let log = Term::App(sym("log"), vec![a.clone()]);
+                let exponent = Term::App(sym("·"), vec![e.clone(), log]);
+                self.expand(&Term::App(sym("exp"), vec![exponent]), order)
+            }
+            ("sqrt", [a]) => {
+                let base = self.expand(a, order)?;
+                self.power(&base, &BigRational::new(1.into(), 2.into()))
+            }
+            (f, [a]) => {
+                let inner = self.expand(a, order)?;
+                let c = inner.coefficients()[0].clone();
+                let outer = self.function(f, &c, order)?;
+                Ok(inner
+                    .compose(&outer)
+                    .expect("outer series about the inner value"))
+            }
+            (f, _) => Err(SeriesError(format!("no series known for `{f}`"))),
+        }
+    }
+
+    /// The value of a term free of the variable, which must be rational.
+    fn rational(&self, t: &Term) -> Result<BigRational, SeriesError> {
+        let value = evaluate(t).map_err(|err| SeriesError(err.0))?;
+        term_to_rat(&value).ok_or_else(|| match t {
+            Term::Var(y) => SeriesError(format!(
+                "`{y}` is not a number; series coefficients must be rational"
+            )),
+            _ => SeriesError("series coefficients must be rational".into()),
+        })
+    }
+
+    fn pole(&self) -> SeriesError {
+        SeriesError(format!(
+            "no power series: pole at `{}` = {}",
+            self.x, self.point
+        ))
+    }
+
+    /// `base ^ r` for a non-integer rational `r`, about a positive value
+    /// whose `r`-th power is rational: `c ^ r · (1 + w / c) ^ r` by the
+    /// binomial series.
+    fn power(&self, base: &Series, r: &BigRational) -> Result<Series, SeriesError> {
+        let c = base.coefficients()[0].clone();
+        let head = c
+            .is_positive()
+            .then(|| fold_power(&c, r).ok().flatten())
+            .flatten()
+            .and_then(|t| term_to_rat(&t))
+            .ok_or_else(|| {
+                SeriesError(format!(
+                    "no power series with rational coefficients: ({c}) ^ ({r}) at `{}` = {}",
+                    self.x, self.point
+                ))
+            })?;
+        let mut coeffs = Vec::with_capacity(base.order() as usize);
+        let mut binomial = head;
+        for k in 0..base.order() {
+            coeffs.push(binomial.clone());
+            let k = BigRational::from_integer(k.into());
+            binomial = binomial * (r - &k) / ((&k + BigRational::one()) * &c);
+        }
+        let outer = Series {
+            var: base.var.clone(),
+            point: c,
+            coeffs,
+        };
+        Ok(base
+            .compose(&outer)
+            .expect("outer series about the inner value"))
+    }
+
+    /// The series of the function `f` about `c`, to order `order`.
+    fn function(&self, f: &str, c: &BigRational, order: u32) -> Result<Series, SeriesError> {
+        let n = order as usize;
+        let mut coeffs = vec![BigRational::zero(); n];
+        let mut factorial = BigRational::one();
+        let mut fill = |value: &dyn Fn(usize) -> Option<BigRational>| {
+            for (k, coeff) in coeffs.iter_mut().enumerate() {
+                if k > 0 {
+                    factorial *= BigRational::from_integer(BigInt::from(k));
+                }
+                if let Some(v) = value(k) {
+                    *coeff = v / &factorial;
+                }
+            }
+        };
+        let sign = |j: usize| {
+            if j.is_multiple_of(2) {
+                BigRational::one()
+            } else {
+                -BigRational::one()
+            }
+        };
+        match f {
+            "exp" if c.is_zero() => fill(&|_| Some(BigRational::one())),
+            "sin" if c.is_zero() => fill(&|k| (k % 2 == 1).then(|| sign(k / 2))),
+            "cos" if c.is_zero() => fill(&|k| (k % 2 == 0).then(|| sign(k / 2))),
+            // log(1 + t) = t - t ^ 2 / 2 + …
+            "log" if c.is_one() => {
+                for (k, coeff) in coeffs.iter_mut().enumerate().skip(1) {
+                    *coeff = sign(k - 1) / BigRational::from_integer(BigInt::from(k));
+                }
+            }
+            "exp" | "sin" | "cos" | "log" => {
+                return Err(SeriesError(format!(
+                    "no power series with rational coefficients: `{f}` at {c}"
+                )))
+            }
+            _ => coeffs = self.taylor(f, c, n)?,
+        }
+        Ok(Series {
+            var: self.x.clone(),
+            point: c.clone(),
+            coeffs,
+        })
+    }
+
+    /// Taylor coefficients of a function the theory knows: `f^(k)(c) / k!`,
+    /// with the derivatives from `kernel::diff` and their values from
+    /// `simplify`.
+    fn taylor(&self, f: &str, c: &BigRational, n: usize) -> Result<Vec<BigRational>, SeriesError> {
+        let y = sym("y");
+        let mut at = std::collections::HashMap::new();
+        at.insert(y.clone(), rat_to_term(c.clone()));
+        let mut d = Term::App(sym(f), vec![Term::Var(y.clone())]);
+        let mut factorial = BigRational::one();
+        let mut coeffs = Vec::with_capacity(n);
+        for k in 0..n {
+            if k > 0 {
+                d = derivative(&d, &y, self.theory).map_err(|err| SeriesError(err.0))?;
+                factorial *= BigRational::from_integer(BigInt::from(k));
+            }
+            let value = simplify(&subst(&d, &at), self.theory);
+            let value = term_to_rat(&value).ok_or_else(|| {
+                SeriesError(format!(
+                    "no series known for `{f}`: its value or that of a derivative at {c} is not a rational the theory knows"
+                ))
+            })?;
+            coeffs.push(value / &factorial);
+        }
+        Ok(coeffs)
+    }
+}
+
+/// Whether `x` occurs in `t`.
+fn mentions(t: &Term, x: &Symbol) -> bool {
+    match t {
+        Term::Var(y) => x == y,
+        Term::App(_, args) => args.iter().any(|a| mentions(a, x)),
+        _ => false,
+    }
+}
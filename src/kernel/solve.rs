@@ -0,0 +1,162 @@
+//! Linear equations and systems, `solve 2·x + 3 = 7 for x` and
+//! `solve {x + y = 3, x - y = 1} for x, y`.
+//!
+//! Each equation `l = r` is read as the polynomial `l - r` (see
+//! `kernel::poly`), which must be linear in the unknowns with rational
+//! coefficients: `a · x` with `a` another variable, `x ^ 2` or `log(x)` is
+//! refused. The part without unknowns may mention other variables, which
+//! act as parameters, so `solve x + a = 3 for x` gives `x = -a + 3`. Gaussian
+//! elimination over `BigRational` brings the system to reduced row echelon
+//! form. Unknowns without a pivot are free, and the others are solved for
+//! in terms of them and the parameters.
+//!
+//! A solution is a substitution for the solved unknowns, ready for
+//! `kernel::subst::subst`: putting it into the equations makes them hold.
+
+use std::collections::HashMap;
+
+use num_rational::BigRational;
+use num_traits::Zero;
+
+use crate::kernel::poly::{degree, Poly};
+use crate::kernel::term::{sym, Symbol, Term};
+
+#[derive(Debug)]
+pub struct SolveError(pub String);
+
+/// The solutions of a linear system.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solution {
+    /// One solution: every unknown has a value.
+    Unique(HashMap<Symbol, Term>),
+    /// A family of solutions: the unknowns in the substitution are given in
+    /// terms of the free ones, which take any value.
+    Parametric(HashMap<Symbol, Term>, Vec<Symbol>),
+    /// No solution.
+    Inconsistent,
+    /// Solutions exist only where this equation between the parameters
+    /// holds, as for `x = a` and `x = b`.
+    Conditional(Term),
+}
+
+/// One equation `coeffs · unknowns = rhs`.
+struct Row {
+    coeffs: Vec<BigRational>,
+    rhs: Poly,
+}
+
+/// Solve the equations for the unknowns. An equation is an `=` term.
+pub fn solve(equations: &[Term], unknowns: &[Symbol]) -> Result<Solution, SolveError> {
+    let repeated = (0..unknowns.len()).find(|&k| unknowns[..k].contains(&unknowns[k]));
+    if let Some(k) = repeated {
+        return Err(SolveError(format!(
+            "the unknown `{}` is listed twice",
+            unknowns[k]
+        )));
+    }
+    let mut rows = equations
+        .iter()
+        .map(|eq| row(eq, unknowns))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Gauss–Jordan elimination, column by column: the pivot row is scaled
+    // to a leading 1 and the column cleared in every other row.
+    let mut pivots: Vec<(usize, usize)> = Vec::new();
+    let mut next = 0;
+    for col in 0..unknowns.len() {
+        let Some(p) = (next..rows.len()).find(|&r| !rows[r].coeffs[col].is_zero()) else {
+            continue;
+        };
+        rows.swap(next, p);
+        let inv = rows[next].coeffs[col].recip();
+        rows[next].coeffs.iter_mut().for_each(|c| *c *= &inv);
+        rows[next].rhs = rows[next].rhs.scale(&inv);
+        for r in 0..rows.len() {
+            let factor = rows[r].coeffs[col].clone();
+            if r == next || factor.is_zero() {
+                continue;
+            }
+            let (coeffs, rhs) = (rows[next].coeffs.clone(), rows[next].rhs.clone());
+            for (c, p) in rows[r].coeffs.iter_mut().zip(&coeffs) {
+                *c -= &factor * p;
+            }
+            rows[r].rhs = &rows[r].rhs - &rhs.scale(&factor);
+        }
+        pivots.push((next, col));
+        next += 1;
+    }
+
+    // Rows left over have no unknowns: `0 = rhs`.
+    for r in &rows[next..] {
+        match r.rhs.as_constant() {
+            Some(c) if c.is_zero() => {}
+            Some(_) => return Ok(Solution::Inconsistent),
+            None => {
+                let zero = Term::Nat(0u32.into());
+                return Ok(Solution::Conditional(Term::App(
+                    sym("="),
+                    vec![r.rhs.to_term(), zero],
+                )));
+            }
+        }
+    }
+
+    let free: Vec<usize> = (0..unknowns.len())
+        .filter(|c| !pivots.iter().any(|&(_, p)| p == *c))
+        .collect();
+    let mut sigma = HashMap::new();
+    for &(r, col) in &pivots {
+        let mut value = rows[r].rhs.clone();
+        for &f in &free {
+            let c = &rows[r].coeffs[f];
+            value = &value - &Poly::var(Term::Var(unknowns[f].clone())).scale(c);
+        }
+        sigma.insert(unknowns[col].clone(), value.to_term());
+    }
+    Ok(if free.is_empty() {
+        Solution::Unique(sigma)
+    } else {
+        Solution::Parametric(
+            sigma,
+            free.into_iter().map(|f| unknowns[f].clone()).collect(),
+        )
+    })
+}
+
+/// The equation `l = r` as a row: the coefficients of the unknowns in
+/// `l - r`, and minus the rest on the right.
+fn row(eq: &Term, unknowns: &[Symbol]) -> Result<Row, SolveError> {
+    let (l, r) = match eq {
+        Term::App(head, args) if head.as_ref() == "=" && args.len() == 2 => (&args[0], &args[1]),
+        _ => return Err(SolveError("`solve` needs equations".into())),
+    };
+    let p = &Poly::from_term(l) - &Poly::from_term(r);
+    let mut coeffs = vec![BigRational::zero(); unknowns.len()];
+    let mut rhs = Poly::zero();
+    for (m, c) in p.terms() {
+        let unknown = m
+            .keys()
+            .find_map(|t| unknowns.iter().position(|u| mentions(t, u)));
+        match unknown {
+            None => rhs.add_term(m.clone(), -c),
+            Some(k) if degree(m) == 1 && m.contains_key(&Term::Var(unknowns[k].clone())) => {
+                coeffs[k] += c;
+            }
+            Some(k) => {
+                return Err(SolveError(format!(
+                    "not linear in `{}` with rational coefficients",
+                    unknowns[k]
+                )))
+            }
+        }
+    }
+    Ok(Row { coeffs, rhs })
+}
+
+fn mentions(t: &Term, x: &Symbol) -> bool {
+    match t {
+        Term::Var(y) => x == y,
+        Term::App(_, args) => args.iter().any(|a| mentions(a, x)),
+        _ => false,
+    }
+}
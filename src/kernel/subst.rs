@@ -1,19 +1,89 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::kernel::term::{Symbol, Term};
+use crate::kernel::term::{as_big_op, big_op, sym, Symbol, Term};
 
 /// Substitute every free variable in `t` whose symbol appears in `sigma` with
 /// the corresponding replacement term, recursing into application arguments.
+/// The bound variable of a finite sum or product shadows its own replacement
+/// in the body, and is renamed to a fresh variable when a replacement for
+/// another variable of the body mentions it, so `∑ m for k from 1 to 3` with
+/// `m := k` is `∑ k for k1 from 1 to 3`.
 pub fn subst(t: &Term, sigma: &HashMap<Symbol, Term>) -> Term {
+    subst_in(t, sigma, false)
+}
+
+/// `subst` for the match `sigma` of a rule's pattern. A bound variable that
+/// `sigma` maps to a variable is renamed to it, as a pattern's bound
+/// variable is when it matches a sum, and the replacements in the body may
+/// then mention it.
+pub(crate) fn subst_match(t: &Term, sigma: &HashMap<Symbol, Term>) -> Term {
+    subst_in(t, sigma, true)
+}
+
+fn subst_in(t: &Term, sigma: &HashMap<Symbol, Term>, matched: bool) -> Term {
     match t {
-        Term::Nat(_) | Term::Int(_) | Term::Rat(_) => t.clone(),
+        Term::Nat(_) | Term::Int(_) | Term::Rat(_) | Term::Complex(..) | Term::Residue(..) => t.clone(),
         Term::Var(s) => match sigma.get(s) {
             Some(replacement) => replacement.clone(),
             None => t.clone(),
         },
         Term::App(head, args) => {
-            let new_args = args.iter().map(|a| subst(a, sigma)).collect();
+            if let Some((head, var, lo, hi, body)) = as_big_op(t) {
+                let (lo, hi) = (subst_in(lo, sigma, matched), subst_in(hi, sigma, matched));
+                if let (true, Some(Term::Var(renamed))) = (matched, sigma.get(var)) {
+                    return big_op(head, renamed.clone(), lo, hi, subst_in(body, sigma, matched));
+                }
+                let mut inner = sigma.clone();
+                inner.remove(var);
+                let captured = inner
+                    .iter()
+                    .any(|(y, r)| occurs_free(y, body) && occurs_free(var, r));
+                if !captured {
+                    return big_op(head, var.clone(), lo, hi, subst_in(body, &inner, matched));
+                }
+                let fresh = fresh(var, body, &inner);
+                inner.insert(var.clone(), Term::Var(fresh.clone()));
+                return big_op(head, fresh, lo, hi, subst_in(body, &inner, matched));
+            }
+            let new_args = args.iter().map(|a| subst_in(a, sigma, matched)).collect();
             Term::App(head.clone(), new_args)
         }
     }
 }
+
+/// Whether `x` occurs in `t` outside every sum and product binding it.
+fn occurs_free(x: &Symbol, t: &Term) -> bool {
+    if let Some((_, var, lo, hi, body)) = as_big_op(t) {
+        return occurs_free(x, lo) || occurs_free(x, hi) || (var != x && occurs_free(x, body));
+    }
+    match t {
+        Term::Var(y) => x == y,
+        Term::App(_, args) => args.iter().any(|a| occurs_free(x, a)),
+        _ => false,
+    }
+}
+
+/// The first of `x1`, `x2`, … that is none of the variables of `body` and
+/// of `sigma`.
+fn fresh(x: &Symbol, body: &Term, sigma: &HashMap<Symbol, Term>) -> Symbol {
+    let mut taken = HashSet::new();
+    vars(body, &mut taken);
+    for (y, r) in sigma {
+        taken.insert(y.clone());
+        vars(r, &mut taken);
+    }
+    (1..)
+        .map(|i| sym(&format!("{x}{i}")))
+        .find(|s| !taken.contains(s))
+        .expect("an unused name")
+}
+
+fn vars(t: &Term, out: &mut HashSet<Symbol>) {
+    match t {
+        Term::Var(y) => {
+            out.insert(y.clone());
+        }
+        Term::App(_, args) => args.iter().for_each(|a| vars(a, out)),
+        _ => {}
+    }
+}
@@ -1,14 +1,16 @@
 use num_bigint::{BigInt, BigUint};
 use num_rational::BigRational;
+use num_traits::{One, Zero};
 use std::rc::Rc;
 
 pub type Symbol = Rc<str>;
 
 /// The variant order matters: it defines the kernel's canonical total order on
-/// terms (`App < Var < Nat < Int < Rat`), which `simplify` uses to sort AC
-/// operands into a canonical form. Apps come first, then variables, then
-/// literals — this puts numeric constants last in printed output (`a + 5`, not
-/// `5 + a`). Within a variant, the derived order falls back to field comparison.
+/// terms (`App < Var < Nat < Int < Rat < Complex < Residue`), which `simplify` uses to
+/// sort AC operands into a canonical form. Apps come first, then variables,
+/// then literals — this puts numeric constants last in printed output
+/// (`a + 5`, not `5 + a`). Within a variant, the derived order falls back to
+/// field comparison.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Term {
     App(Symbol, Vec<Term>),
@@ -16,8 +18,72 @@ pub enum Term {
     Nat(BigUint),
     Int(BigInt),
     Rat(BigRational),
+    /// The Gaussian rational `re + im · i`, with `im ≠ 0`; see
+    /// `eval::complex_to_term`. Boxed to keep `Term` small.
+    Complex(Box<(BigRational, BigRational)>),
+    /// The residue class `value (mod modulus)` in `ℤ/nℤ`, with
+    /// `value < modulus`; see `kernel::modular`.
+    Residue(Box<(BigUint, BigUint)>),
 }
 
 pub fn sym(s: &str) -> Symbol {
     Rc::from(s)
 }
+
+/// The imaginary unit `i`.
+pub fn imaginary_unit() -> Term {
+    Term::Complex(Box::new((BigRational::zero(), BigRational::one())))
+}
+
+/// The truth constant `True` or `False`. Truth constants are nullary
+/// applications rather than a variant of their own, so matching, substitution
+/// and the term order treat them like any other constant head.
+pub fn truth(b: bool) -> Term {
+    Term::App(sym(if b { "True" } else { "False" }), Vec::new())
+}
+
+/// The piecewise term `if c then a else b`: an application of the reserved
+/// head `if`, which no surface function name can collide with.
+pub fn cond(c: Term, a: Term, b: Term) -> Term {
+    Term::App(sym("if"), vec![c, a, b])
+}
+
+/// Split a piecewise term into condition and branches.
+pub fn as_cond(t: &Term) -> Option<(&Term, &Term, &Term)> {
+    match t {
+        Term::App(head, args) if head.as_ref() == "if" && args.len() == 3 => {
+            Some((&args[0], &args[1], &args[2]))
+        }
+        _ => None,
+    }
+}
+
+/// The finite sum `∑ body for var from lo to hi`, or with head `∏` the
+/// product: an application of the reserved head to the bound variable, the
+/// bounds and the body. See `kernel::bigop`.
+pub fn big_op(head: &str, var: Symbol, lo: Term, hi: Term, body: Term) -> Term {
+    Term::App(sym(head), vec![Term::Var(var), lo, hi, body])
+}
+
+/// Split a finite sum or product into head, bound variable, bounds and body.
+pub fn as_big_op(t: &Term) -> Option<(&Symbol, &Symbol, &Term, &Term, &Term)> {
+    match t {
+        Term::App(head, args) if matches!(head.as_ref(), "∑" | "∏") => match args.as_slice() {
+            [Term::Var(var), lo, hi, body] => Some((head, var, lo, hi, body)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Read a truth constant back; `None` for every other term.
+pub fn as_truth(t: &Term) -> Option<bool> {
+    match t {
+        Term::App(head, args) if args.is_empty() => match head.as_ref() {
+            "True" => Some(true),
+            "False" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -10,11 +10,19 @@
 //!   `e` as a right identity for `f`; `f(e, x) = x` registers it as a left
 //!   identity. For AC operators the two coincide, so a single fact covers both
 //!   sides.
+//!
+//! Multiplicative inverses are earned the same way: the conditional fact
+//! `x / x = 1 if x ≠ 0` marks `/` as field division (`Theory::is_field`).
 
 use std::collections::{HashMap, HashSet};
 
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+use crate::kernel::eval::term_to_rat;
 use crate::kernel::kbo::{kbo, KboOrd};
-use crate::kernel::term::{Symbol, Term};
+use crate::kernel::logic::complement;
+use crate::kernel::term::{as_cond, sym, Symbol, Term};
 
 /// A set defined by a predicate: `{var ∈ domain | pred}`.
 /// Membership `e ∈ S` is checked by substituting `var := e` into `pred`.
@@ -42,6 +50,19 @@ pub struct NamedFact {
     pub lhs: Term,
     pub rhs: Term,
     pub condition: Option<Term>,
+    /// The `∀` domain of each bound variable, as written; informational for
+    /// rewriting, used by `check` to draw instances.
+    pub domains: Vec<(Symbol, Term)>,
+}
+
+/// A fact whose conclusion is a membership or comparison, such as
+/// `∀ x ∈ Pos. x ≠ 0` or `x > 0 ⇒ x ∈ Pos`. It is not a rewrite rule; instead
+/// `condition_holds` chains through it: an atom matching `conclusion` holds if
+/// the instantiated `premise` does.
+#[derive(Debug, Clone)]
+pub struct Implication {
+    pub premise: Option<Term>,
+    pub conclusion: Term,
 }
 
 /// Outcome of trying to install an equality `l = r` as a rewrite rule.
@@ -82,8 +103,14 @@ pub enum FactEffect {
     LeftIdentity(Symbol, Term),
     RightIdentity(Symbol, Term),
     AcPromoted(Symbol),
+    /// `x / x = 1 if x ≠ 0` was recognised; see `Theory::is_field`.
+    Inverses,
     AlreadyKnown,
     SubsetFact,
+    ImplicationInstalled,
+    /// `D(f) = g` entered `g` as the derivative of `f`; see
+    /// `Theory::derivative`.
+    Derivative(Symbol),
 }
 
 #[derive(Debug, Default)]
@@ -91,11 +118,14 @@ pub struct Theory {
     pub rules: Vec<Rule>,
     pub named: HashMap<Symbol, NamedFact>,
     pub predicate_sets: HashMap<Symbol, PredicateSet>,
+    pub implications: Vec<Implication>,
     ac: HashSet<Symbol>,
     saw_comm: HashSet<Symbol>,
     saw_assoc: HashSet<Symbol>,
     left_id: HashMap<Symbol, Term>,
     right_id: HashMap<Symbol, Term>,
+    inverses: bool,
+    derivatives: HashMap<Symbol, Term>,
 }
 
 impl Theory {
@@ -119,6 +149,30 @@ impl Theory {
         self.right_id.get(f)
     }
 
+    /// Whether `+` and `·` are AC with identities `0` and `1`. `simplify`
+    /// then reads ring subterms as polynomials (see `kernel::poly`).
+    pub fn is_ring(&self) -> bool {
+        let unit = |op: &str, e: i32| {
+            let f = sym(op);
+            self.is_ac(&f)
+                && self.right_identity(&f).and_then(term_to_rat) == Some(BigRational::from_integer(e.into()))
+        };
+        unit("+", 0) && unit("·", 1)
+    }
+
+    /// Whether the theory is a ring (`is_ring`) in which `x / x = 1` for
+    /// `x ≠ 0`. `simplify` then reads field subterms as rational functions
+    /// (see `kernel::ratfun`).
+    pub fn is_field(&self) -> bool {
+        self.inverses && self.is_ring()
+    }
+
+    /// The derivative of the function `f`, from a fact `D(f) = g`: a function
+    /// built pointwise from names, as `kernel::diff` reads it.
+    pub fn derivative(&self, f: &Symbol) -> Option<&Term> {
+        self.derivatives.get(f)
+    }
+
     /// Install a fact, optionally under a `name` and with a side `condition`.
     ///
     /// Named facts are stored as-written (lhs, rhs order preserved) so that
@@ -128,6 +182,18 @@ impl Theory {
     ///
     /// AC recognition is suppressed when a condition is present (as per the
     /// design: side conditions disqualify a fact from AC recognition).
+    ///
+    /// An implication `P ⇒ C` is installed as `C if P` (conjoined with any
+    /// existing condition). If `C` is an equation this is an ordinary
+    /// conditional rule; if it is a membership or comparison it becomes an
+    /// `Implication` for forward reasoning, as does an unconditional
+    /// membership or comparison fact. Other conclusions (`∨`, …) stay inert.
+    ///
+    /// An equation with a piecewise side is installed as two conditional
+    /// facts, one per branch, guarded by the condition and its complement.
+    ///
+    /// An unconditional `D(f) = g`, with `f` a name, enters `g` in the table
+    /// of derivatives instead of becoming a rule.
     pub fn install_fact(
         &mut self,
         t: &Term,
@@ -142,6 +208,20 @@ impl Theory {
                 // Subset claims are accepted but not used for rewriting yet.
                 return vec![FactEffect::SubsetFact];
             }
+            Term::App(head, args) if head.as_ref() == "⇒" && args.len() == 2 => {
+                let premise = match condition {
+                    Some(c) => Term::App(sym("∧"), vec![c.clone(), args[0].clone()]),
+                    None => args[0].clone(),
+                };
+                return self.install_fact(&args[1], name, Some(&premise));
+            }
+            Term::App(head, args) if is_atom_head(head) && args.len() == 2 => {
+                self.implications.push(Implication {
+                    premise: condition.cloned(),
+                    conclusion: t.clone(),
+                });
+                return vec![FactEffect::ImplicationInstalled];
+            }
             _ => return vec![FactEffect::NotEquality],
         };
 
@@ -153,12 +233,37 @@ impl Theory {
                     lhs: l.clone(),
                     rhs: r.clone(),
                     condition: condition.cloned(),
+                    domains: Vec::new(),
                 },
             );
         }
 
+        // A piecewise side splits into one conditional fact per branch, each
+        // oriented on its own: `abs(x) = if x ≥ 0 then x else -x` installs
+        // `abs(x) = x if x ≥ 0` and `abs(x) = -x if x < 0`.
+        let split = match (as_cond(l), as_cond(r)) {
+            (None, Some(branches)) => Some((l, branches)),
+            (Some(branches), None) => Some((r, branches)),
+            _ => None,
+        };
+        if let Some((side, (c, a, b))) = split {
+            let mut effects = Vec::new();
+            for (branch, guard) in [(a, c.clone()), (b, complement(c))] {
+                let guard = match condition {
+                    Some(cond) => Term::App(sym("∧"), vec![cond.clone(), guard]),
+                    None => guard,
+                };
+                let eq = Term::App(sym("="), vec![side.clone(), branch.clone()]);
+                effects.extend(self.install_fact(&eq, None, Some(&guard)));
+            }
+            return effects;
+        }
+
         // AC recognition is only attempted for unconditional facts.
         if condition.is_none() {
+            if let Some((f, g)) = match_derivative(l, r) {
+                return self.note_derivative(f, g);
+            }
             if let Some(f) = match_commutativity(l, r) {
                 return self.note_commutativity(f);
             }
@@ -173,15 +278,22 @@ impl Theory {
             }
         }
 
-        match orient(l, r) {
+        // The inverse law is recognised, and also installed as a rule.
+        let mut effects = Vec::new();
+        if condition.is_some_and(|c| is_inverse_law(l, r, c)) && !self.inverses {
+            self.inverses = true;
+            effects.push(FactEffect::Inverses);
+        }
+        effects.push(match orient(l, r) {
             Orient::Rule(mut rule) => {
                 rule.condition = condition.cloned();
                 self.rules.push(rule);
-                vec![FactEffect::RuleInstalled]
+                FactEffect::RuleInstalled
             }
-            Orient::Trivial => vec![FactEffect::Trivial],
-            Orient::Incomparable => vec![FactEffect::Incomparable],
-        }
+            Orient::Trivial => FactEffect::Trivial,
+            Orient::Incomparable => FactEffect::Incomparable,
+        });
+        effects
     }
 
     fn note_commutativity(&mut self, f: Symbol) -> Vec<FactEffect> {
@@ -230,6 +342,13 @@ impl Theory {
         vec![FactEffect::LeftIdentity(f, e)]
     }
 
+    fn note_derivative(&mut self, f: Symbol, g: Term) -> Vec<FactEffect> {
+        if self.derivatives.insert(f.clone(), g.clone()).as_ref() == Some(&g) {
+            return vec![FactEffect::AlreadyKnown];
+        }
+        vec![FactEffect::Derivative(f)]
+    }
+
     fn merge_identities_after_ac(&mut self, f: &Symbol) {
         if let Some(e) = self.left_id.get(f).cloned() {
             self.right_id.entry(f.clone()).or_insert(e);
@@ -240,6 +359,30 @@ impl Theory {
     }
 }
 
+/// Heads of the atoms an `Implication` can conclude.
+fn is_atom_head(head: &Symbol) -> bool {
+    matches!(head.as_ref(), "∈" | "≠" | "<" | "≤" | ">" | "≥")
+}
+
+/// `D(f) = g` with `f` a name and `D` absent from `g`.
+fn match_derivative(l: &Term, r: &Term) -> Option<(Symbol, Term)> {
+    match l {
+        Term::App(head, args) if head.as_ref() == "D" => match args.as_slice() {
+            [Term::Var(f)] if !mentions_head(r, head) => Some((f.clone(), r.clone())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn mentions_head(t: &Term, f: &Symbol) -> bool {
+    match t {
+        Term::App(head, args) => head == f || args.iter().any(|a| mentions_head(a, f)),
+        Term::Var(x) => x == f,
+        _ => false,
+    }
+}
+
 fn match_commutativity(l: &Term, r: &Term) -> Option<Symbol> {
     let (f, la, lb) = bin_app_of_two_vars(l)?;
     let (g, ra, rb) = bin_app_of_two_vars(r)?;
@@ -307,6 +450,30 @@ fn match_left_identity(l: &Term, r: &Term) -> Option<(Symbol, Term)> {
     Some((f.clone(), a.clone()))
 }
 
+/// `x / x = 1` (either way round) under the condition `x ≠ 0`.
+fn is_inverse_law(l: &Term, r: &Term, condition: &Term) -> bool {
+    let is_one = |t: &Term| term_to_rat(t).is_some_and(|c| c.is_one());
+    let quotient = match (l, r) {
+        (q, one) | (one, q) if is_one(one) => q,
+        _ => return false,
+    };
+    let x = match bin_app(quotient) {
+        Some((f, a, b)) if f.as_ref() == "/" && a == b => match as_var(a) {
+            Some(x) => x,
+            None => return false,
+        },
+        _ => return false,
+    };
+    match bin_app(condition) {
+        Some((ne, a, b)) if ne.as_ref() == "≠" => {
+            let is_x = |t: &Term| as_var(t) == Some(x);
+            let is_zero = |t: &Term| term_to_rat(t).is_some_and(|c| c.is_zero());
+            (is_x(a) && is_zero(b)) || (is_zero(a) && is_x(b))
+        }
+        _ => false,
+    }
+}
+
 fn bin_app(t: &Term) -> Option<(&Symbol, &Term, &Term)> {
     match t {
         Term::App(f, args) if args.len() == 2 => Some((f, &args[0], &args[1])),
@@ -328,7 +495,7 @@ fn as_var(t: &Term) -> Option<&Symbol> {
 
 fn is_closed(t: &Term) -> bool {
     match t {
-        Term::Nat(_) | Term::Int(_) | Term::Rat(_) => true,
+        Term::Nat(_) | Term::Int(_) | Term::Rat(_) | Term::Complex(..) | Term::Residue(..) => true,
         Term::Var(_) => false,
         Term::App(_, args) => args.iter().all(is_closed),
     }
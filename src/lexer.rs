@@ -1,9 +1,18 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
 use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::One;
+
+use crate::ast::{InfixOp, InfixTable};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Token {
     Ident(String),
     Int(BigInt),
+    /// A decimal literal (`0.25`, `0.1(6)`, `1.5e-3`), read exactly
+    Decimal(BigRational),
     Plus,
     Minus,      // -
     Dot,        // ·
@@ -18,6 +27,8 @@ pub enum Token {
     LeftArrow,  // ←
     Arrow,      // →
     ForAll,     // ∀
+    Sum,        // ∑
+    Product,    // ∏
     In,         // ∈
     Subset,     // ⊆
     Period,     // .
@@ -27,6 +38,7 @@ pub enum Token {
     LBrace,     // {
     RBrace,     // }
     Bar,        // |
+    Bang,       // ! (postfix factorial)
     Lt,         // <
     Gt,         // >
     Le,         // ≤
@@ -36,9 +48,27 @@ pub enum Token {
     Print,
     Evaluate,
     Simplify,
+    Expand,
+    Normalize,
+    Together,
+    Cancel,
+    Factor,
+    Approx,
+    Diff,
+    Series,
+    Solve,
     Apply,
     To,
     If,
+    InKw,       // in (keyword, `let … in …`)
+    Infix,
+    Prove,
+    Check,
+    Not,        // ¬
+    True,
+    False,
+    UserOp(String), // symbol registered by an `infix` declaration
+    Symbol(String), // run of operator characters with no meaning yet
 }
 
 #[derive(Debug)]
@@ -46,32 +76,84 @@ pub struct LexError(pub String);
 
 /// Split `src` into the token stream consumed by the parser. Whitespace is
 /// skipped; identifiers, integer literals, punctuation, and reserved keywords
-/// are recognized.
+/// are recognized. Every Unicode operator and keyword also has an ASCII
+/// spelling that lexes to the same token: `*` `<=` `>=` `!=` `->` `<-` `=>`
+/// `/\` `\/` `~` `forall` `sum` `prod` `subset`, and `in` for `∈` (see the parser for how
+/// `in` is told apart from the `let … in …` keyword). `<-` is only `←` after
+/// `apply`, the one place the grammar uses it, so `a<-b` is `a < -b`. `!=` is
+/// always `≠`, so a factorial compared for equality is written `n! = m`.
 pub fn tokenize(src: &str) -> Result<Vec<Token>, LexError> {
+    tokenize_with(src, &InfixTable::new())
+}
+
+/// Like `tokenize`, but symbols declared in `ops` are recognized as
+/// `Token::UserOp`. Declared symbols win over built-in punctuation by longest
+/// match, so `<+>` is one token once declared. Any other run of operator
+/// characters becomes `Token::Symbol`, which only an `infix` declaration
+/// accepts.
+pub fn tokenize_with(src: &str, ops: &InfixTable) -> Result<Vec<Token>, LexError> {
     let mut chars = src.chars().peekable();
     let mut tokens = Vec::new();
 
     while let Some(&c) = chars.peek() {
         if c.is_whitespace() {
             chars.next();
+        } else if c != '#' && declares_symbol(&tokens) {
+            // `infix <prec> <assoc> <symbol>`: the symbol is read verbatim up to
+            // whitespace, so it may reuse built-in characters (`<+>`).
+            let mut s = String::new();
+            while let Some(&d) = chars.peek() {
+                if d.is_whitespace() || d == '#' {
+                    break;
+                }
+                s.push(d);
+                chars.next();
+            }
+            tokens.push(Token::Symbol(s));
+        } else if let Some(op) = match_user_op(&chars, c, ops) {
+            for _ in op.symbol.chars() {
+                chars.next();
+            }
+            tokens.push(Token::UserOp(op.symbol));
         } else if c == '+' {
             chars.next();
             tokens.push(Token::Plus);
+        } else if c == '-' && next_is(&chars, '>') {
+            chars.nth(1);
+            tokens.push(Token::Arrow);
         } else if c == '-' {
             chars.next();
             tokens.push(Token::Minus);
-        } else if c == '·' {
+        } else if c == '·' || c == '*' {
             chars.next();
             tokens.push(Token::Dot);
+        } else if c == '/' && next_is(&chars, '\\') {
+            chars.nth(1);
+            tokens.push(Token::And);
+        } else if c == '\\' && next_is(&chars, '/') {
+            chars.nth(1);
+            tokens.push(Token::Or);
         } else if c == '/' {
             chars.next();
             tokens.push(Token::Slash);
         } else if c == '^' {
             chars.next();
             tokens.push(Token::Caret);
+        } else if c == '=' && next_is(&chars, '>') {
+            chars.nth(1);
+            tokens.push(Token::Implies);
         } else if c == '=' {
             chars.next();
             tokens.push(Token::Equals);
+        } else if c == '!' && next_is(&chars, '=') {
+            chars.nth(1);
+            tokens.push(Token::NotEquals);
+        } else if c == '!' {
+            chars.next();
+            tokens.push(Token::Bang);
+        } else if c == '¬' || c == '~' {
+            chars.next();
+            tokens.push(Token::Not);
         } else if c == '≠' {
             chars.next();
             tokens.push(Token::NotEquals);
@@ -87,6 +169,12 @@ pub fn tokenize(src: &str) -> Result<Vec<Token>, LexError> {
         } else if c == '∀' {
             chars.next();
             tokens.push(Token::ForAll);
+        } else if c == '∑' {
+            chars.next();
+            tokens.push(Token::Sum);
+        } else if c == '∏' {
+            chars.next();
+            tokens.push(Token::Product);
         } else if c == '∈' {
             chars.next();
             tokens.push(Token::In);
@@ -114,6 +202,15 @@ pub fn tokenize(src: &str) -> Result<Vec<Token>, LexError> {
         } else if c == '≥' {
             chars.next();
             tokens.push(Token::Ge);
+        } else if c == '<' && next_is(&chars, '=') {
+            chars.nth(1);
+            tokens.push(Token::Le);
+        } else if c == '<' && next_is(&chars, '-') && tokens.last() == Some(&Token::Apply) {
+            chars.nth(1);
+            tokens.push(Token::LeftArrow);
+        } else if c == '>' && next_is(&chars, '=') {
+            chars.nth(1);
+            tokens.push(Token::Ge);
         } else if c == '<' {
             chars.next();
             tokens.push(Token::Lt);
@@ -136,17 +233,7 @@ pub fn tokenize(src: &str) -> Result<Vec<Token>, LexError> {
             chars.next();
             tokens.push(Token::RParen);
         } else if c.is_ascii_digit() {
-            let mut s = String::new();
-            while let Some(&d) = chars.peek() {
-                if d.is_ascii_digit() {
-                    s.push(d);
-                    chars.next();
-                } else {
-                    break;
-                }
-            }
-            let n: BigInt = s.parse().map_err(|e| LexError(format!("bad int: {e}")))?;
-            tokens.push(Token::Int(n));
+            tokens.push(number(&mut chars)?);
         } else if is_ident_start(c) {
             let mut s = String::new();
             while let Some(&d) = chars.peek() {
@@ -166,11 +253,29 @@ pub fn tokenize(src: &str) -> Result<Vec<Token>, LexError> {
                 "apply" => Token::Apply,
                 "to" => Token::To,
                 "if" => Token::If,
+                "in" => Token::InKw,
+                "forall" => Token::ForAll,
+                "subset" => Token::Subset,
+                "infix" => Token::Infix,
+                "True" => Token::True,
+                "False" => Token::False,
+                _ if ops.get(&s).is_some() => Token::UserOp(s),
                 _ => Token::Ident(s),
             });
         } else if c == '#' {
             // line comment — discard the rest of the input
             break;
+        } else if is_symbol_char(c) {
+            let mut s = String::new();
+            while let Some(&d) = chars.peek() {
+                if is_symbol_char(d) {
+                    s.push(d);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Symbol(s));
         } else {
             return Err(LexError(format!("unexpected character: {c:?}")));
         }
@@ -179,6 +284,148 @@ pub fn tokenize(src: &str) -> Result<Vec<Token>, LexError> {
     Ok(tokens)
 }
 
+/// The keyword for a command word such as `expand` or `solve`. These words
+/// start a command only as its first token, which the parser promotes with
+/// this function; anywhere else they are ordinary identifiers, so `factor`
+/// and `check` remain usable as names.
+pub(crate) fn command_keyword(word: &str) -> Option<Token> {
+    Some(match word {
+        "expand" => Token::Expand,
+        "normalize" => Token::Normalize,
+        "together" => Token::Together,
+        "cancel" => Token::Cancel,
+        "factor" => Token::Factor,
+        "approx" => Token::Approx,
+        "diff" => Token::Diff,
+        "series" => Token::Series,
+        "solve" => Token::Solve,
+        "prove" => Token::Prove,
+        "check" => Token::Check,
+        _ => return None,
+    })
+}
+
+/// Whether the character after the current one is `c`. Used for the
+/// two-character ASCII spellings (`<=`, `->`, `/\`, …).
+fn next_is(chars: &Peekable<Chars>, c: char) -> bool {
+    chars.clone().nth(1) == Some(c)
+}
+
+/// Largest exponent accepted in a decimal literal such as `1e100`.
+const MAX_DECIMAL_EXPONENT: i64 = 100_000;
+
+/// An integer literal, or a decimal one: digits, then optionally a point
+/// with fractional digits and a repeating period in parentheses (`0.1(6)`),
+/// or an exponent (`1.5e-3`, `2e10`). The point and the exponent are only
+/// taken when digits follow, so `∀ x ∈ ℕ. 1` and `2 · e` lex as before.
+fn number(chars: &mut Peekable<Chars>) -> Result<Token, LexError> {
+    let int = digits(chars);
+    let (mut frac, mut period) = (String::new(), String::new());
+    let mut decimal = false;
+    if chars.peek() == Some(&'.') {
+        let mut ahead = chars.clone();
+        ahead.next();
+        let follows = match ahead.next() {
+            Some('(') => ahead.next().is_some_and(|d| d.is_ascii_digit()),
+            d => d.is_some_and(|d| d.is_ascii_digit()),
+        };
+        if follows {
+            chars.next();
+            decimal = true;
+            frac = digits(chars);
+            if chars.peek() == Some(&'(') {
+                chars.next();
+                period = digits(chars);
+                if period.is_empty() || chars.next() != Some(')') {
+                    return Err(LexError("expected `digits)` after `(` in a decimal".into()));
+                }
+            }
+        }
+    }
+    let mut exponent = 0i64;
+    if period.is_empty() && matches!(chars.peek(), Some('e' | 'E')) {
+        let mut ahead = chars.clone();
+        ahead.next();
+        let sign = match ahead.peek() {
+            Some(&s @ ('+' | '-')) => {
+                ahead.next();
+                Some(s)
+            }
+            _ => None,
+        };
+        if ahead.peek().is_some_and(|d| d.is_ascii_digit()) {
+            chars.next();
+            if sign.is_some() {
+                chars.next();
+            }
+            decimal = true;
+            exponent = digits(chars)
+                .parse()
+                .map_err(|e| LexError(format!("bad exponent: {e}")))?;
+            if sign == Some('-') {
+                exponent = -exponent;
+            }
+        }
+    }
+    let parse = |s: &str| -> Result<BigInt, LexError> {
+        if s.is_empty() {
+            return Ok(BigInt::from(0));
+        }
+        s.parse().map_err(|e| LexError(format!("bad number: {e}")))
+    };
+    if !decimal {
+        return Ok(Token::Int(parse(&int)?));
+    }
+    let ten = BigRational::from_integer(BigInt::from(10));
+    let shift = ten.pow(frac.len() as i32);
+    let mut value = BigRational::from_integer(parse(&(int + &frac))?) / &shift;
+    if !period.is_empty() {
+        let repeat = ten.pow(period.len() as i32) - BigRational::one();
+        value += BigRational::from_integer(parse(&period)?) / (shift * repeat);
+    }
+    if exponent.abs() > MAX_DECIMAL_EXPONENT {
+        return Err(LexError(format!("exponent {exponent} is too large")));
+    }
+    Ok(Token::Decimal(value * ten.pow(exponent as i32)))
+}
+
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut s = String::new();
+    while let Some(&d) = chars.peek() {
+        if !d.is_ascii_digit() {
+            break;
+        }
+        s.push(d);
+        chars.next();
+    }
+    s
+}
+
+/// Whether the next token is the symbol slot of an `infix` declaration.
+fn declares_symbol(tokens: &[Token]) -> bool {
+    matches!(tokens, [Token::Infix, Token::Int(_), Token::Ident(_)])
+}
+
+/// The declared operator symbol starting at the current position, if any.
+/// Identifier-like symbols (`mod`) are matched after the identifier is read.
+fn match_user_op(chars: &Peekable<Chars>, c: char, ops: &InfixTable) -> Option<InfixOp> {
+    if is_ident_continue(c) || !ops.any_starts_with(c) {
+        return None;
+    }
+    let rest: String = chars.clone().collect();
+    ops.longest_prefix(&rest).cloned()
+}
+
+/// Characters that may make up a user-declared operator symbol: anything that
+/// is not whitespace, not part of an identifier, not a delimiter, and not
+/// claimed by a built-in token.
+fn is_symbol_char(c: char) -> bool {
+    !c.is_whitespace()
+        && !c.is_control()
+        && !is_ident_continue(c)
+        && !"+-·*/^=≠⇒∧∨¬~!∀∑∏∈.,:←→⊆≤≥<>{}|()#".contains(c)
+}
+
 fn is_ident_start(c: char) -> bool {
     c.is_alphabetic() || c == '_'
 }
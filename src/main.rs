@@ -3,15 +3,28 @@ use std::env;
 use std::fs::File;
 use std::io::{self, BufRead, Write};
 
-use regler::ast::{Command, Expr, Op};
+use num_traits::One;
+
+use regler::ast::{Command, Expr, InfixTable, MonomialOrder, Op};
+use regler::kernel::approx::{approx, DEFAULT_DIGITS, MAX_DIGITS};
+use regler::kernel::cases::simplify_by_cases;
+use regler::kernel::check::{check_fact, CheckOutcome, Failure};
+use regler::kernel::diff::derivative;
 use regler::kernel::eval::evaluate;
-use regler::kernel::lower::lower;
-use regler::kernel::print::to_surface;
-use regler::kernel::rewrite::{apply_eq_conditional, simplify};
+use regler::kernel::factor::factor;
+use regler::kernel::groebner::{ideal_membership, Membership, MAX_PAIRS};
+use regler::kernel::lower::{fact_scope, lower, lower_with};
+use regler::kernel::modular::{element, quotient_modulus, set_modulus};
+use regler::kernel::poly::{expand, Poly};
+use regler::kernel::print::{to_surface_in, Numbers};
+use regler::kernel::ratfun::{cancel, together};
+use regler::kernel::rewrite::{apply_eq_conditional, normalize, simplify, simplify_assuming};
+use regler::kernel::series::series;
+use regler::kernel::solve::{solve, Solution};
 use regler::kernel::subst::subst;
-use regler::kernel::term::{sym, Symbol, Term};
+use regler::kernel::term::{as_truth, sym, Symbol, Term};
 use regler::kernel::theory::{FactEffect, Theory};
-use regler::parser::parse_command;
+use regler::parser::parse_command_with;
 use regler::printer::{print_command, print_expr};
 
 fn main() -> io::Result<()> {
@@ -19,17 +32,38 @@ fn main() -> io::Result<()> {
     let mut bindings: HashMap<String, Expr> = HashMap::new();
     let mut kernel_bindings: HashMap<Symbol, Term> = HashMap::new();
     let mut theory = Theory::new();
+    let mut ops = InfixTable::new();
 
-    if let Some(path) = env::args().nth(1) {
-        let file = File::open(&path).map_err(|e| io::Error::new(e.kind(), format!("{path}: {e}")))?;
+    // `regler [--check-facts] [--decimals] [file]`: with `--check-facts`,
+    // every fact is tested on random instances as it is installed; with
+    // `--decimals`, results show rationals as decimals.
+    let args: Vec<String> = env::args().skip(1).collect();
+    let check_facts = args.iter().any(|a| a == "--check-facts");
+    let numbers = if args.iter().any(|a| a == "--decimals") {
+        Numbers::Decimals
+    } else {
+        Numbers::Fractions
+    };
+
+    if let Some(path) = args.iter().find(|a| !a.starts_with("--")) {
+        let file =
+            File::open(path).map_err(|e| io::Error::new(e.kind(), format!("{path}: {e}")))?;
         for line in io::BufReader::new(file).lines() {
             let line = line?;
             let trimmed = line.trim();
             if trimmed.is_empty() {
                 continue;
             }
-            match parse_command(trimmed) {
-                Ok(Some(cmd)) => dispatch(cmd, &mut bindings, &mut kernel_bindings, &mut theory),
+            match parse_command_with(trimmed, &ops) {
+                Ok(Some(cmd)) => dispatch(
+                    cmd,
+                    &mut bindings,
+                    &mut kernel_bindings,
+                    &mut theory,
+                    &mut ops,
+                    check_facts,
+                    numbers,
+                ),
                 Ok(None) => {}
                 Err(err) => println!("parse error: {}", err.0),
             }
@@ -50,8 +84,16 @@ fn main() -> io::Result<()> {
         if trimmed.is_empty() {
             continue;
         }
-        match parse_command(trimmed) {
-            Ok(Some(cmd)) => dispatch(cmd, &mut bindings, &mut kernel_bindings, &mut theory),
+        match parse_command_with(trimmed, &ops) {
+            Ok(Some(cmd)) => dispatch(
+                cmd,
+                &mut bindings,
+                &mut kernel_bindings,
+                &mut theory,
+                &mut ops,
+                check_facts,
+                numbers,
+            ),
             Ok(None) => {}
             Err(err) => println!("parse error: {}", err.0),
         }
@@ -64,15 +106,33 @@ fn dispatch(
     bindings: &mut HashMap<String, Expr>,
     kernel_bindings: &mut HashMap<Symbol, Term>,
     theory: &mut Theory,
+    ops: &mut InfixTable,
+    check_facts: bool,
+    numbers: Numbers,
 ) {
     match cmd {
         Command::Let(name, ty, rhs) => {
-            println!("{}", print_command(&Command::Let(name.clone(), ty.clone(), rhs.clone())));
-            handle_let(name, ty, rhs, bindings, kernel_bindings, theory);
+            println!(
+                "{}",
+                print_command(&Command::Let(name.clone(), ty.clone(), rhs.clone()))
+            );
+            handle_let(
+                name,
+                ty,
+                rhs,
+                bindings,
+                kernel_bindings,
+                theory,
+                ops,
+                numbers,
+            );
         }
         Command::Fact(name, e, cond) => {
-            println!("{}", print_command(&Command::Fact(name.clone(), e.clone(), cond.clone())));
-            install_fact(name, &e, cond.as_ref(), theory);
+            println!(
+                "{}",
+                print_command(&Command::Fact(name.clone(), e.clone(), cond.clone()))
+            );
+            install_fact(name, &e, cond.as_ref(), theory, check_facts, ops, numbers);
         }
         Command::Print(e) => {
             let resolved = match &e {
@@ -81,25 +141,101 @@ fn dispatch(
             };
             println!("{}", print_expr(&resolved));
         }
-        Command::Evaluate(e) => match run_evaluate(&e, kernel_bindings) {
+        Command::Evaluate(e) => match run_evaluate(&e, kernel_bindings, ops, numbers) {
+            Ok(out) => println!("{}", out),
+            Err(msg) => println!("error: {}", msg),
+        },
+        Command::Simplify(e, assumption, by_cases) => match run_simplify(
+            &e,
+            assumption.as_ref(),
+            by_cases,
+            kernel_bindings,
+            theory,
+            ops,
+            numbers,
+        ) {
+            Ok(out) => println!("{}", out),
+            Err(msg) => println!("error: {}", msg),
+        },
+        Command::Expand(e) => match run_transform(&e, kernel_bindings, ops, numbers, expand) {
+            Ok(out) => println!("{}", out),
+            Err(msg) => println!("error: {}", msg),
+        },
+        Command::Normalize(e) => {
+            match run_transform(&e, kernel_bindings, ops, numbers, |t| normalize(t, theory)) {
+                Ok(out) => println!("{}", out),
+                Err(msg) => println!("error: {}", msg),
+            }
+        }
+        Command::Together(e) => match run_transform(&e, kernel_bindings, ops, numbers, together) {
+            Ok(out) => println!("{}", out),
+            Err(msg) => println!("error: {}", msg),
+        },
+        Command::Cancel(e) => match run_cancel(&e, kernel_bindings, ops, numbers) {
             Ok(out) => println!("{}", out),
             Err(msg) => println!("error: {}", msg),
         },
-        Command::Simplify(e) => match run_simplify(&e, kernel_bindings, theory) {
+        Command::Factor(e) => match run_transform(&e, kernel_bindings, ops, numbers, factor) {
             Ok(out) => println!("{}", out),
             Err(msg) => println!("error: {}", msg),
         },
-        Command::Apply(name, e) => match run_apply(&name, &e, false, kernel_bindings, theory) {
+        Command::Approx(e, digits) => match run_approx(&e, digits, kernel_bindings) {
             Ok(out) => println!("{}", out),
             Err(msg) => println!("error: {}", msg),
         },
-        Command::ApplyRev(name, e) => match run_apply(&name, &e, true, kernel_bindings, theory) {
+        Command::Diff(e, x) => match run_diff(&e, &x, kernel_bindings, theory, ops, numbers) {
             Ok(out) => println!("{}", out),
             Err(msg) => println!("error: {}", msg),
         },
+        Command::Series(e, x, a, n) => {
+            match run_series(&e, &x, &a, n, kernel_bindings, theory, ops, numbers) {
+                Ok(out) => println!("{}", out),
+                Err(msg) => println!("error: {}", msg),
+            }
+        }
+        Command::Solve(eqs, unknowns) => {
+            match run_solve(&eqs, &unknowns, kernel_bindings, ops, numbers) {
+                Ok(out) => println!("{}", out),
+                Err(msg) => println!("error: {}", msg),
+            }
+        }
+        Command::Apply(name, e) => {
+            match run_apply(&name, &e, false, kernel_bindings, theory, ops, numbers) {
+                Ok(out) => println!("{}", out),
+                Err(msg) => println!("error: {}", msg),
+            }
+        }
+        Command::ApplyRev(name, e) => {
+            match run_apply(&name, &e, true, kernel_bindings, theory, ops, numbers) {
+                Ok(out) => println!("{}", out),
+                Err(msg) => println!("error: {}", msg),
+            }
+        }
+        Command::Prove(e, hyps, order, by_cases) => match run_prove(
+            &e,
+            &hyps,
+            order,
+            by_cases,
+            kernel_bindings,
+            theory,
+            ops,
+            numbers,
+        ) {
+            Ok(out) => println!("{}", out),
+            Err(msg) => println!("error: {}", msg),
+        },
+        Command::Check(name) => match run_check(&name, theory, ops, numbers) {
+            Ok(out) => println!("{}", out),
+            Err(msg) => println!("error: {}", msg),
+        },
+        Command::Infix(op) => {
+            println!("{}", print_command(&Command::Infix(op.clone())));
+            ops.declare(op);
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_let(
     name: String,
     ty: Option<Expr>,
@@ -107,6 +243,8 @@ fn handle_let(
     bindings: &mut HashMap<String, Expr>,
     kernel_bindings: &mut HashMap<Symbol, Term>,
     theory: &mut Theory,
+    ops: &InfixTable,
+    numbers: Numbers,
 ) {
     match (ty.as_ref(), rhs.as_ref()) {
         // `let Name : Set` — opaque set declaration
@@ -114,7 +252,9 @@ fn handle_let(
 
         // `let Name : Set = {x ∈ S | P}` — predicate set definition
         (Some(Expr::Ident(t)), Some(Expr::SetBuilder(var, domain, pred))) if t == "Set" => {
-            match (lower(domain), lower(pred)) {
+            let mut names = bound_names(kernel_bindings);
+            names.push(var.clone());
+            match (lower(domain), lower_with(pred, &names)) {
                 (Ok(dom_term), Ok(pred_term)) => {
                     theory.add_predicate_set(sym(&name), sym(var), dom_term, pred_term);
                 }
@@ -122,13 +262,46 @@ fn handle_let(
             }
         }
 
-        // `let name : ty` — opaque declaration with type annotation (e.g. function signature)
-        (Some(_ty), None) => {}
+        // `let name : ty` — opaque declaration with type annotation (e.g. function signature).
+        // The name is bound to itself, which marks it declared (see `lower_resolved`).
+        (Some(_ty), None) => {
+            kernel_bindings.insert(sym(&name), Term::Var(sym(&name)));
+        }
 
         // `let name [: ty] = rhs` — value definition
         (_, Some(rhs_expr)) => {
-            match lower(rhs_expr) {
-                Ok(t) => {
+            match lower_with(rhs_expr, &bound_names(kernel_bindings)) {
+                Ok(mut t) => {
+                    // A set `ℤ/nℤ` needs a modulus greater than 1.
+                    if let Some(Expr::Ident(set)) = ty.as_ref() {
+                        if let (true, Some(Err(err))) = (set == "Set", quotient_modulus(&t)) {
+                            println!("error: {}", err.0);
+                            return;
+                        }
+                    }
+                    // A value of type `ℤ/nℤ` is read as a residue modulo `n`.
+                    let modulus = match ty.as_ref() {
+                        Some(Expr::Ident(set)) => {
+                            kernel_bindings.get(set.as_str()).and_then(set_modulus)
+                        }
+                        _ => None,
+                    };
+                    if let Some(n) = modulus {
+                        match element(&subst(&t, kernel_bindings), &n) {
+                            Ok(r) => t = r,
+                            Err(err) => {
+                                println!("error: {}", err.0);
+                                return;
+                            }
+                        }
+                    }
+                    if let Some(Expr::Ident(set)) = ty.as_ref() {
+                        let value = subst(&t, kernel_bindings);
+                        if let Err(msg) = discharge_membership(&value, set, theory, ops, numbers) {
+                            println!("error: {msg}");
+                            return;
+                        }
+                    }
                     kernel_bindings.insert(sym(&name), t);
                     bindings.insert(name, rhs_expr.clone());
                 }
@@ -140,32 +313,415 @@ fn handle_let(
     }
 }
 
-fn run_evaluate(e: &Expr, bindings: &HashMap<Symbol, Term>) -> Result<String, String> {
-    let t = lower(e).map_err(|err| err.0)?;
-    let t = subst(&t, bindings);
+/// Check the membership obligation `value ∈ set` of an annotated `let` by
+/// simplifying it. An obligation that simplifies to `False` is always an
+/// error; one that stays undecided is an error only for predicate-defined
+/// sets, since other annotations are informational.
+fn discharge_membership(
+    value: &Term,
+    set: &str,
+    theory: &Theory,
+    ops: &InfixTable,
+    numbers: Numbers,
+) -> Result<(), String> {
+    let obligation = Term::App(sym("∈"), vec![value.clone(), Term::Var(sym(set))]);
+    let reduced = simplify(&obligation, theory);
+    match as_truth(&reduced) {
+        Some(true) => Ok(()),
+        None if !theory.predicate_sets.contains_key(&sym(set)) => Ok(()),
+        Some(false) => {
+            let surface = to_surface_in(&obligation, ops, numbers).map_err(|err| err.0)?;
+            Err(format!(
+                "membership obligation is false: {}",
+                print_expr(&surface)
+            ))
+        }
+        None => {
+            let surface = to_surface_in(&reduced, ops, numbers).map_err(|err| err.0)?;
+            Err(format!(
+                "membership obligation not discharged: {}",
+                print_expr(&surface)
+            ))
+        }
+    }
+}
+
+/// The names bound by `let` commands.
+fn bound_names(bindings: &HashMap<Symbol, Term>) -> Vec<String> {
+    bindings.keys().map(|x| x.to_string()).collect()
+}
+
+/// Lower `e` and substitute the `let` bindings. A bound name is a variable
+/// even when it is `i`, so `let i = 3` shadows the imaginary unit.
+fn lower_resolved(e: &Expr, bindings: &HashMap<Symbol, Term>) -> Result<Term, String> {
+    let t = lower_with(e, &bound_names(bindings)).map_err(|err| err.0)?;
+    Ok(subst(&t, bindings))
+}
+
+fn run_evaluate(
+    e: &Expr,
+    bindings: &HashMap<Symbol, Term>,
+    ops: &InfixTable,
+    numbers: Numbers,
+) -> Result<String, String> {
+    let t = lower_resolved(e, bindings)?;
     let t = evaluate(&t).map_err(|err| err.0)?;
-    let surface = to_surface(&t).map_err(|err| err.0)?;
+    let surface = to_surface_in(&t, ops, numbers).map_err(|err| err.0)?;
     Ok(print_expr(&surface))
 }
 
 fn run_simplify(
     e: &Expr,
+    assumption: Option<&Expr>,
+    by_cases: bool,
+    bindings: &HashMap<Symbol, Term>,
+    theory: &Theory,
+    ops: &InfixTable,
+    numbers: Numbers,
+) -> Result<String, String> {
+    let t = lower_resolved(e, bindings)?;
+    let assumption = match assumption {
+        Some(a) => Some(lower_resolved(a, bindings)?),
+        None => None,
+    };
+    let t = match (assumption, by_cases) {
+        (a, true) => simplify_by_cases(&t, a.as_ref(), theory),
+        (Some(a), false) => simplify_assuming(&t, &a, theory),
+        (None, false) => simplify(&t, theory),
+    };
+    let surface = to_surface_in(&t, ops, numbers).map_err(|err| err.0)?;
+    Ok(print_expr(&surface))
+}
+
+/// Lower `e`, resolve `let` bindings, and print the result of `f`.
+fn run_transform(
+    e: &Expr,
+    bindings: &HashMap<Symbol, Term>,
+    ops: &InfixTable,
+    numbers: Numbers,
+    f: impl Fn(&Term) -> Term,
+) -> Result<String, String> {
+    let t = f(&lower_resolved(e, bindings)?);
+    let surface = to_surface_in(&t, ops, numbers).map_err(|err| err.0)?;
+    Ok(print_expr(&surface))
+}
+
+/// Print the value of `e` in decimal, to `digits` places.
+fn run_approx(
+    e: &Expr,
+    digits: Option<u32>,
+    bindings: &HashMap<Symbol, Term>,
+) -> Result<String, String> {
+    let digits = digits.unwrap_or(DEFAULT_DIGITS);
+    if digits > MAX_DIGITS {
+        return Err(format!("at most {MAX_DIGITS} decimal places"));
+    }
+    let t = lower_resolved(e, bindings)?;
+    approx(&t, digits).map_err(|err| err.0)
+}
+
+/// Differentiate `e` with respect to `x` and print the simplified result.
+/// A `let` binding of `x` itself is ignored.
+fn run_diff(
+    e: &Expr,
+    x: &str,
+    bindings: &HashMap<Symbol, Term>,
+    theory: &Theory,
+    ops: &InfixTable,
+    numbers: Numbers,
+) -> Result<String, String> {
+    let x = sym(x);
+    let mut bindings = bindings.clone();
+    bindings.insert(x.clone(), Term::Var(x.clone()));
+    let t = lower_resolved(e, &bindings)?;
+    let t = derivative(&t, &x, theory).map_err(|err| err.0)?;
+    let surface = to_surface_in(&t, ops, numbers).map_err(|err| err.0)?;
+    Ok(print_expr(&surface))
+}
+
+/// Expand `e` in a power series in `x` about the value of `a`, to order `n`.
+/// As for `diff`, a `let` binding of `x` is ignored.
+#[allow(clippy::too_many_arguments)]
+fn run_series(
+    e: &Expr,
+    x: &str,
+    a: &Expr,
+    n: u32,
     bindings: &HashMap<Symbol, Term>,
     theory: &Theory,
+    ops: &InfixTable,
+    numbers: Numbers,
 ) -> Result<String, String> {
-    let t = lower(e).map_err(|err| err.0)?;
-    let t = subst(&t, bindings);
-    let t = simplify(&t, theory);
-    let surface = to_surface(&t).map_err(|err| err.0)?;
+    let point = lower_resolved(a, bindings)?;
+    let point = evaluate(&point)
+        .ok()
+        .and_then(|p| Poly::from_term(&p).as_constant())
+        .ok_or("the expansion point must be a rational number")?;
+    let x = sym(x);
+    let mut bindings = bindings.clone();
+    bindings.insert(x.clone(), Term::Var(x.clone()));
+    let t = lower_resolved(e, &bindings)?;
+    let s = series(&t, &x, &point, n, theory).map_err(|err| err.0)?;
+    let surface = to_surface_in(&s.to_term(), ops, numbers).map_err(|err| err.0)?;
     Ok(print_expr(&surface))
 }
 
+/// Solve linear equations for `unknowns` and print the solution as
+/// `x = 2, y = 1`, with the free unknowns of a parametric solution after
+/// `for any`. `let` bindings of the unknowns are ignored.
+fn run_solve(
+    eqs: &[Expr],
+    unknowns: &[String],
+    bindings: &HashMap<Symbol, Term>,
+    ops: &InfixTable,
+    numbers: Numbers,
+) -> Result<String, String> {
+    let unknowns: Vec<Symbol> = unknowns.iter().map(|x| sym(x)).collect();
+    let mut bindings = bindings.clone();
+    for x in &unknowns {
+        bindings.insert(x.clone(), Term::Var(x.clone()));
+    }
+    let mut terms = Vec::new();
+    for e in eqs {
+        terms.push(lower_resolved(e, &bindings)?);
+    }
+    let (sigma, free) = match solve(&terms, &unknowns).map_err(|err| err.0)? {
+        Solution::Unique(sigma) => (sigma, Vec::new()),
+        Solution::Parametric(sigma, free) => (sigma, free),
+        Solution::Inconsistent => return Ok("no solution".into()),
+        Solution::Conditional(condition) => {
+            let surface = to_surface_in(&condition, ops, numbers).map_err(|err| err.0)?;
+            return Err(format!(
+                "the equations are consistent only if {}",
+                print_expr(&surface)
+            ));
+        }
+    };
+    let mut parts = Vec::new();
+    for x in unknowns.iter().filter(|x| sigma.contains_key(*x)) {
+        let surface = to_surface_in(&sigma[x], ops, numbers).map_err(|err| err.0)?;
+        parts.push(format!("{} = {}", x, print_expr(&surface)));
+    }
+    let free: Vec<&str> = free.iter().map(|x| x.as_ref()).collect();
+    Ok(match (parts.is_empty(), free.is_empty()) {
+        (_, true) => parts.join(", "),
+        (true, false) => format!("any {}", free.join(", ")),
+        (false, false) => format!("{} for any {}", parts.join(", "), free.join(", ")),
+    })
+}
+
+/// Cancel common factors in `e` and print the result, followed by the side
+/// conditions under which it equals `e`: `x + 1 if x ≠ 1`.
+fn run_cancel(
+    e: &Expr,
+    bindings: &HashMap<Symbol, Term>,
+    ops: &InfixTable,
+    numbers: Numbers,
+) -> Result<String, String> {
+    let (t, conditions) = cancel(&lower_resolved(e, bindings)?).map_err(|err| err.0)?;
+    let out = print_expr(&to_surface_in(&t, ops, numbers).map_err(|err| err.0)?);
+    match conditions
+        .into_iter()
+        .reduce(|a, b| Term::App(sym("∧"), vec![a, b]))
+    {
+        Some(c) => {
+            let c = to_surface_in(&c, ops, numbers).map_err(|err| err.0)?;
+            Ok(format!("{} if {}", out, print_expr(&c)))
+        }
+        None => Ok(out),
+    }
+}
+
+/// Simplify a proposition and report whether it reduced to `True`, to
+/// `False`, or to some other residual goal. With `by_cases`, undecided
+/// conditions are split on and the proposition must hold in every branch.
+/// Hypotheses are assumed while simplifying, unless the goal and every
+/// hypothesis are equations (or a monomial order is given): then the goal is
+/// decided by ideal membership, see `run_ideal_membership`.
+#[allow(clippy::too_many_arguments)]
+fn run_prove(
+    e: &Expr,
+    hyps: &[Expr],
+    order: Option<MonomialOrder>,
+    by_cases: bool,
+    bindings: &HashMap<Symbol, Term>,
+    theory: &Theory,
+    ops: &InfixTable,
+    numbers: Numbers,
+) -> Result<String, String> {
+    let t = lower_resolved(e, bindings)?;
+    let hyps = hyps
+        .iter()
+        .map(|h| lower_resolved(h, bindings))
+        .collect::<Result<Vec<_>, _>>()?;
+    let equations = std::iter::once(&t)
+        .chain(&hyps)
+        .all(|h| as_equation(h).is_some());
+    // An explicit `using <order>` settles the goal by ideal membership alone.
+    // Otherwise equations are tried that way first, and a goal outside the
+    // ideal is simplified under the hypotheses, where the theory's rules apply.
+    if !by_cases && (order.is_some() || (!hyps.is_empty() && equations)) {
+        let (proved, outcome) = run_ideal_membership(
+            &t,
+            &hyps,
+            order.unwrap_or(MonomialOrder::Grevlex),
+            ops,
+            numbers,
+        )?;
+        if proved || order.is_some() {
+            return Ok(outcome);
+        }
+    }
+    let assumption = hyps
+        .into_iter()
+        .reduce(|a, b| Term::App(sym("∧"), vec![a, b]));
+    let t = match (assumption, by_cases) {
+        (a, true) => simplify_by_cases(&t, a.as_ref(), theory),
+        (Some(a), false) => simplify_assuming(&t, &a, theory),
+        (None, false) => simplify(&t, theory),
+    };
+    match as_truth(&t) {
+        Some(true) => Ok("proved".into()),
+        Some(false) => Ok("disproved".into()),
+        None => {
+            let surface = to_surface_in(&t, ops, numbers).map_err(|err| err.0)?;
+            Ok(format!(
+                "not proved; remaining goal: {}",
+                print_expr(&surface)
+            ))
+        }
+    }
+}
+
+fn as_equation(t: &Term) -> Option<(&Term, &Term)> {
+    match t {
+        Term::App(f, args) if f.as_ref() == "=" && args.len() == 2 => Some((&args[0], &args[1])),
+        _ => None,
+    }
+}
+
+/// Prove the equation `goal` from the equations `hyps` over ℚ[x, …]: report
+/// the cofactors `ci` with `l - r = c1 · (l1 - r1) + …`, or the remainder of
+/// `l - r` on division by a Gröbner basis when it is not a consequence. The
+/// flag says whether the goal was proved.
+fn run_ideal_membership(
+    goal: &Term,
+    hyps: &[Term],
+    order: MonomialOrder,
+    ops: &InfixTable,
+    numbers: Numbers,
+) -> Result<(bool, String), String> {
+    let show = |t: &Term| -> Result<String, String> {
+        Ok(print_expr(
+            &to_surface_in(t, ops, numbers).map_err(|err| err.0)?,
+        ))
+    };
+    let difference = |t: &Term| -> Result<Poly, String> {
+        let (l, r) = match as_equation(t) {
+            Some(eq) => eq,
+            None => return Err(format!("expected an equation, got {}", show(t)?)),
+        };
+        Ok(&Poly::from_term(l) - &Poly::from_term(r))
+    };
+    let p = difference(goal)?;
+    let hs = hyps.iter().map(difference).collect::<Result<Vec<_>, _>>()?;
+    match ideal_membership(&p, &hs, order) {
+        Membership::Member(cofactors) => {
+            let certificate = cofactors
+                .iter()
+                .zip(&hs)
+                .filter(|(c, _)| !c.is_zero())
+                .map(|(c, h)| match c.as_constant() {
+                    Some(c) if c.is_one() => (false, h.to_term()),
+                    Some(c) if (-&c).is_one() => (true, h.to_term()),
+                    _ => (false, Term::App(sym("·"), vec![c.to_term(), h.to_term()])),
+                })
+                .fold(None, |acc, (negative, t)| match (acc, negative) {
+                    (None, false) => Some(t),
+                    (None, true) => Some(Term::App(sym("-"), vec![t])),
+                    (Some(a), false) => Some(Term::App(sym("+"), vec![a, t])),
+                    (Some(a), true) => Some(Term::App(sym("-"), vec![a, t])),
+                })
+                .unwrap_or_else(|| Poly::zero().to_term());
+            let eq = Term::App(sym("="), vec![p.to_term(), certificate]);
+            Ok((true, format!("proved: {}", show(&eq)?)))
+        }
+        Membership::NotMember(r) => Ok((
+            false,
+            format!("not proved; remaining goal: {} = 0", show(&r.to_term())?),
+        )),
+        Membership::Unknown => Ok((
+            false,
+            format!("not proved; gave up after {MAX_PAIRS} S-polynomials"),
+        )),
+    }
+}
+
+fn run_check(
+    name: &str,
+    theory: &Theory,
+    ops: &InfixTable,
+    numbers: Numbers,
+) -> Result<String, String> {
+    let nf = theory
+        .named
+        .get(&sym(name))
+        .ok_or_else(|| format!("no named fact `{name}`"))?;
+    let prop = Term::App(sym("="), vec![nf.lhs.clone(), nf.rhs.clone()]);
+    let outcome = check_fact(&prop, nf.condition.as_ref(), &nf.domains, theory);
+    Ok(describe_check(&outcome, ops, numbers))
+}
+
+fn describe_check(outcome: &CheckOutcome, ops: &InfixTable, numbers: Numbers) -> String {
+    let show = |t: &Term| match to_surface_in(t, ops, numbers) {
+        Ok(e) => print_expr(&e),
+        Err(err) => err.0,
+    };
+    let at = |instance: &[(Symbol, Term)]| -> String {
+        instance
+            .iter()
+            .map(|(v, t)| format!("{} = {}", v, show(t)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match outcome {
+        CheckOutcome::Passed(1) => "no counterexample in 1 instance".into(),
+        CheckOutcome::Passed(n) => format!("no counterexample in {} instances", n),
+        CheckOutcome::Counterexample(instance, failure) => {
+            let mut s = String::from("counterexample");
+            if !instance.is_empty() {
+                s.push_str(" at ");
+                s.push_str(&at(instance));
+            }
+            match failure {
+                Failure::Unequal(a, b) => s.push_str(&format!(": {} ≠ {}", show(a), show(b))),
+                Failure::False => {}
+                Failure::Error(err) => s.push_str(&format!(": {}", err.0)),
+            }
+            s
+        }
+        CheckOutcome::Undecided(instance, t) if instance.is_empty() => {
+            format!("not checked: cannot evaluate {}", show(t))
+        }
+        CheckOutcome::Undecided(instance, t) => {
+            format!(
+                "not checked: cannot evaluate {} at {}",
+                show(t),
+                at(instance)
+            )
+        }
+        CheckOutcome::Vacuous => "not checked: no instance satisfies the condition".into(),
+    }
+}
+
 fn run_apply(
     name: &str,
     e: &Expr,
     reverse: bool,
     bindings: &HashMap<Symbol, Term>,
     theory: &Theory,
+    ops: &InfixTable,
+    numbers: Numbers,
 ) -> Result<String, String> {
     let nf = theory
         .named
@@ -178,12 +734,11 @@ fn run_apply(
         (&nf.lhs, &nf.rhs)
     };
 
-    let target = lower(e).map_err(|err| err.0)?;
-    let target = subst(&target, bindings);
+    let target = lower_resolved(e, bindings)?;
 
     match apply_eq_conditional(pat, rhs, nf.condition.as_ref(), &target, theory) {
         Some(result) => {
-            let surface = to_surface(&result).map_err(|err| err.0)?;
+            let surface = to_surface_in(&result, ops, numbers).map_err(|err| err.0)?;
             Ok(print_expr(&surface))
         }
         None => Err(format!(
@@ -200,8 +755,12 @@ fn install_fact(
     e: &Expr,
     condition: Option<&Expr>,
     theory: &mut Theory,
+    check_facts: bool,
+    ops: &InfixTable,
+    numbers: Numbers,
 ) {
     let (body_expr, binder_cond) = extract_binder_conditions(e, theory);
+    let domains = binder_domains(e);
 
     // Merge binder-generated conditions with explicit `if` condition.
     let merged_cond: Option<Expr> = match (binder_cond, condition.cloned()) {
@@ -211,14 +770,20 @@ fn install_fact(
         (None, None) => None,
     };
 
-    let t = match lower(&body_expr) {
+    // The variables of a leading `∀` stay variables once it is stripped, and
+    // so does an `i` in the pattern.
+    let bound = match e {
+        Expr::Forall(vars, _, _) => fact_scope(&body_expr, vars),
+        _ => fact_scope(&body_expr, &[]),
+    };
+    let t = match lower_with(&body_expr, &bound) {
         Ok(t) => t,
         Err(err) => {
             println!("note: fact not installed: {}", err.0);
             return;
         }
     };
-    let cond_term = match merged_cond.as_ref().map(lower) {
+    let cond_term = match merged_cond.as_ref().map(|c| lower_with(c, &bound)) {
         Some(Ok(t)) => Some(t),
         Some(Err(err)) => {
             println!("note: condition not installed: {}", err.0);
@@ -227,10 +792,21 @@ fn install_fact(
         None => None,
     };
     let sym_name = name.as_deref().map(sym);
-    for effect in theory.install_fact(&t, sym_name, cond_term.as_ref()) {
+    if check_facts {
+        let outcome = check_fact(&t, cond_term.as_ref(), &domains, theory);
+        if let CheckOutcome::Counterexample(..) = outcome {
+            println!("warning: {}", describe_check(&outcome, ops, numbers));
+        }
+    }
+    let effects = theory.install_fact(&t, sym_name.clone(), cond_term.as_ref());
+    if let Some(nf) = sym_name.and_then(|n| theory.named.get_mut(&n)) {
+        nf.domains = domains;
+    }
+    for effect in effects {
         match effect {
             FactEffect::NotEquality => {}
             FactEffect::SubsetFact => {}
+            FactEffect::ImplicationInstalled => {}
             FactEffect::RuleInstalled => {}
             FactEffect::AlreadyKnown => {}
             FactEffect::Trivial => println!("note: trivial equality, no rule installed"),
@@ -252,10 +828,25 @@ fn install_fact(
             FactEffect::AcPromoted(f) => {
                 println!("note: `{}` promoted to AC", f)
             }
+            FactEffect::Inverses => println!("note: recognised multiplicative inverses for `/`"),
+            FactEffect::Derivative(f) => {
+                println!("note: registered derivative of `{}`", f)
+            }
         }
     }
 }
 
+/// The domain of each variable bound by a leading `∀` of `e`.
+fn binder_domains(e: &Expr) -> Vec<(Symbol, Term)> {
+    match e {
+        Expr::Forall(vars, domain, _) => match lower(domain) {
+            Ok(d) => vars.iter().map(|v| (sym(v), d.clone())).collect(),
+            Err(_) => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
 /// If `e` is `Forall(vars, domain, body)` and `domain` names a predicate-defined
 /// set in `theory`, generate membership conditions `v ∈ domain` for each `v` and
 /// return `(body, Some(conditions))`. Otherwise return `(e, None)`.
@@ -1,16 +1,25 @@
-use crate::ast::{Command, Expr, Op, UnaryOp};
-use crate::lexer::{tokenize, Token};
+use num_bigint::BigInt;
+
+use crate::ast::{
+    Assoc, BigOp, Command, Expr, InfixOp, InfixTable, MonomialOrder, Op, UnaryOp, PREDECLARED_INFIX,
+};
+use crate::lexer::{command_keyword, tokenize, tokenize_with, Token};
 
 #[derive(Debug)]
 pub struct ParseError(pub String);
 
 /// Parse a single REPL command from source text. Returns `None` for blank/comment-only input.
 pub fn parse_command(src: &str) -> Result<Option<Command>, ParseError> {
-    let tokens = tokenize(src).map_err(|e| ParseError(e.0))?;
+    parse_command_with(src, &InfixTable::new())
+}
+
+/// Like `parse_command`, with the user infix operators declared in `ops`.
+pub fn parse_command_with(src: &str, ops: &InfixTable) -> Result<Option<Command>, ParseError> {
+    let tokens = tokenize_with(src, ops).map_err(|e| ParseError(e.0))?;
     if tokens.is_empty() {
         return Ok(None);
     }
-    let mut p = Parser { tokens, pos: 0 };
+    let mut p = Parser { tokens, pos: 0, ops, no_in: false };
     let cmd = p.parse_command()?;
     p.expect_eof()?;
     Ok(Some(cmd))
@@ -18,19 +27,50 @@ pub fn parse_command(src: &str) -> Result<Option<Command>, ParseError> {
 
 /// Parse a standalone expression from source text.
 pub fn parse_expr(src: &str) -> Result<Expr, ParseError> {
-    let tokens = tokenize(src).map_err(|e| ParseError(e.0))?;
-    let mut p = Parser { tokens, pos: 0 };
+    parse_expr_with(src, &InfixTable::new())
+}
+
+/// Like `parse_expr`, with the user infix operators declared in `ops`.
+pub fn parse_expr_with(src: &str, ops: &InfixTable) -> Result<Expr, ParseError> {
+    let tokens = tokenize_with(src, ops).map_err(|e| ParseError(e.0))?;
+    let mut p = Parser { tokens, pos: 0, ops, no_in: false };
     let e = p.parse_expr(0)?;
     p.expect_eof()?;
     Ok(e)
 }
 
-struct Parser {
+/// An infix operator found in the token stream: built-in or user-declared.
+enum InfixKind {
+    Builtin(Op),
+    User(InfixOp),
+}
+
+impl InfixKind {
+    fn prec(&self) -> u8 {
+        match self {
+            InfixKind::Builtin(op) => op.prec(),
+            InfixKind::User(op) => op.prec,
+        }
+    }
+
+    fn right_assoc(&self) -> bool {
+        match self {
+            InfixKind::Builtin(op) => op.right_assoc(),
+            InfixKind::User(op) => op.assoc == Assoc::Right,
+        }
+    }
+}
+
+struct Parser<'a> {
     tokens: Vec<Token>,
     pos: usize,
+    ops: &'a InfixTable,
+    /// Set while parsing the value of a local `let`, where the ASCII `in`
+    /// ends the value instead of meaning `∈`. Brackets clear it again.
+    no_in: bool,
 }
 
-impl Parser {
+impl Parser<'_> {
     fn peek(&self) -> Option<&Token> {
         self.tokens.get(self.pos)
     }
@@ -47,6 +87,13 @@ impl Parser {
         t
     }
 
+    /// Whether the next token is the contextual keyword `word`. Words such
+    /// as `for`, `then` and `assuming` lex as identifiers and only act as
+    /// keywords where the grammar expects them, so they stay usable as names.
+    fn at_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(s)) if s == word)
+    }
+
     fn expect_eof(&self) -> Result<(), ParseError> {
         if self.pos == self.tokens.len() {
             Ok(())
@@ -59,6 +106,11 @@ impl Parser {
     }
 
     fn parse_command(&mut self) -> Result<Command, ParseError> {
+        if let Some(Token::Ident(word)) = self.peek() {
+            if let Some(keyword) = command_keyword(word) {
+                self.tokens[self.pos] = keyword;
+            }
+        }
         match self.peek() {
             Some(Token::Let) => {
                 self.advance();
@@ -129,10 +181,154 @@ impl Parser {
                 let e = self.parse_expr(0)?;
                 Ok(Command::Evaluate(e))
             }
+            Some(Token::Expand) => {
+                self.advance();
+                Ok(Command::Expand(self.parse_expr(0)?))
+            }
+            Some(Token::Normalize) => {
+                self.advance();
+                Ok(Command::Normalize(self.parse_expr(0)?))
+            }
+            Some(Token::Together) => {
+                self.advance();
+                Ok(Command::Together(self.parse_expr(0)?))
+            }
+            Some(Token::Cancel) => {
+                self.advance();
+                Ok(Command::Cancel(self.parse_expr(0)?))
+            }
+            Some(Token::Factor) => {
+                self.advance();
+                Ok(Command::Factor(self.parse_expr(0)?))
+            }
+            Some(Token::Approx) => {
+                self.advance();
+                let e = self.parse_expr(0)?;
+                let digits = match self.peek() {
+                    Some(Token::Int(n)) => {
+                        let d = u32::try_from(n)
+                            .map_err(|_| ParseError(format!("bad number of digits: {n}")))?;
+                        self.advance();
+                        Some(d)
+                    }
+                    _ => None,
+                };
+                Ok(Command::Approx(e, digits))
+            }
+            Some(Token::Diff) => {
+                self.advance();
+                // `diff(e, x)`, when that is the whole command.
+                if matches!(self.peek(), Some(Token::LParen)) {
+                    let start = self.pos;
+                    self.advance();
+                    if let Ok(e) = self.parse_bracketed() {
+                        if let (Some(Token::Comma), Some(Token::Ident(x)), Some(Token::RParen)) =
+                            (self.advance(), self.advance(), self.advance())
+                        {
+                            if self.pos == self.tokens.len() {
+                                return Ok(Command::Diff(e, x));
+                            }
+                        }
+                    }
+                    self.pos = start;
+                }
+                // `diff e for x`
+                let e = self.parse_expr(0)?;
+                let x = match (self.advance(), self.advance()) {
+                    (Some(Token::Ident(kw)), Some(Token::Ident(x))) if kw == "for" => x,
+                    _ => {
+                        return Err(ParseError(
+                            "expected `for <variable>` in diff command".into(),
+                        ))
+                    }
+                };
+                Ok(Command::Diff(e, x))
+            }
+            Some(Token::Series) => {
+                self.advance();
+                let e = self.parse_expr(0)?;
+                let x = match (self.advance(), self.advance(), self.advance()) {
+                    (Some(Token::Ident(kw)), Some(Token::Ident(x)), Some(Token::Equals))
+                        if kw == "about" =>
+                    {
+                        x
+                    }
+                    _ => {
+                        return Err(ParseError(
+                            "expected `about <variable> =` in series command".into(),
+                        ))
+                    }
+                };
+                // The point stops before `to`, and before a comparison.
+                let a = self.parse_expr(41)?;
+                let order = match (self.advance(), self.advance(), self.advance()) {
+                    (Some(Token::To), Some(Token::Ident(kw)), Some(Token::Int(n))) if kw == "order" => {
+                        u32::try_from(&n).map_err(|_| ParseError(format!("bad order: {n}")))?
+                    }
+                    _ => {
+                        return Err(ParseError(
+                            "expected `to order <n>` in series command".into(),
+                        ))
+                    }
+                };
+                Ok(Command::Series(e, x, a, order))
+            }
+            Some(Token::Solve) => {
+                self.advance();
+                let mut eqs = Vec::new();
+                if matches!(self.peek(), Some(Token::LBrace)) {
+                    self.advance();
+                    loop {
+                        eqs.push(self.parse_bracketed()?);
+                        match self.advance() {
+                            Some(Token::Comma) => {}
+                            Some(Token::RBrace) => break,
+                            other => {
+                                return Err(ParseError(format!(
+                                    "expected `,` or `}}` between equations, got {other:?}"
+                                )))
+                            }
+                        }
+                    }
+                } else {
+                    eqs.push(self.parse_expr(0)?);
+                }
+                match self.advance() {
+                    Some(Token::Ident(kw)) if kw == "for" => {}
+                    other => {
+                        return Err(ParseError(format!(
+                            "expected `for` in solve command, got {other:?}"
+                        )))
+                    }
+                }
+                let mut unknowns = Vec::new();
+                loop {
+                    match self.advance() {
+                        Some(Token::Ident(x)) => unknowns.push(x),
+                        other => {
+                            return Err(ParseError(format!(
+                                "expected unknown in solve command, got {other:?}"
+                            )))
+                        }
+                    }
+                    if !matches!(self.peek(), Some(Token::Comma)) {
+                        break;
+                    }
+                    self.advance(); // consume ','
+                }
+                Ok(Command::Solve(eqs, unknowns))
+            }
             Some(Token::Simplify) => {
                 self.advance();
                 let e = self.parse_expr(0)?;
-                Ok(Command::Simplify(e))
+                let assumption = if self.at_word("assuming") {
+                    self.advance();
+                    Some(self.parse_expr(0)?)
+                } else {
+                    None
+                };
+                let by_cases = self.parse_by_cases()?;
+                Ok(Command::Simplify(e, assumption, by_cases))
             }
             Some(Token::Apply) => {
                 self.advance();
@@ -165,8 +361,91 @@ impl Parser {
                     Ok(Command::Apply(name, e))
                 }
             }
+            Some(Token::Prove) => {
+                self.advance();
+                let e = self.parse_expr(0)?;
+                let mut hyps = Vec::new();
+                if self.at_word("assuming") {
+                    self.advance();
+                    hyps.push(self.parse_expr(0)?);
+                    while matches!(self.peek(), Some(Token::Comma)) {
+                        self.advance();
+                        hyps.push(self.parse_expr(0)?);
+                    }
+                }
+                let order = self.parse_monomial_order()?;
+                let by_cases = self.parse_by_cases()?;
+                Ok(Command::Prove(e, hyps, order, by_cases))
+            }
+            Some(Token::Check) => {
+                self.advance();
+                match self.advance() {
+                    Some(Token::Ident(name)) => Ok(Command::Check(name)),
+                    other => Err(ParseError(format!(
+                        "expected fact name after `check`, got {other:?}"
+                    ))),
+                }
+            }
+            Some(Token::Infix) => {
+                self.advance();
+                let prec = match self.advance() {
+                    Some(Token::Int(n)) => match u8::try_from(&n) {
+                        Ok(p) if (1..=99).contains(&p) => p,
+                        _ => {
+                            return Err(ParseError(format!(
+                                "infix precedence must be between 1 and 99, got {n}"
+                            )))
+                        }
+                    },
+                    other => {
+                        return Err(ParseError(format!(
+                            "expected precedence after `infix`, got {other:?}"
+                        )))
+                    }
+                };
+                let assoc = match self.advance() {
+                    Some(Token::Ident(s)) if s == "left" => Assoc::Left,
+                    Some(Token::Ident(s)) if s == "right" => Assoc::Right,
+                    Some(Token::Ident(s)) if s == "none" => Assoc::None,
+                    other => {
+                        return Err(ParseError(format!(
+                            "expected `left`, `right` or `none` in infix declaration, got {other:?}"
+                        )))
+                    }
+                };
+                let symbol = match self.advance() {
+                    Some(Token::Symbol(s)) => s,
+                    other => {
+                        return Err(ParseError(format!(
+                            "expected an operator symbol in infix declaration, got {other:?}"
+                        )))
+                    }
+                };
+                // A symbol that is already one built-in token, a command
+                // word, or the predeclared `mod` or `div` cannot be
+                // redeclared.
+                if PREDECLARED_INFIX.contains(&symbol.as_str()) {
+                    return Err(ParseError(format!(
+                        "`{symbol}` is a built-in operator and cannot be redeclared"
+                    )));
+                }
+                match tokenize(&symbol).as_deref() {
+                    Ok([Token::Ident(w)]) if command_keyword(w).is_some() => {
+                        return Err(ParseError(format!(
+                            "`{symbol}` is a command word and cannot be declared infix"
+                        )))
+                    }
+                    Ok([Token::Ident(_) | Token::Symbol(_) | Token::UserOp(_)]) | Ok([_, _, ..]) => {}
+                    _ => {
+                        return Err(ParseError(format!(
+                            "`{symbol}` is a built-in operator or keyword and cannot be declared infix"
+                        )))
+                    }
+                }
+                Ok(Command::Infix(InfixOp { symbol, prec, assoc }))
+            }
             other => Err(ParseError(format!(
-                "expected command (let/fact/print/evaluate/simplify/apply), got {other:?}"
+                "expected command (let/fact/print/evaluate/simplify/expand/normalize/together/cancel/factor/approx/apply/prove/check/infix), got {other:?}"
             ))),
         }
     }
@@ -186,6 +465,7 @@ impl Parser {
             Token::NotEquals => Some(Op::Ne),
             Token::Subset => Some(Op::Subset),
             Token::In => Some(Op::In),
+            Token::InKw if !self.no_in => Some(Op::In),
             Token::Lt => Some(Op::Lt),
             Token::Gt => Some(Op::Gt),
             Token::Le => Some(Op::Le),
@@ -194,10 +474,62 @@ impl Parser {
         }
     }
 
+    fn peek_infix(&self) -> Option<InfixKind> {
+        if let Some(op) = self.peek_binop() {
+            return Some(InfixKind::Builtin(op));
+        }
+        match self.peek()? {
+            Token::UserOp(s) => self.ops.get(s).cloned().map(InfixKind::User),
+            _ => None,
+        }
+    }
+
+    /// Parse an optional `using lex|grevlex|kbo` clause.
+    fn parse_monomial_order(&mut self) -> Result<Option<MonomialOrder>, ParseError> {
+        if !self.at_word("using") {
+            return Ok(None);
+        }
+        self.advance();
+        match self.advance() {
+            Some(Token::Ident(s)) if s == "lex" => Ok(Some(MonomialOrder::Lex)),
+            Some(Token::Ident(s)) if s == "grevlex" => Ok(Some(MonomialOrder::Grevlex)),
+            Some(Token::Ident(s)) if s == "kbo" => Ok(Some(MonomialOrder::Kbo)),
+            other => Err(ParseError(format!(
+                "expected monomial order (lex/grevlex/kbo) after `using`, got {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Parse an optional trailing `by cases` clause.
+    fn parse_by_cases(&mut self) -> Result<bool, ParseError> {
+        if !self.at_word("by") {
+            return Ok(false);
+        }
+        self.advance();
+        match self.advance() {
+            Some(Token::Ident(kw)) if kw == "cases" => Ok(true),
+            other => Err(ParseError(format!("expected `cases` after `by`, got {:?}", other))),
+        }
+    }
+
+    /// Parse an expression enclosed in brackets, where `in` means `∈` again.
+    fn parse_bracketed(&mut self) -> Result<Expr, ParseError> {
+        let saved = std::mem::replace(&mut self.no_in, false);
+        let e = self.parse_expr(0);
+        self.no_in = saved;
+        e
+    }
+
     /// Precedence-climbing expression parser.
     fn parse_expr(&mut self, min_prec: u8) -> Result<Expr, ParseError> {
         let mut lhs = self.parse_atom()?;
-        while let Some(op) = self.peek_binop() {
+        if let Some(Token::Symbol(s)) = self.peek() {
+            return Err(ParseError(format!(
+                "unknown operator `{s}`; declare it with `infix <prec> <assoc> {s}`"
+            )));
+        }
+        while let Some(op) = self.peek_infix() {
             if op.prec() < min_prec {
                 break;
             }
@@ -208,7 +540,10 @@ impl Parser {
                 op.prec() + 1
             };
             let rhs = self.parse_expr(next_min)?;
-            lhs = Expr::BinOp(op, Box::new(lhs), Box::new(rhs));
+            lhs = match op {
+                InfixKind::Builtin(op) => Expr::BinOp(op, Box::new(lhs), Box::new(rhs)),
+                InfixKind::User(op) => Expr::Infix(op, Box::new(lhs), Box::new(rhs)),
+            };
         }
         Ok(lhs)
     }
@@ -218,15 +553,24 @@ impl Parser {
         // Unary minus
         if matches!(self.peek(), Some(Token::Minus)) {
             self.advance();
-            if matches!(self.peek(), Some(Token::Int(_))) {
+            if matches!(self.peek(), Some(Token::Int(_) | Token::Decimal(_)))
+                && !matches!(self.peek2(), Some(Token::Bang))
+            {
                 return match self.advance() {
-                    Some(Token::Int(n)) => Ok(Expr::Int(-n)),
+                    Some(Token::Int(n)) => self.parse_int_suffix(-n),
+                    Some(Token::Decimal(r)) => Ok(Expr::Decimal(-r)),
                     _ => unreachable!(),
                 };
             }
             let operand = self.parse_atom()?;
             return Ok(Expr::UnaryOp(UnaryOp::Neg, Box::new(operand)));
         }
+        // Logical negation binds like unary minus: `¬a = b` is `(¬a) = b`
+        if matches!(self.peek(), Some(Token::Not)) {
+            self.advance();
+            let operand = self.parse_atom()?;
+            return Ok(Expr::UnaryOp(UnaryOp::Not, Box::new(operand)));
+        }
         // `∀ var, var, … ∈ domain. body`
         if matches!(self.peek(), Some(Token::ForAll)) {
             self.advance();
@@ -244,7 +588,7 @@ impl Parser {
                 self.advance(); // consume ','
             }
             match self.advance() {
-                Some(Token::In) => {}
+                Some(Token::In | Token::InKw) => {}
                 other => return Err(ParseError(format!(
                     "expected ∈ after variables in ∀ binding, got {other:?}"
                 ))),
@@ -259,6 +603,110 @@ impl Parser {
             let body = self.parse_expr(0)?;
             return Ok(Expr::Forall(vars, Box::new(domain), Box::new(body)));
         }
+        // `if cond then a else b` — piecewise expression
+        if matches!(self.peek(), Some(Token::If)) {
+            self.advance();
+            let cond = self.parse_expr(0)?;
+            match self.advance() {
+                Some(Token::Ident(kw)) if kw == "then" => {}
+                other => return Err(ParseError(format!(
+                    "expected `then` after condition, got {other:?}"
+                ))),
+            }
+            let then = self.parse_expr(0)?;
+            match self.advance() {
+                Some(Token::Ident(kw)) if kw == "else" => {}
+                other => return Err(ParseError(format!(
+                    "expected `else` in if-expression, got {other:?}"
+                ))),
+            }
+            let otherwise = self.parse_expr(0)?;
+            return Ok(Expr::If(Box::new(cond), Box::new(then), Box::new(otherwise)));
+        }
+        // `∑ body for var from lo to hi` — finite sum or product. The bounds
+        // stop before comparisons, so `∑ k for k from 1 to n = …` is an equation.
+        // The ASCII `sum` and `prod` only start one when `for` follows the body;
+        // otherwise they are names, as in `sum(x)` or `let sum = 3`.
+        let big_op = match self.peek() {
+            Some(Token::Sum) => Some((BigOp::Sum, false)),
+            Some(Token::Product) => Some((BigOp::Product, false)),
+            Some(Token::Ident(s)) if s == "sum" => Some((BigOp::Sum, true)),
+            Some(Token::Ident(s)) if s == "prod" => Some((BigOp::Product, true)),
+            _ => None,
+        };
+        if let Some((op, ascii)) = big_op {
+            let start = self.pos;
+            self.advance();
+            let body = self.parse_expr(0);
+            if ascii && !(body.is_ok() && self.at_word("for")) {
+                self.pos = start;
+            } else {
+                let body = body?;
+                match self.advance() {
+                    Some(Token::Ident(kw)) if kw == "for" => {}
+                    other => return Err(ParseError(format!(
+                        "expected `for` after the body of {}, got {other:?}", op.symbol()
+                    ))),
+                }
+                let var = match self.advance() {
+                    Some(Token::Ident(s)) => s,
+                    other => return Err(ParseError(format!(
+                        "expected index variable after `for`, got {other:?}"
+                    ))),
+                };
+                match self.advance() {
+                    Some(Token::Ident(kw)) if kw == "from" => {}
+                    other => return Err(ParseError(format!(
+                        "expected `from` after index variable, got {other:?}"
+                    ))),
+                }
+                let lo = self.parse_expr(41)?;
+                match self.advance() {
+                    Some(Token::To) => {}
+                    other => return Err(ParseError(format!(
+                        "expected `to` after lower bound, got {other:?}"
+                    ))),
+                }
+                let hi = self.parse_expr(41)?;
+                return Ok(Expr::BigOp(op, var, Box::new(lo), Box::new(hi), Box::new(body)));
+            }
+        }
+        // `let name [: ty] = value in body` — local definition
+        if matches!(self.peek(), Some(Token::Let)) {
+            self.advance();
+            let name = match self.advance() {
+                Some(Token::Ident(s)) => s,
+                other => return Err(ParseError(format!(
+                    "expected identifier after `let`, got {other:?}"
+                ))),
+            };
+            let ty = if matches!(self.peek(), Some(Token::Colon)) {
+                self.advance(); // consume ':'
+                // Same as the `let` command: stop the type before `=`.
+                Some(Box::new(self.parse_expr(41)?))
+            } else {
+                None
+            };
+            match self.advance() {
+                Some(Token::Equals) => {}
+                other => return Err(ParseError(format!(
+                    "expected `=` in local let, got {other:?}"
+                ))),
+            }
+            // Parse value — `in` ends it rather than meaning `∈`
+            let saved = std::mem::replace(&mut self.no_in, true);
+            let value = self.parse_expr(0);
+            self.no_in = saved;
+            let value = value?;
+            match self.advance() {
+                Some(Token::InKw) => {}
+                other => return Err(ParseError(format!(
+                    "expected `in` after local let value, got {other:?}"
+                ))),
+            }
+            let body = self.parse_expr(0)?;
+            return Ok(Expr::Let(name, ty, Box::new(value), Box::new(body)));
+        }
         // `{var ∈ domain | pred}` — set-builder comprehension
         if matches!(self.peek(), Some(Token::LBrace)) {
             self.advance();
@@ -269,13 +717,13 @@ impl Parser {
                 ))),
             };
             match self.advance() {
-                Some(Token::In) => {}
+                Some(Token::In | Token::InKw) => {}
                 other => return Err(ParseError(format!(
                     "expected ∈ in set-builder, got {other:?}"
                 ))),
             }
             // Parse domain — stops naturally before `|` (not a binop)
-            let domain = self.parse_expr(0)?;
+            let domain = self.parse_bracketed()?;
             match self.advance() {
                 Some(Token::Bar) => {}
                 other => return Err(ParseError(format!(
@@ -283,7 +731,7 @@ impl Parser {
                 ))),
             }
             // Parse predicate — stops naturally before `}`
-            let pred = self.parse_expr(0)?;
+            let pred = self.parse_bracketed()?;
             match self.advance() {
                 Some(Token::RBrace) => {}
                 other => return Err(ParseError(format!(
@@ -292,15 +740,57 @@ impl Parser {
             }
             return Ok(Expr::SetBuilder(var, Box::new(domain), Box::new(pred)));
         }
+        let primary = self.parse_primary()?;
+        Ok(self.parse_factorials(primary))
+    }
+
+    /// Apply any postfix `!`s: `n!` is `factorial(n)`. It binds tighter than
+    /// unary minus, so `-3!` is `-(3!)`.
+    fn parse_factorials(&mut self, mut e: Expr) -> Expr {
+        while matches!(self.peek(), Some(Token::Bang)) {
+            self.advance();
+            e = Expr::App("factorial".into(), vec![e]);
+        }
+        e
+    }
+
+    /// An integer literal with what may follow it directly: `nℤ`, the
+    /// multiples of `n` as in `ℤ/7ℤ`, or `(mod n)`, making it a residue.
+    fn parse_int_suffix(&mut self, n: BigInt) -> Result<Expr, ParseError> {
+        if matches!(self.peek(), Some(Token::Ident(z)) if z == "ℤ") {
+            self.advance();
+            return Ok(Expr::BinOp(Op::Mul, Box::new(Expr::Int(n)), Box::new(Expr::Ident("ℤ".into()))));
+        }
+        let is_mod = |t: Option<&Token>| matches!(t, Some(Token::UserOp(s) | Token::Ident(s)) if s == "mod");
+        if !(matches!(self.peek(), Some(Token::LParen)) && is_mod(self.peek2())) {
+            return Ok(Expr::Int(n));
+        }
+        self.advance(); // consume '('
+        self.advance(); // consume 'mod'
+        let modulus = match self.advance() {
+            Some(Token::Int(m)) => m,
+            other => return Err(ParseError(format!(
+                "expected a modulus after `(mod`, got {other:?}"
+            ))),
+        };
         match self.advance() {
-            Some(Token::Ident(s)) => {
+            Some(Token::RParen) => Ok(Expr::Residue(n, modulus)),
+            other => Err(ParseError(format!("expected `)` after modulus, got {other:?}"))),
+        }
+    }
+
+    /// Parse a name, application, literal or parenthesized expression.
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        match self.advance() {
+            // A declared infix symbol is also accepted in prefix form: `∘(f, g)`.
+            Some(Token::Ident(s) | Token::UserOp(s)) => {
                 // Check for function application: `f(a, b, ...)`
                 if matches!(self.peek(), Some(Token::LParen)) {
                     self.advance(); // consume '('
                     let mut args = vec![];
                     if !matches!(self.peek(), Some(Token::RParen)) {
                         loop {
-                            args.push(self.parse_expr(0)?);
+                            args.push(self.parse_bracketed()?);
                             if !matches!(self.peek(), Some(Token::Comma)) {
                                 break;
                             }
@@ -314,13 +804,23 @@ impl Parser {
                         ))),
                     }
                     Ok(Expr::App(s, args))
+                } else if self.ops.get(&s).is_some() {
+                    Err(ParseError(format!(
+                        "infix operator `{s}` used without operands"
+                    )))
                 } else {
                     Ok(Expr::Ident(s))
                 }
             }
-            Some(Token::Int(n)) => Ok(Expr::Int(n)),
+            Some(Token::Symbol(s)) => Err(ParseError(format!(
+                "unknown operator `{s}`; declare it with `infix <prec> <assoc> {s}`"
+            ))),
+            Some(Token::Int(n)) => self.parse_int_suffix(n),
+            Some(Token::Decimal(r)) => Ok(Expr::Decimal(r)),
+            Some(Token::True) => Ok(Expr::Bool(true)),
+            Some(Token::False) => Ok(Expr::Bool(false)),
             Some(Token::LParen) => {
-                let e = self.parse_expr(0)?;
+                let e = self.parse_bracketed()?;
                 match self.advance() {
                     Some(Token::RParen) => Ok(e),
                     other => Err(ParseError(format!("expected `)`, got {other:?}"))),
@@ -1,4 +1,8 @@
-use crate::ast::{Command, Expr, Op};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+use crate::ast::{Assoc, Command, Expr, Op, UnaryOp};
 
 #[derive(Clone, Copy)]
 enum Side {
@@ -7,24 +11,111 @@ enum Side {
     Top,
 }
 
+/// Which spelling to use for operators and keywords that have both a Unicode
+/// and an ASCII form. Both styles parse back to the same AST.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Unicode,
+    Ascii,
+}
+
 /// Render an expression to surface syntax.
 pub fn print_expr(e: &Expr) -> String {
+    print_expr_in(e, Style::Unicode)
+}
+
+/// Render an expression using only ASCII spellings (`*`, `<=`, `forall`, …).
+/// User-declared infix symbols are printed as declared.
+pub fn print_expr_ascii(e: &Expr) -> String {
+    print_expr_in(e, Style::Ascii)
+}
+
+pub fn print_command(c: &Command) -> String {
+    print_command_in(c, Style::Unicode)
+}
+
+/// Render a command using only ASCII spellings, like `print_expr_ascii`.
+pub fn print_command_ascii(c: &Command) -> String {
+    print_command_in(c, Style::Ascii)
+}
+
+/// `r` in decimal notation: `0.25`, or `0.1(6)` with the repeating period in
+/// parentheses. A terminating decimal is written with an exponent (`1e-10`)
+/// when that is at least three characters shorter. `None` if the period is longer than `max_period`
+/// digits.
+pub fn decimal(r: &BigRational, max_period: usize) -> Option<String> {
+    let sign = if r.is_negative() { "-" } else { "" };
+    let (n, d) = (r.numer().abs(), r.denom());
+    // With `d = 2^a · 5^b · m` and `m` coprime to 10, `max(a, b)` digits
+    // come before the period, whose length is the order of 10 modulo `m`.
+    let (two, five, ten) = (BigInt::from(2), BigInt::from(5), BigInt::from(10));
+    let (mut m, mut a, mut b) = (d.clone(), 0, 0);
+    while (&m % &two).is_zero() {
+        m /= &two;
+        a += 1;
+    }
+    while (&m % &five).is_zero() {
+        m /= &five;
+        b += 1;
+    }
+    let mut period = 0;
+    if !m.is_one() {
+        let mut x = &ten % &m;
+        period = 1;
+        while !x.is_one() {
+            if period == max_period {
+                return None;
+            }
+            x = x * &ten % &m;
+            period += 1;
+        }
+    }
+    let pre = usize::max(a, b);
+    let mut rem = &n % d;
+    let mut digits = String::new();
+    for _ in 0..pre + period {
+        rem *= &ten;
+        digits.push_str(&(&rem / d).to_string());
+        rem %= d;
+    }
+    let int = (&n / d).to_string();
+    if period > 0 {
+        let (fixed, repeating) = digits.split_at(pre);
+        return Some(format!("{sign}{int}.{fixed}({repeating})"));
+    }
+    let frac = if digits.is_empty() { "0" } else { digits.as_str() };
+    let positional = format!("{sign}{int}.{frac}");
+    // The significant digits `s` and the exponent of the last one.
+    let all = format!("{int}{digits}");
+    let s = all.trim_start_matches('0').trim_end_matches('0');
+    if s.is_empty() {
+        return Some(positional);
+    }
+    let last = (all.len() - all.trim_end_matches('0').len()) as i64 - digits.len() as i64;
+    let (head, tail) = s.split_at(1);
+    let point = if tail.is_empty() { "" } else { "." };
+    let scientific = format!("{sign}{head}{point}{tail}e{}", last + s.len() as i64 - 1);
+    Some(if scientific.len() + 3 <= positional.len() { scientific } else { positional })
+}
+
+fn print_expr_in(e: &Expr, style: Style) -> String {
     let mut out = String::new();
-    fmt_expr(e, 0, Side::Top, &mut out);
+    fmt_expr(e, 0, Side::Top, style, &mut out);
     out
 }
 
-pub fn print_command(c: &Command) -> String {
+fn print_command_in(c: &Command, style: Style) -> String {
+    let show = |e: &Expr| print_expr_in(e, style);
     match c {
         Command::Let(name, ty, rhs) => {
             let mut s = format!("let {name}");
             if let Some(t) = ty {
                 s.push_str(" : ");
-                s.push_str(&print_expr(t));
+                s.push_str(&show(t));
             }
             if let Some(r) = rhs {
                 s.push_str(" = ");
-                s.push_str(&print_expr(r));
+                s.push_str(&show(r));
             }
             s
         }
@@ -34,25 +125,92 @@ pub fn print_command(c: &Command) -> String {
                 s.push_str(n);
                 s.push_str(" : ");
             }
-            s.push_str(&print_expr(e));
+            s.push_str(&show(e));
             if let Some(c) = cond {
                 s.push_str(" if ");
-                s.push_str(&print_expr(c));
+                s.push_str(&show(c));
+            }
+            s
+        }
+        Command::Print(e) => format!("print {}", show(e)),
+        Command::Evaluate(e) => format!("evaluate {}", show(e)),
+        Command::Simplify(e, assumption, by_cases) => {
+            let mut s = format!("simplify {}", show(e));
+            if let Some(c) = assumption {
+                s.push_str(" assuming ");
+                s.push_str(&show(c));
+            }
+            if *by_cases {
+                s.push_str(" by cases");
+            }
+            s
+        }
+        Command::Expand(e) => format!("expand {}", show(e)),
+        Command::Normalize(e) => format!("normalize {}", show(e)),
+        Command::Together(e) => format!("together {}", show(e)),
+        Command::Cancel(e) => format!("cancel {}", show(e)),
+        Command::Factor(e) => format!("factor {}", show(e)),
+        Command::Approx(e, digits) => match digits {
+            Some(d) => format!("approx {} {}", show(e), d),
+            None => format!("approx {}", show(e)),
+        },
+        Command::Diff(e, x) => format!("diff({}, {})", show(e), x),
+        Command::Series(e, x, a, n) => {
+            format!("series {} about {} = {} to order {}", show(e), x, show(a), n)
+        }
+        Command::Solve(eqs, unknowns) => {
+            let eqs: Vec<String> = eqs.iter().map(show).collect();
+            match eqs.as_slice() {
+                [eq] => format!("solve {} for {}", eq, unknowns.join(", ")),
+                _ => format!("solve {{{}}} for {}", eqs.join(", "), unknowns.join(", ")),
+            }
+        }
+        Command::Apply(name, e) => format!("apply {} to {}", name, show(e)),
+        Command::ApplyRev(name, e) => {
+            let arrow = if style == Style::Ascii { "<-" } else { "←" };
+            format!("apply {} {} to {}", arrow, name, show(e))
+        }
+        Command::Infix(op) => format!("infix {} {} {}", op.prec, op.assoc.keyword(), op.symbol),
+        Command::Prove(e, hyps, order, by_cases) => {
+            let mut s = format!("prove {}", show(e));
+            if !hyps.is_empty() {
+                let hyps: Vec<String> = hyps.iter().map(show).collect();
+                s.push_str(" assuming ");
+                s.push_str(&hyps.join(", "));
+            }
+            if let Some(order) = order {
+                s.push_str(" using ");
+                s.push_str(order.keyword());
+            }
+            if *by_cases {
+                s.push_str(" by cases");
             }
             s
         }
-        Command::Print(e) => format!("print {}", print_expr(e)),
-        Command::Evaluate(e) => format!("evaluate {}", print_expr(e)),
-        Command::Simplify(e) => format!("simplify {}", print_expr(e)),
-        Command::Apply(name, e) => format!("apply {} to {}", name, print_expr(e)),
-        Command::ApplyRev(name, e) => format!("apply ← {} to {}", name, print_expr(e)),
+        Command::Check(name) => format!("check {}", name),
     }
 }
 
-fn fmt_expr(e: &Expr, parent: u8, side: Side, out: &mut String) {
+fn fmt_expr(e: &Expr, parent: u8, side: Side, style: Style, out: &mut String) {
+    let ascii = style == Style::Ascii;
+    let in_kw = if ascii { " in " } else { " ∈ " };
+    // A negative base of `^` is bracketed, `(-4) ^ (1 / 2)`, so it cannot be
+    // read as `-(4 ^ (1 / 2))`.
+    if matches!(side, Side::Left) && parent == Op::Pow.prec() && is_negative(e) {
+        out.push('(');
+        fmt_expr(e, 0, Side::Top, style, out);
+        out.push(')');
+        return;
+    }
     match e {
         Expr::Ident(s) => out.push_str(s),
         Expr::Int(n) => out.push_str(&n.to_string()),
+        Expr::Decimal(r) => out.push_str(&decimal(r, usize::MAX).expect("unbounded period")),
+        // As an arithmetic operand, `x + (1 (mod 7))` is not read as a sum
+        // modulo 7.
+        Expr::Residue(v, n) if parent >= Op::Add.prec() => out.push_str(&format!("({v} (mod {n}))")),
+        Expr::Residue(v, n) => out.push_str(&format!("{v} (mod {n})")),
+        Expr::Bool(b) => out.push_str(if *b { "True" } else { "False" }),
         Expr::App(f, args) => {
             out.push_str(f);
             out.push('(');
@@ -60,47 +218,111 @@ fn fmt_expr(e: &Expr, parent: u8, side: Side, out: &mut String) {
                 if i > 0 {
                     out.push_str(", ");
                 }
-                fmt_expr(a, 0, Side::Top, out);
+                fmt_expr(a, 0, Side::Top, style, out);
             }
             out.push(')');
         }
         Expr::BinOp(op, l, r) => {
-            let p = op.prec();
-            let needs = p < parent || (p == parent && wrong_side(*op, side));
+            if let (Op::Mul, Some(n)) = (op, multiples(l, r)) {
+                // `nℤ`, the multiples of `n`, reads back as one operand.
+                out.push_str(&format!("{n}ℤ"));
+                return;
+            }
+            let symbol = if ascii { op.ascii_symbol() } else { op.symbol() };
+            fmt_infix(symbol, op.prec(), op.assoc(), l, r, parent, side, style, out)
+        }
+        Expr::Infix(op, l, r) => {
+            fmt_infix(&op.symbol, op.prec, op.assoc, l, r, parent, side, style, out)
+        }
+        Expr::UnaryOp(op, operand) => {
+            out.push_str(if ascii { op.ascii_symbol() } else { op.symbol() });
+            let needs = matches!(
+                **operand,
+                Expr::BinOp(_, _, _)
+                    | Expr::Residue(_, _)
+                    | Expr::Infix(_, _, _)
+                    | Expr::Forall(_, _, _)
+                    | Expr::Let(_, _, _, _)
+                    | Expr::If(_, _, _)
+                    | Expr::BigOp(..)
+            );
             if needs {
                 out.push('(');
             }
-            fmt_expr(l, p, Side::Left, out);
-            out.push(' ');
-            out.push_str(op.symbol());
-            out.push(' ');
-            fmt_expr(r, p, Side::Right, out);
+            fmt_expr(operand, 0, Side::Top, style, out);
             if needs {
                 out.push(')');
             }
         }
-        Expr::UnaryOp(op, operand) => {
-            out.push_str(op.symbol());
-            let needs = matches!(**operand, Expr::BinOp(_, _, _) | Expr::Forall(_, _, _));
+        Expr::Forall(vars, domain, body) => {
+            let needs = parent > 0;
             if needs {
                 out.push('(');
             }
-            fmt_expr(operand, 0, Side::Top, out);
+            out.push_str(if ascii { "forall " } else { "∀ " });
+            out.push_str(&vars.join(", "));
+            out.push_str(in_kw);
+            fmt_expr(domain, 0, Side::Top, style, out);
+            out.push_str(". ");
+            fmt_expr(body, 0, Side::Top, style, out);
             if needs {
                 out.push(')');
             }
         }
-        Expr::Forall(vars, domain, body) => {
+        Expr::Let(name, ty, value, body) => {
             let needs = parent > 0;
             if needs {
                 out.push('(');
             }
-            out.push_str("∀ ");
-            out.push_str(&vars.join(", "));
-            out.push_str(" ∈ ");
-            fmt_expr(domain, 0, Side::Top, out);
-            out.push_str(". ");
-            fmt_expr(body, 0, Side::Top, out);
+            out.push_str("let ");
+            out.push_str(name);
+            if let Some(t) = ty {
+                out.push_str(" : ");
+                fmt_expr(t, 0, Side::Top, style, out);
+            }
+            out.push_str(" = ");
+            // In ASCII, a bare `in` would end the value early, so anything
+            // binding as loosely as membership is parenthesized.
+            let value_parent = if ascii { 41 } else { 0 };
+            fmt_expr(value, value_parent, Side::Top, style, out);
+            out.push_str(" in ");
+            fmt_expr(body, 0, Side::Top, style, out);
+            if needs {
+                out.push(')');
+            }
+        }
+        Expr::If(c, a, b) => {
+            let needs = parent > 0;
+            if needs {
+                out.push('(');
+            }
+            out.push_str("if ");
+            fmt_expr(c, 0, Side::Top, style, out);
+            out.push_str(" then ");
+            fmt_expr(a, 0, Side::Top, style, out);
+            out.push_str(" else ");
+            fmt_expr(b, 0, Side::Top, style, out);
+            if needs {
+                out.push(')');
+            }
+        }
+        Expr::BigOp(op, var, lo, hi, body) => {
+            let needs = parent > 0;
+            if needs {
+                out.push('(');
+            }
+            out.push_str(if ascii { op.ascii_symbol() } else { op.symbol() });
+            out.push(' ');
+            // A nested sum reads better in parentheses, though it needs none.
+            let nested = matches!(**body, Expr::BigOp(..));
+            fmt_expr(body, u8::from(nested), Side::Top, style, out);
+            out.push_str(" for ");
+            out.push_str(var);
+            // The bounds are parsed above comparisons, like a `let` type.
+            out.push_str(" from ");
+            fmt_expr(lo, 41, Side::Top, style, out);
+            out.push_str(" to ");
+            fmt_expr(hi, 41, Side::Top, style, out);
             if needs {
                 out.push(')');
             }
@@ -108,24 +330,68 @@ fn fmt_expr(e: &Expr, parent: u8, side: Side, out: &mut String) {
         Expr::SetBuilder(var, domain, pred) => {
             out.push('{');
             out.push_str(var);
-            out.push_str(" ∈ ");
-            fmt_expr(domain, 0, Side::Top, out);
+            out.push_str(in_kw);
+            fmt_expr(domain, 0, Side::Top, style, out);
             out.push_str(" | ");
-            fmt_expr(pred, 0, Side::Top, out);
+            fmt_expr(pred, 0, Side::Top, style, out);
             out.push('}');
         }
     }
 }
 
-fn wrong_side(op: Op, side: Side) -> bool {
-    match (op, side) {
+#[allow(clippy::too_many_arguments)]
+fn fmt_infix(
+    symbol: &str,
+    p: u8,
+    assoc: Assoc,
+    l: &Expr,
+    r: &Expr,
+    parent: u8,
+    side: Side,
+    style: Style,
+    out: &mut String,
+) {
+    let needs = p < parent || (p == parent && wrong_side(assoc, side));
+    if needs {
+        out.push('(');
+    }
+    fmt_expr(l, p, Side::Left, style, out);
+    out.push(' ');
+    out.push_str(symbol);
+    out.push(' ');
+    fmt_expr(r, p, Side::Right, style, out);
+    if needs {
+        out.push(')');
+    }
+}
+
+/// Whether `e` prints with a leading minus sign.
+fn is_negative(e: &Expr) -> bool {
+    match e {
+        Expr::Int(n) => n.is_negative(),
+        Expr::Decimal(r) => r.is_negative(),
+        Expr::UnaryOp(UnaryOp::Neg, _) => true,
+        _ => false,
+    }
+}
+
+/// `n` when `l · r` is `n · ℤ`, printed `nℤ`.
+fn multiples<'a>(l: &'a Expr, r: &Expr) -> Option<&'a BigInt> {
+    match (l, r) {
+        (Expr::Int(n), Expr::Ident(z)) if z == "ℤ" => Some(n),
+        _ => None,
+    }
+}
+
+fn wrong_side(assoc: Assoc, side: Side) -> bool {
+    match (assoc, side) {
         (_, Side::Top) => false,
         // Right-associative: left operand at same prec needs parens
-        (Op::Pow | Op::Implies | Op::Arrow, Side::Left) => true,
-        (Op::Pow | Op::Implies | Op::Arrow, Side::Right) => false,
+        (Assoc::Right, Side::Left) => true,
+        (Assoc::Right, Side::Right) => false,
         // Non-associative: both sides need parens at same level
-        (Op::Eq | Op::Ne | Op::Subset | Op::In | Op::Lt | Op::Gt | Op::Le | Op::Ge, _) => true,
-        (_, Side::Right) => true,
-        (_, Side::Left) => false,
+        (Assoc::None, _) => true,
+        (Assoc::Left, Side::Right) => true,
+        (Assoc::Left, Side::Left) => false,
     }
 }
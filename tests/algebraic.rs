@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+
+use num_rational::BigRational;
+use regler::kernel::algebraic::Algebraic;
+use regler::kernel::eval::evaluate;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::simplify;
+use regler::kernel::term::Term;
+use regler::kernel::theory::Theory;
+use regler::parser::parse_expr;
+use regler::printer::print_expr;
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn eval_str(src: &str) -> String {
+    show(&evaluate(&lower_str(src)).expect("evaluate"))
+}
+
+fn alg(src: &str) -> Algebraic {
+    Algebraic::from_term(&lower_str(src)).expect("algebraic")
+}
+
+fn theory_from_facts(facts: &[&str]) -> Theory {
+    let mut theory = Theory::new();
+    for f in facts {
+        let (prop, cond) = match f.split_once(" if ") {
+            Some((p, c)) => (p, Some(lower_str(c))),
+            None => (*f, None),
+        };
+        theory.install_fact(&lower_str(prop), None, cond.as_ref());
+    }
+    theory
+}
+
+#[test]
+fn square_roots_have_canonical_forms() {
+    assert_eq!(eval_str("sqrt(8)"), "2 · sqrt(2)");
+    assert_eq!(eval_str("sqrt(12 / 25)"), "2 / 5 · sqrt(3)");
+    assert_eq!(eval_str("sqrt(1 / 2)"), "1 / 2 · sqrt(2)");
+    assert_eq!(eval_str("sqrt(49)"), "7");
+    assert_eq!(eval_str("sqrt(2)"), "sqrt(2)");
+    assert_eq!(eval_str("8 ^ (1 / 2)"), "2 · sqrt(2)");
+}
+
+#[test]
+fn arithmetic_stays_exact() {
+    assert_eq!(eval_str("sqrt(2) · sqrt(2)"), "2");
+    assert_eq!(eval_str("sqrt(2) · sqrt(6)"), "2 · sqrt(3)");
+    assert_eq!(eval_str("(1 + sqrt(2)) ^ 2"), "3 + 2 · sqrt(2)");
+    assert_eq!(eval_str("sqrt(8) - 2 · sqrt(2)"), "0");
+    assert_eq!(eval_str("2 - sqrt(2) · 3"), "2 - 3 · sqrt(2)");
+    assert_eq!(eval_str("sqrt(3) - sqrt(2) + 1"), "1 - sqrt(2) + sqrt(3)");
+}
+
+#[test]
+fn inverses_rationalize_the_denominator() {
+    assert_eq!(eval_str("1 / sqrt(2)"), "1 / 2 · sqrt(2)");
+    assert_eq!(eval_str("1 / (1 + sqrt(2))"), "-1 + sqrt(2)");
+    assert_eq!(
+        eval_str("1 / (sqrt(2) + sqrt(3) + sqrt(5))"),
+        "1 / 4 · sqrt(2) + 1 / 6 · sqrt(3) - 1 / 12 · sqrt(30)"
+    );
+    let x = alg("sqrt(2) + sqrt(3) + sqrt(6) - 4");
+    assert_eq!(&x * &x.recip().expect("nonzero"), alg("1"));
+    assert!(evaluate(&lower_str("1 / (sqrt(8) - 2 · sqrt(2))")).is_err());
+}
+
+#[test]
+fn nested_radicals_denest_when_they_can() {
+    assert_eq!(eval_str("sqrt(3 + 2 · sqrt(2))"), "1 + sqrt(2)");
+    assert_eq!(eval_str("sqrt(5 - 2 · sqrt(6))"), "-sqrt(2) + sqrt(3)");
+    assert_eq!(eval_str("sqrt(1 + sqrt(2))"), "sqrt(1 + sqrt(2))");
+    assert_eq!(eval_str("sqrt(-4)"), "sqrt(-4)");
+    assert_eq!(eval_str("sqrt(1 - sqrt(2))"), "sqrt(1 - sqrt(2))");
+}
+
+#[test]
+fn signs_come_from_isolating_intervals() {
+    assert_eq!(alg("sqrt(2) - 1").signum(), Ordering::Greater);
+    assert_eq!(alg("sqrt(2) + sqrt(3) - sqrt(10)").signum(), Ordering::Less);
+    // Agrees to about eight digits: 1.41421356… vs 1.41421356…
+    assert_eq!(alg("sqrt(2) - 665857 / 470832").signum(), Ordering::Less);
+    assert!(alg("sqrt(3)") > alg("sqrt(2)"));
+    let (lo, hi) = alg("sqrt(2)").enclosure(20);
+    let two = BigRational::from_integer(2.into());
+    assert!(&lo * &lo <= two && &hi * &hi >= two);
+    assert!(&hi - &lo <= BigRational::new(1.into(), (1 << 20).into()));
+}
+
+#[test]
+fn comparisons_are_decided() {
+    assert_eq!(eval_str("sqrt(2) > 1"), "True");
+    assert_eq!(eval_str("sqrt(2) + sqrt(3) < sqrt(10)"), "True");
+    assert_eq!(eval_str("sqrt(8) = 2 · sqrt(2)"), "True");
+    assert_eq!(eval_str("sqrt(2) ≥ 3 / 2"), "False");
+    assert_eq!(eval_str("x < sqrt(2)"), "x < sqrt(2)");
+}
+
+#[test]
+fn conditions_on_radicals_are_discharged() {
+    let theory = theory_from_facts(&["f(x) = 1 if x > 1", "g(x) = 0 if x < 0"]);
+    let simp = |src: &str| show(&simplify(&lower_str(src), &theory));
+    assert_eq!(simp("f(sqrt(2))"), "1");
+    assert_eq!(simp("f(sqrt(2) - 1)"), "f(-1 + sqrt(2))");
+    assert_eq!(simp("g(sqrt(2) - sqrt(3))"), "0");
+    assert_eq!(simp("if sqrt(2) > 1 then a else b"), "a");
+}
+
+#[test]
+fn ring_normal_form_multiplies_roots_out() {
+    let theory = theory_from_facts(&[
+        "a + b = b + a",
+        "(a + b) + c = a + (b + c)",
+        "x + 0 = x",
+        "a · b = b · a",
+        "(a · b) · c = a · (b · c)",
+        "x · 1 = x",
+    ]);
+    let simp = |src: &str| show(&simplify(&lower_str(src), &theory));
+    assert_eq!(simp("(x + sqrt(2)) · sqrt(2)"), "sqrt(2) · x + 2");
+    assert_eq!(simp("sqrt(2) · y · sqrt(2)"), "y · 2");
+    assert_eq!(simp("sqrt(2) · sqrt(3) · y"), "sqrt(6) · y");
+}
@@ -0,0 +1,125 @@
+use num_rational::BigRational;
+use regler::ast::Command;
+use regler::kernel::approx::{approx, enclose, DEFAULT_DIGITS};
+use regler::kernel::eval::evaluate;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::simplify;
+use regler::kernel::term::Term;
+use regler::kernel::theory::Theory;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn approx_str(src: &str, digits: u32) -> String {
+    approx(&lower_str(src), digits).expect("approx")
+}
+
+fn approx_err(src: &str) -> String {
+    approx(&lower_str(src), DEFAULT_DIGITS).expect_err(src).0
+}
+
+#[test]
+fn rationals_print_as_rounded_decimals() {
+    assert_eq!(approx_str("1 / 7", DEFAULT_DIGITS), "0.1428571429");
+    assert_eq!(approx_str("1 / 7", 3), "0.143");
+    assert_eq!(approx_str("-1 / 3", 5), "-0.33333");
+    assert_eq!(approx_str("1 / 8", 2), "0.13");
+    assert_eq!(approx_str("5 / 2", 0), "3");
+    assert_eq!(approx_str("2 ^ 10", 1), "1024.0");
+}
+
+#[test]
+fn constants_and_functions() {
+    assert_eq!(
+        approx_str("π", 40),
+        "3.1415926535897932384626433832795028841972"
+    );
+    assert_eq!(approx_str("exp(1)", 30), "2.718281828459045235360287471353");
+    assert_eq!(approx_str("log(2)", 20), "0.69314718055994530942");
+    assert_eq!(approx_str("sqrt(2)", 20), "1.41421356237309504880");
+    assert_eq!(approx_str("sin(1)", 20), "0.84147098480789650665");
+    assert_eq!(approx_str("cos(1)", 20), "0.54030230586813971740");
+}
+
+#[test]
+fn sums_of_transcendentals() {
+    assert_eq!(approx_str("sqrt(2) + log(3)", 20), "2.51282585104120474020");
+    assert_eq!(approx_str("π ^ π", 15), "36.462159607207912");
+    assert_eq!(approx_str("2 ^ (1 / 3)", 15), "1.259921049894873");
+    assert_eq!(approx_str("(-8) ^ (1 / 3)", 3), "-2.000");
+    assert_eq!(
+        approx_str("exp(-50)", 30),
+        "0.000000000000000000000192874985"
+    );
+}
+
+#[test]
+fn large_arguments_are_reduced() {
+    assert_eq!(approx_str("log(10 ^ 100)", 20), "230.25850929940456840180");
+    assert_eq!(approx_str("sin(10 ^ 30)", 20), "-0.09011690191213805803");
+    assert_eq!(approx_str("cos(π)", 5), "-1.00000");
+}
+
+#[test]
+fn exact_zeros_print_without_a_sign() {
+    assert_eq!(approx_str("sin(π)", 10), "0.0000000000");
+    assert_eq!(approx_str("sqrt(2) · sqrt(2) - 2", 10), "0.0000000000");
+}
+
+#[test]
+fn terms_without_a_real_value_are_errors() {
+    assert_eq!(approx_err("x + 1"), "`x` has no value");
+    assert_eq!(approx_err("f(2)"), "no numeric value for `f`");
+    assert_eq!(approx_err("1 / (1 - 1)"), "division by zero");
+    assert_eq!(approx_err("log(0)"), "logarithm of a non-positive number");
+    assert_eq!(approx_err("sqrt(-1)"), "square root of a negative number");
+    assert_eq!(approx_err("i"), "complex numbers have no decimal value");
+}
+
+#[test]
+fn enclosures_contain_the_value() {
+    let iv = enclose(&lower_str("π"), 64).expect("enclose");
+    let lo = BigRational::new(314159265358979i64.into(), 100000000000000i64.into());
+    let hi = BigRational::new(314159265358980i64.into(), 100000000000000i64.into());
+    assert!(lo < iv.lo && iv.hi < hi);
+    assert!(iv.width() < BigRational::new(1.into(), (1u64 << 60).into()));
+}
+
+#[test]
+fn closed_terms_compare_by_enclosure() {
+    let eval = |s: &str| show(&evaluate(&lower_str(s)).expect("evaluate"));
+    assert_eq!(eval("sin(1) < 1"), "True");
+    assert_eq!(eval("log(3) > 1"), "True");
+    assert_eq!(eval("exp(1) = 3"), "False");
+    assert_eq!(eval("π ≠ 22 / 7"), "True");
+    // Equal values are never decided numerically.
+    assert_eq!(eval("sin(π) = 0"), "sin(π) = 0");
+
+    let mut theory = Theory::new();
+    theory.install_fact(&lower_str("f(x) = 1"), None, Some(&lower_str("x > 1")));
+    assert_eq!(show(&simplify(&lower_str("f(log(3))"), &theory)), "1");
+    assert_eq!(
+        show(&simplify(&lower_str("f(log(2))"), &theory)),
+        "f(log(2))"
+    );
+}
+
+#[test]
+fn approx_command_round_trips() {
+    for src in ["approx 1 / 7", "approx sqrt(2) + log(3) 20"] {
+        let cmd = parse_command(src).expect("parse").expect("command");
+        assert_eq!(print_command(&cmd), src);
+    }
+    assert!(matches!(
+        parse_command("approx π 50").expect("parse"),
+        Some(Command::Approx(_, Some(50)))
+    ));
+}
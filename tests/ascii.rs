@@ -0,0 +1,191 @@
+use regler::ast::{Expr, Op};
+use regler::lexer::{tokenize, Token};
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_command_ascii, print_expr, print_expr_ascii};
+
+/// Both spellings must parse to the same AST.
+fn same(ascii: &str, unicode: &str) {
+    let a = parse_expr(ascii).expect("parse ascii");
+    let u = parse_expr(unicode).expect("parse unicode");
+    assert_eq!(a, u, "{ascii:?} and {unicode:?} differ");
+}
+
+/// Round-trip through both printers, and check the ASCII printer's output.
+fn rt_both(src: &str) {
+    let e1 = parse_expr(src).expect("first parse");
+    let uni = print_expr(&e1);
+    assert_eq!(parse_expr(&uni).expect("reparse unicode"), e1, "printed = {uni:?}");
+    let asc = print_expr_ascii(&e1);
+    assert!(asc.is_ascii(), "non-ASCII output: {asc:?}");
+    assert_eq!(parse_expr(&asc).expect("reparse ascii"), e1, "printed = {asc:?}");
+}
+
+fn rt_cmd_both(src: &str) {
+    let c1 = parse_command(src).expect("parse").expect("command");
+    let uni = print_command(&c1);
+    assert_eq!(parse_command(&uni).unwrap().unwrap(), c1, "printed = {uni:?}");
+    let asc = print_command_ascii(&c1);
+    assert!(asc.is_ascii(), "non-ASCII output: {asc:?}");
+    assert_eq!(parse_command(&asc).unwrap().unwrap(), c1, "printed = {asc:?}");
+}
+
+// ── Lexing ───────────────────────────────────────────────────────────────────
+
+#[test]
+fn ascii_operators_lex_to_unicode_tokens() {
+    let pairs = [
+        ("*", Token::Dot),
+        ("<=", Token::Le),
+        (">=", Token::Ge),
+        ("!=", Token::NotEquals),
+        ("->", Token::Arrow),
+        ("=>", Token::Implies),
+        ("/\\", Token::And),
+        ("\\/", Token::Or),
+        ("forall", Token::ForAll),
+        ("subset", Token::Subset),
+    ];
+    for (src, tok) in pairs {
+        assert_eq!(tokenize(src).unwrap(), vec![tok], "lexing {src:?}");
+    }
+}
+
+#[test]
+fn single_characters_keep_their_meaning() {
+    assert_eq!(tokenize("a < b").unwrap()[1], Token::Lt);
+    assert_eq!(tokenize("a - b").unwrap()[1], Token::Minus);
+    assert_eq!(tokenize("a / b").unwrap()[1], Token::Slash);
+    assert_eq!(tokenize("a = b").unwrap()[1], Token::Equals);
+    assert_eq!(tokenize("a < -b").unwrap()[1], Token::Lt);
+}
+
+#[test]
+fn left_arrow_only_after_apply() {
+    assert_eq!(
+        tokenize("apply <- r").unwrap(),
+        vec![Token::Apply, Token::LeftArrow, Token::Ident("r".into())]
+    );
+    assert_eq!(tokenize("a<-b").unwrap()[1..3], [Token::Lt, Token::Minus]);
+    same("a<-b", "a < -b");
+}
+
+#[test]
+fn bang_equals_is_always_not_equals() {
+    // Spacing never changes the meaning: `x!=0` is `x ≠ 0`, as in a side
+    // condition `if x!=0`.
+    assert_eq!(tokenize("x!=0").unwrap()[1], Token::NotEquals);
+    same("x!=0", "x ≠ 0");
+    same("x != 0", "x ≠ 0");
+    // A factorial compared for equality needs a space or brackets.
+    same("n! = m", "factorial(n) = m");
+    same("(n!)=m", "n! = m");
+    same("n! != m", "n! ≠ m");
+}
+
+// ── Parsing both spellings ───────────────────────────────────────────────────
+
+#[test]
+fn ascii_and_unicode_parse_identically() {
+    same("a * b + c", "a · b + c");
+    same("a <= b /\\ b >= c", "a ≤ b ∧ b ≥ c");
+    same("a != b \\/ a = b", "a ≠ b ∨ a = b");
+    same("p => q => r", "p ⇒ q ⇒ r");
+    same("R -> R -> R", "R → R → R");
+    same("A subset B", "A ⊆ B");
+    same("x in S", "x ∈ S");
+    same("forall x, y in R. x * y = y * x", "∀ x, y ∈ R. x · y = y · x");
+    same("{x in R | x > 0}", "{x ∈ R | x > 0}");
+}
+
+#[test]
+fn in_keyword_ends_local_let_value() {
+    let e = parse_expr("let s = a in s in T").unwrap();
+    match e {
+        Expr::Let(_, _, value, body) => {
+            assert_eq!(*value, Expr::Ident("a".into()));
+            assert!(matches!(*body, Expr::BinOp(Op::In, _, _)));
+        }
+        _ => panic!("expected Let, got {e:?}"),
+    }
+}
+
+#[test]
+fn in_keyword_is_membership_inside_brackets() {
+    let e = parse_expr("let b = (x in S) in b").unwrap();
+    match e {
+        Expr::Let(_, _, value, _) => assert!(matches!(*value, Expr::BinOp(Op::In, _, _))),
+        _ => panic!("expected Let, got {e:?}"),
+    }
+    assert!(parse_expr("let b = f(x in S) in b").is_ok());
+}
+
+#[test]
+fn clause_words_are_names_outside_their_clauses() {
+    for word in ["for", "from", "then", "else", "assuming", "using", "by", "cases", "sum", "prod"] {
+        assert!(
+            parse_command(&format!("let {word} = 3")).is_ok(),
+            "`{word}` is reserved"
+        );
+        assert_eq!(
+            parse_expr(&format!("{word} + 1")).unwrap(),
+            Expr::BinOp(Op::Add, Box::new(Expr::Ident(word.into())), Box::new(Expr::Int(1.into())))
+        );
+    }
+    rt_both("if then then for else from");
+    assert!(matches!(parse_expr("sum(x)").unwrap(), Expr::App(..)));
+    assert!(matches!(parse_expr("sum (k + 1) for k from 1 to 3").unwrap(), Expr::BigOp(..)));
+    assert!(matches!(parse_expr("prod for for for from 1 to 3").unwrap(), Expr::BigOp(..)));
+    rt_cmd_both("simplify assuming assuming assuming = 1 by cases");
+}
+
+// ── ASCII printing ───────────────────────────────────────────────────────────
+
+#[test]
+fn ascii_printer_spellings() {
+    let e = parse_expr("∀ x ∈ S. x · y ≤ z ∧ x ≠ 0 ⇒ x ∈ T ∨ S ⊆ T").unwrap();
+    assert_eq!(
+        print_expr_ascii(&e),
+        "forall x in S. x * y <= z /\\ x != 0 => x in T \\/ S subset T"
+    );
+    let e = parse_expr("{x ∈ R | x ≥ 0}").unwrap();
+    assert_eq!(print_expr_ascii(&e), "{x in R | x >= 0}");
+}
+
+#[test]
+fn ascii_printer_protects_membership_in_let_value() {
+    let e = parse_expr("let b = x ∈ S in b").unwrap();
+    assert_eq!(print_expr_ascii(&e), "let b = (x in S) in b");
+    assert_eq!(print_expr(&e), "let b = x ∈ S in b");
+}
+
+#[test]
+fn expressions_round_trip_in_both_styles() {
+    rt_both("a * b + c");
+    rt_both("a <= b /\\ b >= c");
+    rt_both("p => q => r");
+    rt_both("(p => q) => r");
+    rt_both("a != b \\/ a = b");
+    rt_both("forall x in S. x * 1 = x");
+    rt_both("{x in R | x > 0 /\\ x < 1}");
+    rt_both("let b = (x in S) in b /\\ c");
+    rt_both("let b = (x in S /\\ y in S) in b");
+    rt_both("A subset B");
+    rt_both("F -> G -> H");
+    rt_both("a < -b");
+}
+
+#[test]
+fn commands_round_trip_in_both_styles() {
+    rt_cmd_both("fact forall a, b in Z. a * b = b * a");
+    rt_cmd_both("fact log_product : log(a * b) = log(a) + log(b) if a > 0 /\\ b > 0");
+    rt_cmd_both("apply <- distrib to a * b + a * c");
+    rt_cmd_both("let f : R -> R");
+    rt_cmd_both("let Pos : Set = {x in R | x > 0}");
+    rt_cmd_both("simplify x * 1");
+}
+
+#[test]
+fn unicode_commands_print_ascii() {
+    let c = parse_command("apply ← distrib to a · b").unwrap().unwrap();
+    assert_eq!(print_command_ascii(&c), "apply <- distrib to a * b");
+}
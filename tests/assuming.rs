@@ -0,0 +1,93 @@
+use regler::ast::Command;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::{simplify, simplify_assuming, Rule};
+use regler::kernel::term::Term;
+use regler::kernel::theory::Theory;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn simp_assuming(src: &str, assumption: &str, theory: &Theory) -> String {
+    show(&simplify_assuming(&lower_str(src), &lower_str(assumption), theory))
+}
+
+/// The algebra library's cancellation fact.
+fn cancel_theory() -> Theory {
+    let mut theory = Theory::new();
+    let cond = lower_str("x ≠ 0");
+    theory.install_fact(&lower_str("x / x = 1"), None, Some(&cond));
+    theory
+}
+
+#[test]
+fn simplify_assuming_parses_and_round_trips() {
+    let cmd = parse_command("simplify x / x assuming x ≠ 0 ∧ y > 1").unwrap().unwrap();
+    match &cmd {
+        Command::Simplify(_, Some(c), false) => assert_eq!(print_expr(c), "x ≠ 0 ∧ y > 1"),
+        _ => panic!("expected Simplify with assumption, got {cmd:?}"),
+    }
+    assert_eq!(print_command(&cmd), "simplify x / x assuming x ≠ 0 ∧ y > 1");
+    let plain = parse_command("simplify x / x").unwrap().unwrap();
+    assert_eq!(plain, Command::Simplify(parse_expr("x / x").unwrap(), None, false));
+}
+
+#[test]
+fn hypothesis_discharges_rule_condition() {
+    let theory = cancel_theory();
+    assert_eq!(show(&simplify(&lower_str("y / y"), &theory)), "y / y");
+    assert_eq!(simp_assuming("y / y", "y ≠ 0", &theory), "1");
+    assert_eq!(simp_assuming("y / y", "0 ≠ y", &theory), "1");
+    assert_eq!(simp_assuming("y / y", "¬(y = 0)", &theory), "1");
+    assert_eq!(simp_assuming("y / y", "z ≠ 0", &theory), "y / y");
+}
+
+#[test]
+fn literal_bounds_settle_comparisons() {
+    let theory = cancel_theory();
+    assert_eq!(simp_assuming("y / y", "y > 2", &theory), "1");
+    assert_eq!(simp_assuming("y / y", "y ≥ 0", &theory), "y / y");
+    assert_eq!(simp_assuming("y < 1", "y > 2", &theory), "False");
+    assert_eq!(simp_assuming("1 ≤ y", "y > 2", &theory), "True");
+    assert_eq!(simp_assuming("y ≤ 5", "y < 5", &theory), "True");
+    assert_eq!(simp_assuming("y = 5", "y < 5", &theory), "False");
+    assert_eq!(simp_assuming("y > 4", "y < 5", &theory), "y > 4");
+}
+
+#[test]
+fn hypotheses_decide_propositions() {
+    let theory = Theory::new();
+    assert_eq!(simp_assuming("p ∧ q", "p", &theory), "q");
+    assert_eq!(simp_assuming("p ∨ q", "¬q", &theory), "p");
+    assert_eq!(simp_assuming("a = b ⇒ c", "b = a", &theory), "c");
+}
+
+#[test]
+fn variable_equations_are_substituted() {
+    let theory = Theory::new();
+    assert_eq!(simp_assuming("y + 1", "y = 3", &theory), "4");
+    assert_eq!(simp_assuming("y + 1", "y = y + 0", &theory), "y + 1");
+}
+
+#[test]
+fn fired_rule_condition_is_assumed_in_its_result() {
+    let mut theory = cancel_theory();
+    let cond = lower_str("x > 0");
+    theory.install_fact(&lower_str("exp(x) > 0"), None, None);
+    theory.rules.push(Rule {
+        lhs: lower_str("f(x)"),
+        rhs: lower_str("x / x"),
+        condition: Some(cond),
+    });
+    // `exp(a) > 0` is implied, but only the hypothesis it leaves behind
+    // settles `exp(a) ≠ 0` for the cancellation.
+    assert_eq!(show(&simplify(&lower_str("f(exp(a))"), &theory)), "1");
+    assert_eq!(show(&simplify(&lower_str("f(a)"), &theory)), "f(a)");
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use regler::ast::{BigOp, Expr};
+use regler::kernel::approx::approx;
+use regler::kernel::check::{check_fact, CheckOutcome, TRIALS};
+use regler::kernel::eval::evaluate;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::{apply_eq, normalize, simplify};
+use regler::kernel::subst::subst;
+use regler::kernel::term::{sym, Term};
+use regler::kernel::theory::Theory;
+use regler::parser::parse_expr;
+use regler::printer::{print_expr, print_expr_ascii};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn eval_str(src: &str) -> String {
+    show(&evaluate(&lower_str(src)).expect("evaluate"))
+}
+
+/// Apply the equation `rule` once to `target`, or `None` if it does not match.
+fn apply(rule: &str, target: &str) -> Option<String> {
+    let (lhs, rhs) = match lower_str(rule) {
+        Term::App(_, args) => (args[0].clone(), args[1].clone()),
+        _ => panic!("not an equation"),
+    };
+    apply_eq(&lhs, &rhs, &lower_str(target)).map(|t| show(&t))
+}
+
+fn rt_expr(src: &str) {
+    let e1 = parse_expr(src).expect("first parse");
+    let printed = print_expr(&e1);
+    let e2 = parse_expr(&printed).expect("reparse");
+    assert_eq!(e1, e2, "round-trip failed; printed = {printed:?}");
+}
+
+#[test]
+fn sums_parse_with_bounds_below_comparisons() {
+    let k = || Box::new(Expr::Ident("k".into()));
+    assert_eq!(
+        parse_expr("∑ k for k from 1 to n").expect("parse"),
+        Expr::BigOp(
+            BigOp::Sum,
+            "k".into(),
+            Box::new(Expr::Int(1.into())),
+            Box::new(Expr::Ident("n".into())),
+            k()
+        )
+    );
+    assert_eq!(
+        parse_expr("prod k for k from 1 to n").expect("parse"),
+        parse_expr("∏ k for k from 1 to n").expect("parse")
+    );
+    // The upper bound stops before `=`, so this is an equation.
+    assert!(matches!(
+        parse_expr("∑ k for k from 1 to n + 1 = m").expect("parse"),
+        Expr::BinOp(_, _, _)
+    ));
+    assert!(parse_expr("∑ k for k to n").is_err());
+    assert!(parse_expr("∑ k from 1 to n").is_err());
+}
+
+#[test]
+fn sums_round_trip() {
+    for src in [
+        "∑ k ^ 2 for k from 1 to n",
+        "(∑ k for k from 1 to n) = n · (n + 1) / 2",
+        "-(∏ k for k from 1 to n) + 1",
+        "∑ (∑ j for j from 1 to k) for k from 0 to n - 1",
+        "∑ k for k from (if a then 1 else 0) to n",
+        "2 · (∑ f(k) for k from a to b)",
+    ] {
+        rt_expr(src);
+    }
+    let e = parse_expr("∏ k for k from 1 to n").expect("parse");
+    assert_eq!(print_expr_ascii(&e), "prod k for k from 1 to n");
+}
+
+#[test]
+fn evaluate_expands_literal_ranges() {
+    assert_eq!(eval_str("∑ k for k from 1 to 100"), "5050");
+    assert_eq!(eval_str("∏ k for k from 1 to 10"), "3628800");
+    assert_eq!(eval_str("∑ 1 / k for k from 1 to 4"), "25 / 12");
+    assert_eq!(eval_str("∑ k for k from 3 to 3"), "3");
+    assert_eq!(eval_str("∑ k · x for k from 1 to 0"), "0");
+    assert_eq!(eval_str("∏ k for k from 5 to 1"), "1");
+    assert_eq!(eval_str("∑ k for k from 2 - 3 to 1 + 1"), "2");
+    assert_eq!(
+        eval_str("∑ (∑ j for j from 1 to k) for k from 1 to 4"),
+        "20"
+    );
+    // Symbolic bounds are left alone.
+    assert_eq!(
+        eval_str("∑ k ^ 2 for k from 1 to n"),
+        "∑ k ^ 2 for k from 1 to n"
+    );
+    assert!(evaluate(&lower_str("∑ k for k from 1 to 10 ^ 6"))
+        .expect_err("too long")
+        .0
+        .contains("too many"));
+}
+
+#[test]
+fn bound_variables_are_not_substituted() {
+    assert_eq!(eval_str("let k = 5 in ∑ k for k from 1 to 3"), "6");
+    assert_eq!(eval_str("let n = 4 in ∑ k for k from 1 to n"), "10");
+    assert_eq!(eval_str("let n = 4 in ∑ n for k from 1 to n"), "16");
+}
+
+#[test]
+fn bound_variables_are_renamed_apart() {
+    // `let m = k` at the prompt binds `m`; the sum's `k` must not capture it.
+    let norm = |t: &Term| show(&normalize(t, &Theory::new()));
+    let bind = |x: &str, t: &str| HashMap::from([(sym(x), lower_str(t))]);
+    let sum = subst(&lower_str("∑ m for k from 1 to 3"), &bind("m", "k"));
+    assert_eq!(show(&sum), "∑ k for k1 from 1 to 3");
+    assert_eq!(norm(&sum), "k · 3");
+    let sum = subst(&lower_str("∑ q · x for q from 1 to 3"), &bind("q", "x"));
+    assert_eq!(norm(&sum), "x · 6");
+    let sum = subst(&lower_str("∑ m · k1 for k from 1 to n"), &bind("m", "k"));
+    assert_eq!(show(&sum), "∑ k · k1 for k2 from 1 to n");
+    // The same for a local `let`.
+    let norm_str = |s: &str| norm(&lower_str(s));
+    assert_eq!(norm_str("let m = k in ∑ m for k from 1 to 3"), "k · 3");
+    assert_eq!(norm_str("let q = x in ∑ q · x for q from 1 to 3"), "x · 6");
+}
+
+#[test]
+fn simplify_expands_and_takes_single_terms() {
+    let theory = Theory::new();
+    let simp = |s: &str| show(&simplify(&lower_str(s), &theory));
+    assert_eq!(simp("∑ f(k) for k from 1 to 3"), "f(1) + f(2) + f(3)");
+    assert_eq!(simp("∑ f(k) for k from n to n"), "f(n)");
+    assert_eq!(simp("∑ 2 · k for k from 1 to 3 = 12"), "True");
+    assert_eq!(simp("∑ f(k) for k from 1 to n"), "∑ f(k) for k from 1 to n");
+}
+
+#[test]
+fn rules_match_whatever_the_index_is_called() {
+    let gauss = "∑ k for k from 1 to n = n · (n + 1) / 2";
+    assert_eq!(
+        apply(gauss, "∑ j for j from 1 to m").as_deref(),
+        Some("m · (m + 1) / 2")
+    );
+    assert_eq!(apply(gauss, "∑ j ^ 2 for j from 1 to m"), None);
+}
+
+#[test]
+fn splitting_off_the_last_term() {
+    let split = "∑ e for k from a to b = (∑ e for k from a to b - 1) + ∑ e for k from b to b";
+    assert_eq!(
+        apply(split, "∑ j ^ 2 for j from 1 to n").as_deref(),
+        Some("(∑ j ^ 2 for j from 1 to n - 1) + n ^ 2")
+    );
+}
+
+#[test]
+fn shifting_the_index() {
+    let shift =
+        "∑ e for k from a + 1 to b + 1 = ∑ (∑ e for k from k + 1 to k + 1) for k from a to b";
+    assert_eq!(
+        apply(shift, "∑ f(m) · m for m from 0 + 1 to n + 1").as_deref(),
+        Some("∑ f(m + 1) · (m + 1) for m from 0 to n")
+    );
+}
+
+#[test]
+fn matched_terms_do_not_escape_the_binder() {
+    let pull = "∑ c · e for k from a to b = c · ∑ e for k from a to b";
+    assert_eq!(
+        apply(pull, "∑ 3 · f(k) for k from 1 to n").as_deref(),
+        Some("3 · (∑ f(k) for k from 1 to n)")
+    );
+    // `c` would be `k`, which is only meaningful under the sum.
+    assert_eq!(apply(pull, "∑ k · f(k) for k from 1 to n"), None);
+}
+
+#[test]
+fn closed_forms_can_be_checked() {
+    let theory = Theory::new();
+    let over_n = [(sym("n"), Term::Var(sym("ℕ")))];
+    let check = |s: &str| check_fact(&lower_str(s), None, &over_n, &theory);
+    assert!(matches!(
+        check("∑ k for k from 1 to n = n · (n + 1) / 2"),
+        CheckOutcome::Passed(n) if n == TRIALS
+    ));
+    match check("∑ k ^ 2 for k from 1 to n = n ^ 2 · (n + 1) / 2") {
+        CheckOutcome::Counterexample(instance, _) => {
+            assert_eq!(instance.len(), 1, "only `n` is drawn");
+        }
+        other => panic!("expected a counterexample, got {other:?}"),
+    }
+}
+
+#[test]
+fn approx_adds_term_by_term() {
+    let a = |s: &str| approx(&lower_str(s), 10).expect("approx");
+    assert_eq!(a("∑ 1 / k ^ 2 for k from 1 to 1000"), "1.6439345667");
+    assert_eq!(a("∏ (1 + 1 / k) for k from 1 to 9"), "10.0000000000");
+    assert!(approx(&lower_str("∑ k for k from 1 to n"), 10).is_err());
+}
@@ -0,0 +1,142 @@
+use regler::ast::{Expr, UnaryOp};
+use regler::kernel::eval::evaluate;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::{apply_eq_conditional, simplify};
+use regler::kernel::term::{as_truth, sym, truth, Term};
+use regler::kernel::theory::Theory;
+use regler::parser::parse_expr;
+use regler::printer::{print_expr, print_expr_ascii};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn simp(src: &str, theory: &Theory) -> String {
+    print_expr(&to_surface(&simplify(&lower_str(src), theory)).expect("to_surface"))
+}
+
+fn pos_theory() -> Theory {
+    let mut theory = Theory::new();
+    theory.add_predicate_set(sym("Pos"), sym("x"), Term::Var(sym("ℝ")), lower_str("x > 0"));
+    theory
+}
+
+// ── Surface syntax ───────────────────────────────────────────────────────────
+
+#[test]
+fn truth_constants_and_negation_parse() {
+    assert_eq!(parse_expr("True").unwrap(), Expr::Bool(true));
+    let e = parse_expr("¬p").unwrap();
+    assert_eq!(e, Expr::UnaryOp(UnaryOp::Not, Box::new(Expr::Ident("p".into()))));
+    assert_eq!(parse_expr("~p").unwrap(), e);
+}
+
+#[test]
+fn negation_binds_tighter_than_relations() {
+    let e = parse_expr("¬a = b").unwrap();
+    assert_eq!(print_expr(&e), "¬a = b");
+    assert!(matches!(e, Expr::BinOp(_, ref l, _) if matches!(**l, Expr::UnaryOp(_, _))));
+    let e = parse_expr("¬(a = b) ∧ False").unwrap();
+    assert_eq!(print_expr(&e), "¬(a = b) ∧ False");
+    assert_eq!(print_expr_ascii(&e), "~(a = b) /\\ False");
+}
+
+#[test]
+fn truth_constants_lower_and_lift() {
+    assert_eq!(lower_str("True"), truth(true));
+    assert_eq!(as_truth(&lower_str("False")), Some(false));
+    let t = lower_str("¬p ⇒ True");
+    assert_eq!(print_expr(&to_surface(&t).unwrap()), "¬p ⇒ True");
+}
+
+// ── Simplifier ───────────────────────────────────────────────────────────────
+
+#[test]
+fn closed_comparisons_reduce_to_truth_constants() {
+    let th = Theory::new();
+    assert_eq!(simp("2 < 3", &th), "True");
+    assert_eq!(simp("1 + 1 = 3", &th), "False");
+    assert_eq!(simp("1/2 ≥ 1/3", &th), "True");
+    assert_eq!(simp("x = x", &th), "True");
+    assert_eq!(simp("x < x", &th), "False");
+    assert_eq!(simp("x = y", &th), "x = y");
+}
+
+#[test]
+fn undefined_sides_are_not_compared() {
+    let th = Theory::new();
+    assert_eq!(simp("1 / 0 = 1 / 0", &th), "1 / 0 = 1 / 0");
+    assert_eq!(simp("x / 0 ≠ x / 0", &th), "x / 0 ≠ x / 0");
+    assert_eq!(simp("0 ^ -1 ≤ 0 ^ -1", &th), "0 ^ -1 ≤ 0 ^ -1");
+    assert_eq!(simp("x / x = x / x", &th), "x / x = x / x");
+    assert_eq!(simp("x ^ -1 ≥ x ^ -1", &th), "x ^ -1 ≥ x ^ -1");
+    assert_eq!(simp("f(1 / 2) = f(1 / 2)", &th), "True");
+    assert_eq!(simp("x / 2 = x / 2", &th), "True");
+    assert_eq!(simp("x ^ 2 ≤ x ^ 2", &th), "True");
+}
+
+#[test]
+fn constants_are_absorbed() {
+    let th = Theory::new();
+    assert_eq!(simp("p ∧ True", &th), "p");
+    assert_eq!(simp("p ∧ False", &th), "False");
+    assert_eq!(simp("p ∨ 1 = 1", &th), "True");
+    assert_eq!(simp("True ⇒ q", &th), "q");
+    assert_eq!(simp("p ⇒ False", &th), "¬p");
+    assert_eq!(simp("¬¬p", &th), "p");
+}
+
+#[test]
+fn de_morgan_pushes_negation_inward() {
+    let th = Theory::new();
+    assert_eq!(simp("¬(a ∧ b)", &th), "¬a ∨ ¬b");
+    assert_eq!(simp("¬(a ∨ b ∨ c)", &th), "¬a ∧ ¬b ∧ ¬c");
+    assert_eq!(simp("¬(x = y)", &th), "x ≠ y");
+    assert_eq!(simp("¬(p ⇒ q)", &th), "p ∧ ¬q");
+}
+
+#[test]
+fn absorption_idempotence_and_complement() {
+    let th = Theory::new();
+    assert_eq!(simp("a ∧ (a ∨ b)", &th), "a");
+    assert_eq!(simp("a ∨ a ∧ b", &th), "a");
+    assert_eq!(simp("a ∧ b ∧ a", &th), "a ∧ b");
+    assert_eq!(simp("p ∧ ¬p", &th), "False");
+    assert_eq!(simp("x = y ∨ x ≠ y", &th), "True");
+    assert_eq!(simp("a ∧ b ⇒ a", &th), "True");
+}
+
+#[test]
+fn membership_in_number_and_predicate_sets() {
+    let th = pos_theory();
+    assert_eq!(simp("3 ∈ ℕ", &th), "True");
+    assert_eq!(simp("-3 ∈ ℕ", &th), "False");
+    assert_eq!(simp("1/2 ∈ ℤ", &th), "False");
+    assert_eq!(simp("1/2 ∈ Pos", &th), "True");
+    assert_eq!(simp("-1 ∈ Pos", &th), "False");
+    // Undecided memberships are left alone rather than unfolded.
+    assert_eq!(simp("a ∈ Pos", &th), "a ∈ Pos");
+}
+
+// ── evaluate and conditions ──────────────────────────────────────────────────
+
+#[test]
+fn evaluate_folds_closed_propositions_only() {
+    let eval = |src: &str| print_expr(&to_surface(&evaluate(&lower_str(src)).unwrap()).unwrap());
+    assert_eq!(eval("2 < 3"), "True");
+    assert_eq!(eval("¬(1 = 2) ∧ 3 ≤ 2"), "False");
+    assert_eq!(eval("p ∧ True"), "p ∧ True");
+}
+
+#[test]
+fn conditions_use_negation_and_implication() {
+    let th = pos_theory();
+    let (lhs, rhs) = (lower_str("f(x)"), lower_str("g(x)"));
+    let cond = lower_str("¬(x ∈ Pos) ⇒ x = 0");
+    let fire = |target: &str| apply_eq_conditional(&lhs, &rhs, Some(&cond), &lower_str(target), &th);
+    assert!(fire("f(2)").is_some());
+    assert!(fire("f(0)").is_some());
+    assert!(fire("f(-1)").is_none());
+    assert!(fire("f(a)").is_none());
+}
@@ -0,0 +1,86 @@
+use regler::ast::Command;
+use regler::kernel::cases::simplify_by_cases;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::term::{as_cond, cond, Term};
+use regler::kernel::theory::Theory;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn theory_from(facts: &[(&str, &str)]) -> Theory {
+    let mut theory = Theory::new();
+    for (fact, condition) in facts {
+        let c = lower_str(condition);
+        theory.install_fact(&lower_str(fact), None, Some(&c));
+    }
+    theory
+}
+
+fn by_cases(src: &str, assumption: Option<&str>, theory: &Theory) -> String {
+    let a = assumption.map(lower_str);
+    show(&simplify_by_cases(&lower_str(src), a.as_ref(), theory))
+}
+
+#[test]
+fn by_cases_clause_parses_and_round_trips() {
+    for src in [
+        "simplify log(x) + log(y) by cases",
+        "simplify x / x assuming x ≥ 0 by cases",
+        "prove x ≥ 0 ∨ x < 0 by cases",
+    ] {
+        let cmd = parse_command(src).unwrap().unwrap();
+        assert_eq!(print_command(&cmd), src);
+    }
+    let cmd = parse_command("prove p by cases").unwrap().unwrap();
+    assert!(matches!(cmd, Command::Prove(_, _, _, true)));
+    assert!(parse_command("prove p by").is_err());
+}
+
+#[test]
+fn piecewise_term_prints_as_if_then_else() {
+    let t = cond(lower_str("x > 0"), lower_str("x"), lower_str("-x"));
+    assert_eq!(show(&t), "if x > 0 then x else -x");
+    let nested = Term::App("+".into(), vec![t, lower_str("1")]);
+    assert_eq!(show(&nested), "(if x > 0 then x else -x) + 1");
+}
+
+#[test]
+fn undecided_rule_condition_gives_piecewise_result() {
+    let theory = theory_from(&[("log(a · b) = log(a) + log(b)", "a > 0 ∧ b > 0")]);
+    assert_eq!(
+        by_cases("log(x) + log(y)", None, &theory),
+        "if x > 0 ∧ y > 0 then log(x · y) else log(x) + log(y)"
+    );
+    assert_eq!(
+        by_cases("log(x) + log(y)", Some("y > 0"), &theory),
+        "if x > 0 then log(x · y) else log(x) + log(y)"
+    );
+    let t = simplify_by_cases(&lower_str("log(x) + log(y)"), None, &theory);
+    assert!(as_cond(&t).is_some());
+}
+
+#[test]
+fn decided_conditions_do_not_split() {
+    let theory = theory_from(&[("x / x = 1", "x ≠ 0")]);
+    assert_eq!(by_cases("3 / 3", None, &theory), "1");
+    assert_eq!(by_cases("y / y", Some("y > 0"), &theory), "1");
+    assert_eq!(by_cases("y + 1", None, &theory), "y + 1");
+    assert_eq!(by_cases("y / y", None, &theory), "if y ≠ 0 then 1 else y / y");
+}
+
+#[test]
+fn propositions_hold_in_every_branch() {
+    let theory = Theory::new();
+    assert_eq!(by_cases("x ≥ 0 ∨ x < 0", None, &theory), "True");
+    assert_eq!(by_cases("x ≤ 2 ∨ x > 1", None, &theory), "True");
+    assert_eq!(by_cases("x > 0 ∨ x < 0", None, &theory), "x > 0 ∨ x < 0");
+    assert_eq!(by_cases("x > 0 ∧ x < 0", None, &theory), "False");
+}
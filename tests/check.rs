@@ -0,0 +1,142 @@
+use regler::ast::Command;
+use regler::kernel::check::{check_fact, CheckOutcome, Failure, TRIALS};
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::term::{sym, Symbol, Term};
+use regler::kernel::theory::Theory;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn over(vars: &[&str], set: &str) -> Vec<(Symbol, Term)> {
+    vars.iter().map(|v| (sym(v), Term::Var(sym(set)))).collect()
+}
+
+fn check(prop: &str, cond: Option<&str>, domains: &[(Symbol, Term)]) -> CheckOutcome {
+    let mut theory = Theory::new();
+    theory.add_predicate_set(sym("Pos"), sym("x"), Term::Var(sym("ℝ")), lower_str("x > 0"));
+    let cond = cond.map(lower_str);
+    check_fact(&lower_str(prop), cond.as_ref(), domains, &theory)
+}
+
+fn passes(outcome: &CheckOutcome) -> bool {
+    matches!(outcome, CheckOutcome::Passed(n) if *n == TRIALS)
+}
+
+#[test]
+fn check_command_parses_and_prints() {
+    let cmd = parse_command("check binomial").unwrap().unwrap();
+    assert_eq!(cmd, Command::Check("binomial".into()));
+    assert_eq!(print_command(&cmd), "check binomial");
+    assert!(parse_command("check").is_err());
+    assert!(parse_expr("check + 1").is_ok());
+    assert!(matches!(parse_command("check check").unwrap(), Some(Command::Check(name)) if name == "check"));
+}
+
+#[test]
+fn wrong_binomial_has_a_small_counterexample() {
+    let domains = over(&["a", "b"], "ℝ");
+    match check("(a + b)^2 = a^2 + b^2", None, &domains) {
+        CheckOutcome::Counterexample(instance, Failure::Unequal(l, r)) => {
+            assert_eq!(instance.len(), 2);
+            assert!(instance.iter().all(|(_, t)| show(t).len() <= 6));
+            assert_ne!(l, r);
+        }
+        other => panic!("expected a counterexample, got {other:?}"),
+    }
+}
+
+#[test]
+fn true_identities_pass() {
+    let domains = over(&["a", "b"], "ℚ");
+    assert!(passes(&check("(a + b)^2 = a^2 + 2·a·b + b^2", None, &domains)));
+    assert!(passes(&check("(a - b) · (a + b) = a^2 - b^2", None, &domains)));
+    assert!(passes(&check("a / 3 + a / 6 = a / 2", None, &[])));
+}
+
+#[test]
+fn domains_restrict_the_instances() {
+    assert!(passes(&check("x ≥ 0", None, &over(&["x"], "ℕ"))));
+    let out = check("x ≥ 0", None, &over(&["x"], "ℤ"));
+    match out {
+        CheckOutcome::Counterexample(instance, Failure::False) => {
+            assert!(show(&instance[0].1).starts_with('-'));
+        }
+        other => panic!("expected a counterexample, got {other:?}"),
+    }
+    // ℤ draws integers only.
+    assert!(passes(&check("2 · (x / 2) = x", None, &over(&["x"], "ℤ"))));
+}
+
+#[test]
+fn evaluation_errors_are_counterexamples_unless_excluded() {
+    match check("x / x = 1", None, &[]) {
+        CheckOutcome::Counterexample(instance, Failure::Error(_)) => {
+            assert_eq!(show(&instance[0].1), "0");
+        }
+        other => panic!("expected a division by zero, got {other:?}"),
+    }
+    assert!(passes(&check("x / x = 1", Some("x ≠ 0"), &[])));
+    assert!(passes(&check("x ≠ 0 ⇒ x / x = 1", None, &[])));
+}
+
+#[test]
+fn conditions_go_through_predicate_sets() {
+    assert!(passes(&check("x / 2 < x", Some("x ∈ Pos"), &over(&["x"], "Pos"))));
+    assert!(matches!(
+        check("x / 2 < x", None, &over(&["x"], "ℤ")),
+        CheckOutcome::Counterexample(_, Failure::False)
+    ));
+}
+
+#[test]
+fn piecewise_facts_are_evaluated_branchwise() {
+    assert!(passes(&check("(if x ≥ 0 then x else -x) ≥ 0", None, &[])));
+    assert!(matches!(
+        check("(if x > 0 then x else 1 / x) = x", None, &[]),
+        CheckOutcome::Counterexample(_, Failure::Error(_))
+    ));
+}
+
+#[test]
+fn uninterpreted_functions_are_undecided() {
+    match check("log(a · b) = log(a) + log(b)", Some("a ∈ Pos ∧ b ∈ Pos"), &[]) {
+        CheckOutcome::Undecided(instance, residual) => {
+            assert_eq!(instance.len(), 2);
+            assert!(show(&residual).starts_with("log("));
+        }
+        other => panic!("expected an undecided instance, got {other:?}"),
+    }
+}
+
+#[test]
+fn unsatisfiable_conditions_are_vacuous() {
+    assert!(matches!(check("x = 1", Some("x > 0 ∧ x < 0"), &[]), CheckOutcome::Vacuous));
+}
+
+#[test]
+fn closed_facts_take_one_instance() {
+    assert!(matches!(check("2 + 2 = 4", None, &[]), CheckOutcome::Passed(1)));
+    match check("2 + 2 = 5", None, &[]) {
+        CheckOutcome::Counterexample(instance, Failure::Unequal(l, r)) => {
+            assert!(instance.is_empty());
+            assert_eq!((show(&l), show(&r)), ("4".into(), "5".into()));
+        }
+        other => panic!("expected a counterexample, got {other:?}"),
+    }
+}
+
+#[test]
+fn checks_are_reproducible() {
+    let domains = over(&["a", "b"], "ℝ");
+    let first = format!("{:?}", check("(a + b)^2 = a^2 + b^2", None, &domains));
+    let second = format!("{:?}", check("(a + b)^2 = a^2 + b^2", None, &domains));
+    assert_eq!(first, second);
+}
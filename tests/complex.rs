@@ -0,0 +1,171 @@
+use num_rational::BigRational;
+use regler::kernel::eval::evaluate;
+use regler::kernel::kbo::{kbo, KboOrd};
+use regler::kernel::lower::{fact_scope, lower, lower_with};
+use regler::kernel::poly::expand;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::simplify;
+use regler::kernel::term::{imaginary_unit, sym, Term};
+use regler::kernel::theory::Theory;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::print_expr;
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn eval_str(src: &str) -> String {
+    show(&evaluate(&lower_str(src)).expect("evaluate"))
+}
+
+fn ring_theory() -> Theory {
+    let mut theory = Theory::new();
+    for f in [
+        "a + b = b + a",
+        "(a + b) + c = a + (b + c)",
+        "x + 0 = x",
+        "a · b = b · a",
+        "(a · b) · c = a · (b · c)",
+        "x · 1 = x",
+    ] {
+        theory.install_fact(&lower_str(f), None, None);
+    }
+    theory
+}
+
+fn complex(re: (i64, i64), im: (i64, i64)) -> Term {
+    let rat = |(n, d): (i64, i64)| BigRational::new(n.into(), d.into());
+    Term::Complex(Box::new((rat(re), rat(im))))
+}
+
+#[test]
+fn i_lowers_to_a_complex_literal() {
+    assert_eq!(lower_str("i"), imaginary_unit());
+    assert_eq!(show(&complex((3, 1), (-2, 1))), "3 - 2 · i");
+    assert_eq!(show(&complex((1, 2), (1, 1))), "1 / 2 + i");
+    assert_eq!(show(&complex((0, 1), (-1, 1))), "-i");
+    assert_eq!(show(&complex((0, 1), (2, 3))), "2 / 3 · i");
+}
+
+#[test]
+fn bound_i_is_a_variable() {
+    assert_eq!(eval_str("∑ i for i from 1 to 3"), "6");
+    assert_eq!(eval_str("∑ i · k for k from 1 to 2"), "3 · i");
+    assert_eq!(eval_str("let i = 3 in i + 1"), "4");
+    assert_eq!(eval_str("let x = i in x · x"), "-1");
+    let i = Term::Var(sym("i"));
+    assert_eq!(
+        lower_str("∀ i ∈ ℕ. f(i) = i"),
+        Term::App(sym("="), vec![Term::App(sym("f"), vec![i.clone()]), i.clone()])
+    );
+    for src in [
+        "fact ∀ i ∈ ℕ. f(i) = i",
+        "let i = 3",
+        "let i : ℕ",
+        "evaluate ∏ i for i from 1 to 4",
+    ] {
+        assert!(parse_command(src).is_ok(), "{src}");
+    }
+    // A name declared by the caller is not the imaginary unit either.
+    let e = parse_expr("i + 1").expect("parse");
+    assert_eq!(
+        lower_with(&e, &["i".into()]).expect("lower"),
+        Term::App(sym("+"), vec![i, lower_str("1")])
+    );
+    assert_eq!(lower_with(&e, &[]).expect("lower"), lower_str("i + 1"));
+}
+
+#[test]
+fn i_in_a_fact_pattern_is_a_variable() {
+    let fact_term = |src: &str| {
+        let e = parse_expr(src).expect("parse");
+        lower_with(&e, &fact_scope(&e, &[])).expect("lower")
+    };
+    let mut theory = Theory::new();
+    theory.install_fact(&fact_term("h(i) = 0"), None, None);
+    theory.install_fact(&fact_term("r(x) = i"), None, None);
+    assert_eq!(show(&simplify(&lower_str("h(2)"), &theory)), "0");
+    assert_eq!(show(&simplify(&lower_str("r(3) · r(3)"), &theory)), "-1");
+}
+
+#[test]
+fn complex_literals_are_only_in_c() {
+    let theory = Theory::new();
+    let simp = |s: &str| show(&simplify(&lower_str(s), &theory));
+    assert_eq!(simp("i ∈ ℂ"), "True");
+    assert_eq!(simp("1 + 2 · i ∈ ℂ"), "True");
+    for set in ["ℕ", "ℤ", "ℚ", "ℝ"] {
+        assert_eq!(simp(&format!("i ∈ {set}")), "False", "{set}");
+    }
+    assert_eq!(simp("i · i ∈ ℤ"), "True");
+}
+
+#[test]
+fn i_squared_is_minus_one() {
+    assert_eq!(eval_str("i · i"), "-1");
+    assert_eq!(eval_str("i ^ 2"), "-1");
+    assert_eq!(eval_str("i ^ 3"), "-i");
+    assert_eq!(eval_str("i ^ 4"), "1");
+    assert_eq!(eval_str("(1 + i) ^ 4"), "-4");
+}
+
+#[test]
+fn gaussian_rationals_form_a_field() {
+    assert_eq!(eval_str("(1 + 2 · i) / (3 - i)"), "1 / 10 + 7 / 10 · i");
+    assert_eq!(eval_str("i ^ -1"), "-i");
+    assert_eq!(eval_str("(2 + i) · (2 - i)"), "5");
+    assert_eq!(eval_str("(3 + 4 · i) - (3 + 4 · i)"), "0");
+    let err = evaluate(&lower_str("1 / (0 · i)")).expect_err("1 / 0");
+    assert!(err.0.contains("division by zero"), "{}", err.0);
+}
+
+#[test]
+fn complex_literals_compare_only_for_equality() {
+    assert_eq!(eval_str("i = i"), "True");
+    assert_eq!(eval_str("1 + i ≠ 1"), "True");
+    assert_eq!(eval_str("i · i = -1"), "True");
+    assert_eq!(eval_str("i < 1"), "i < 1");
+}
+
+#[test]
+fn simplify_folds_i_squared() {
+    let theory = Theory::new();
+    assert_eq!(show(&simplify(&lower_str("i · i + x"), &theory)), "-1 + x");
+    assert_eq!(show(&simplify(&lower_str("(1 + i) ^ 2"), &theory)), "2 · i");
+}
+
+#[test]
+fn ring_normal_form_reduces_powers_of_i() {
+    let theory = ring_theory();
+    assert_eq!(show(&simplify(&lower_str("x · i · i"), &theory)), "-x");
+    assert_eq!(
+        show(&simplify(&lower_str("(x + i) · (x - i)"), &theory)),
+        "x ^ 2 + 1"
+    );
+    assert_eq!(
+        show(&expand(&lower_str("(x + i) ^ 3"))),
+        "x ^ 3 + x ^ 2 · i · 3 - x · 3 - i"
+    );
+}
+
+#[test]
+fn kbo_orders_complex_literals() {
+    let one = lower_str("1");
+    let i = lower_str("i");
+    assert_eq!(kbo(&i, &i), KboOrd::Eq);
+    assert_ne!(kbo(&i, &one), KboOrd::Incomparable);
+    assert_ne!(kbo(&lower_str("1 + i"), &i), KboOrd::Incomparable);
+}
+
+#[test]
+fn complex_literals_round_trip() {
+    for src in ["3 + 2 · i", "1 / 2 - 3 / 4 · i", "-i", "2 · i"] {
+        let t = evaluate(&lower_str(src)).expect("evaluate");
+        assert_eq!(show(&t), src);
+        assert_eq!(evaluate(&lower_str(&show(&t))).expect("evaluate"), t);
+    }
+}
@@ -0,0 +1,82 @@
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::{apply_eq_conditional, simplify};
+use regler::kernel::term::{sym, Term};
+use regler::kernel::theory::Theory;
+use regler::parser::parse_expr;
+use regler::printer::print_expr;
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn simp(src: &str, theory: &Theory) -> String {
+    print_expr(&to_surface(&simplify(&lower_str(src), theory)).expect("to_surface"))
+}
+
+fn install(theory: &mut Theory, fact: &str, cond: Option<&str>) {
+    let cond = cond.map(lower_str);
+    theory.install_fact(&lower_str(fact), None, cond.as_ref());
+}
+
+/// Apply `lhs = rhs if cond` once to `target`; `None` if it does not fire.
+fn apply_once(theory: &Theory, eq: (&str, &str), cond: &str, target: &str) -> Option<String> {
+    let (lhs, rhs, cond) = (lower_str(eq.0), lower_str(eq.1), lower_str(cond));
+    apply_eq_conditional(&lhs, &rhs, Some(&cond), &lower_str(target), theory)
+        .map(|t| print_expr(&to_surface(&t).unwrap()))
+}
+
+#[test]
+fn conditions_on_unreduced_arithmetic_are_simplified() {
+    let theory = Theory::new();
+    let eq = ("log(a · b)", "log(a) + log(b)");
+    let out = apply_once(&theory, eq, "a > 0 ∧ b > 0", "log((2 + 3) · 7)");
+    assert_eq!(out.as_deref(), Some("log(2 + 3) + log(7)"));
+    assert_eq!(apply_once(&theory, eq, "a > 0 ∧ b > 0", "log((1 - 3) · 7)"), None);
+}
+
+#[test]
+fn conditions_use_the_theory_rules() {
+    let mut theory = Theory::new();
+    install(&mut theory, "k(x) = 7", None);
+    install(&mut theory, "f(x, y) = x", Some("k(y) > y"));
+    assert_eq!(simp("f(a, 2)", &theory), "a");
+    assert_eq!(simp("f(a, 9)", &theory), "f(a, 9)");
+}
+
+#[test]
+fn conditions_through_predicate_sets_are_simplified() {
+    let mut theory = Theory::new();
+    theory.add_predicate_set(sym("Pos"), sym("x"), Term::Var(sym("ℝ")), lower_str("x > 0"));
+    install(&mut theory, "f(x) = x", Some("x ∈ Pos"));
+    assert_eq!(simp("f(1 + 1/2)", &theory), "3 / 2");
+    assert_eq!(simp("f(1 - 2)", &theory), "f(-1)");
+}
+
+#[test]
+fn undecided_conditions_block_the_rule() {
+    let mut theory = Theory::new();
+    install(&mut theory, "f(x) = x", Some("x > 0"));
+    assert_eq!(simp("f(y)", &theory), "f(y)");
+    assert_eq!(simp("f(0)", &theory), "f(0)");
+}
+
+#[test]
+fn negated_and_implied_conditions() {
+    let mut theory = Theory::new();
+    install(&mut theory, "f(x) = x", Some("¬(x = 0) ∧ (x > 5 ⇒ x < 10)"));
+    assert_eq!(simp("f(3)", &theory), "3");
+    assert_eq!(simp("f(7)", &theory), "7");
+    assert_eq!(simp("f(12)", &theory), "f(12)");
+    assert_eq!(simp("f(0)", &theory), "f(0)");
+}
+
+#[test]
+fn self_referential_condition_terminates() {
+    let mut theory = Theory::new();
+    // Discharging the condition needs the rule itself, over and over; the
+    // budget runs out and the rule stays blocked.
+    install(&mut theory, "f(x) = x", Some("f(x) > 0"));
+    assert_eq!(simp("f(a)", &theory), "f(a)");
+    assert_eq!(simp("f(2)", &theory), "f(2)");
+}
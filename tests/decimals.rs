@@ -0,0 +1,137 @@
+use num_rational::BigRational;
+use regler::ast::{Expr, InfixTable};
+use regler::kernel::eval::evaluate;
+use regler::kernel::lower::lower;
+use regler::kernel::print::{to_surface, to_surface_in, Numbers};
+use regler::kernel::term::Term;
+use regler::lexer::{tokenize, Token};
+use regler::parser::parse_expr;
+use regler::printer::{decimal, print_expr};
+
+fn rat(n: i64, d: i64) -> BigRational {
+    BigRational::new(n.into(), d.into())
+}
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn eval_str(src: &str) -> String {
+    print_expr(&to_surface(&evaluate(&lower_str(src)).expect("evaluate")).expect("to_surface"))
+}
+
+fn eval_decimal(src: &str) -> String {
+    let t = evaluate(&lower_str(src)).expect("evaluate");
+    print_expr(&to_surface_in(&t, &InfixTable::new(), Numbers::Decimals).expect("to_surface"))
+}
+
+fn rt_expr(src: &str) {
+    let e1 = parse_expr(src).expect("first parse");
+    let printed = print_expr(&e1);
+    let e2 = parse_expr(&printed).expect("reparse");
+    assert_eq!(e1, e2, "round-trip failed; printed = {printed:?}");
+}
+
+#[test]
+fn decimal_literals_are_exact() {
+    assert_eq!(parse_expr("0.25").expect("parse"), Expr::Decimal(rat(1, 4)));
+    assert_eq!(
+        parse_expr("1.5e-3").expect("parse"),
+        Expr::Decimal(rat(3, 2000))
+    );
+    assert_eq!(
+        parse_expr("2E3").expect("parse"),
+        Expr::Decimal(rat(2000, 1))
+    );
+    assert_eq!(
+        parse_expr("-0.5").expect("parse"),
+        Expr::Decimal(rat(-1, 2))
+    );
+    assert_eq!(lower_str("0.25"), Term::Rat(rat(1, 4)));
+    assert_eq!(lower_str("2.0"), lower_str("2"));
+}
+
+#[test]
+fn repeating_periods_are_exact() {
+    assert_eq!(
+        parse_expr("0.(3)").expect("parse"),
+        Expr::Decimal(rat(1, 3))
+    );
+    assert_eq!(
+        parse_expr("0.1(6)").expect("parse"),
+        Expr::Decimal(rat(1, 6))
+    );
+    assert_eq!(
+        parse_expr("0.(142857)").expect("parse"),
+        Expr::Decimal(rat(1, 7))
+    );
+    assert_eq!(
+        parse_expr("1.(9)").expect("parse"),
+        Expr::Decimal(rat(2, 1))
+    );
+    assert!(tokenize("0.(3").is_err());
+}
+
+#[test]
+fn points_and_es_that_are_not_decimals() {
+    assert_eq!(
+        tokenize("1.").expect("lex"),
+        vec![Token::Int(1.into()), Token::Period]
+    );
+    assert!(parse_expr("∀ x ∈ ℕ. 1 + x").is_ok());
+    assert_eq!(
+        tokenize("2·e").expect("lex"),
+        vec![Token::Int(2.into()), Token::Dot, Token::Ident("e".into())]
+    );
+}
+
+#[test]
+fn arithmetic_on_decimals() {
+    assert_eq!(eval_str("0.1 + 0.2"), "3 / 10");
+    assert_eq!(eval_str("0.(3) · 3"), "1");
+    assert_eq!(eval_str("1.5e-3 · 2"), "3 / 1000");
+}
+
+#[test]
+fn printer_writes_decimals() {
+    assert_eq!(decimal(&rat(1, 4), 32).as_deref(), Some("0.25"));
+    assert_eq!(decimal(&rat(-5, 2), 32).as_deref(), Some("-2.5"));
+    assert_eq!(decimal(&rat(1, 6), 32).as_deref(), Some("0.1(6)"));
+    assert_eq!(decimal(&rat(22, 7), 32).as_deref(), Some("3.(142857)"));
+    assert_eq!(
+        decimal(&rat(1, 1_000_000_000_000), 32).as_deref(),
+        Some("1e-12")
+    );
+    assert_eq!(decimal(&rat(1500, 1), 32).as_deref(), Some("1500.0"));
+    assert_eq!(decimal(&rat(1, 97), 32), None);
+}
+
+#[test]
+fn decimal_output_option() {
+    assert_eq!(eval_decimal("1 / 4 + 1 / 2"), "0.75");
+    assert_eq!(eval_decimal("1 / 7"), "0.(142857)");
+    assert_eq!(eval_decimal("x · (1 / 2)"), "x · 0.5");
+    assert_eq!(eval_decimal("1 / 3 + 1 / 4 · i"), "0.(3) + 0.25 · i");
+    // Integers stay integers; periods over 32 digits stay fractions.
+    assert_eq!(eval_decimal("3 / 3"), "1");
+    assert_eq!(eval_decimal("1 / 97"), "1 / 97");
+}
+
+#[test]
+fn decimals_round_trip() {
+    for src in [
+        "0.25",
+        "1.5e-3",
+        "0.1(6)",
+        "-2.5",
+        "1e-12",
+        "x · 0.(3) + 1.25",
+    ] {
+        rt_expr(src);
+    }
+    // The printed decimal reads back as the same rational.
+    let t = evaluate(&lower_str("1 / 7 + 3 / 8")).expect("evaluate");
+    let shown =
+        print_expr(&to_surface_in(&t, &InfixTable::new(), Numbers::Decimals).expect("surface"));
+    assert_eq!(lower_str(&shown), t);
+}
@@ -0,0 +1,165 @@
+use regler::ast::Command;
+use regler::kernel::diff::derivative;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::term::{sym, Term};
+use regler::kernel::theory::{FactEffect, Theory};
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::print_command;
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    regler::printer::print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn diff_in(theory: &Theory, src: &str) -> String {
+    show(&derivative(&lower_str(src), &sym("x"), theory).expect("derivative"))
+}
+
+fn diff(src: &str) -> String {
+    diff_in(&Theory::new(), src)
+}
+
+/// A theory knowing the derivatives of `sin`, `cos`, `exp` and `log`.
+fn calculus() -> Theory {
+    let mut theory = Theory::new();
+    for fact in [
+        "D(sin) = cos",
+        "D(cos) = -sin",
+        "D(exp) = exp",
+        "D(log) = 1 / id",
+    ] {
+        theory.install_fact(&lower_str(fact), None, None);
+    }
+    theory
+}
+
+#[test]
+fn sums_and_powers() {
+    assert_eq!(diff("x ^ 3 + 2 · x"), "3 · x ^ 2 + 2");
+    assert_eq!(diff("x - y"), "1");
+    assert_eq!(diff("-x ^ 2"), "2 · x");
+    assert_eq!(diff("-(x ^ 2)"), "-2 · x");
+    assert_eq!(diff("y ^ 2 + 5"), "0");
+    assert_eq!(diff("x ^ (1 / 2)"), "x ^ (-1 / 2) / 2");
+    assert_eq!(diff("(x ^ 2 + 1) ^ 3"), "6 · x · (x ^ 2 + 1) ^ 2");
+    assert_eq!(diff("x ^ n"), "n · x ^ (n - 1)");
+}
+
+#[test]
+fn products_and_quotients() {
+    assert_eq!(diff("x · y"), "y");
+    assert_eq!(diff("x · f(y) · x"), "f(y) · x + x · f(y)");
+    assert_eq!(diff("1 / x"), "-1 / x ^ 2");
+    assert_eq!(diff("x / 3"), "1 / 3");
+    assert_eq!(diff("sqrt(x)"), "1 / (2 · sqrt(x))");
+}
+
+#[test]
+fn chain_rule_uses_the_table() {
+    let theory = calculus();
+    assert_eq!(diff_in(&theory, "sin(x ^ 2)"), "2 · x · cos(x ^ 2)");
+    assert_eq!(diff_in(&theory, "x · cos(x)"), "cos(x) - x · sin(x)");
+    assert_eq!(diff_in(&theory, "exp(sin(x))"), "cos(x) · exp(sin(x))");
+    assert_eq!(diff_in(&theory, "log(log(x))"), "1 / (x · log(x))");
+    assert_eq!(diff_in(&theory, "2 ^ x"), "2 ^ x · log(2)");
+    assert_eq!(
+        diff_in(&theory, "x ^ x"),
+        "x · x ^ (x - 1) + x ^ x · log(x)"
+    );
+}
+
+#[test]
+fn unknown_functions_are_errors() {
+    let err =
+        derivative(&lower_str("tan(x)"), &sym("x"), &Theory::new()).expect_err("no table entry");
+    assert!(err.0.contains("fact D(tan)"), "{}", err.0);
+    assert!(derivative(&lower_str("f(x, 1)"), &sym("x"), &Theory::new()).is_err());
+    // Functions of other variables are constants.
+    assert_eq!(diff("tan(y) · x"), "tan(y)");
+}
+
+#[test]
+fn derivative_facts_fill_the_table() {
+    let mut theory = Theory::new();
+    let effects = theory.install_fact(&lower_str("D(tan) = 1 + tan ^ 2"), None, None);
+    assert!(matches!(effects.as_slice(), [FactEffect::Derivative(f)] if f.as_ref() == "tan"));
+    assert!(theory.rules.is_empty());
+    let again = theory.install_fact(&lower_str("D(tan) = 1 + tan ^ 2"), None, None);
+    assert!(matches!(again.as_slice(), [FactEffect::AlreadyKnown]));
+    assert_eq!(diff_in(&theory, "tan(x)"), "1 + tan(x) ^ 2");
+    // Only a bare name is differentiated this way.
+    let other = theory.install_fact(&lower_str("D(f(x)) = g"), None, None);
+    assert!(!matches!(other.as_slice(), [FactEffect::Derivative(_)]));
+}
+
+#[test]
+fn sums_and_piecewise_terms() {
+    assert_eq!(
+        diff("∑ k · x ^ k for k from 1 to n"),
+        "∑ k · k · x ^ (k - 1) for k from 1 to n"
+    );
+    assert_eq!(diff("∑ x for x from 1 to 3"), "0");
+    assert!(derivative(
+        &lower_str("∑ k for k from 1 to x"),
+        &sym("x"),
+        &Theory::new()
+    )
+    .is_err());
+    assert_eq!(
+        diff("if x > 0 then x ^ 2 else -x"),
+        "if x > 0 then 2 · x else -1"
+    );
+}
+
+#[test]
+fn results_are_simplified_with_the_theory() {
+    let mut theory = Theory::new();
+    for fact in [
+        "a + b = b + a",
+        "(a + b) + c = a + (b + c)",
+        "x + 0 = x",
+        "a · b = b · a",
+        "(a · b) · c = a · (b · c)",
+        "x · 1 = x",
+    ] {
+        theory.install_fact(&lower_str(fact), None, None);
+    }
+    assert_eq!(
+        diff_in(&theory, "(x ^ 2 + 1) ^ 3"),
+        "x ^ 5 · 6 + x ^ 3 · 12 + x · 6"
+    );
+    assert_eq!(diff_in(&theory, "x ^ 2 · (x - 1)"), "x ^ 2 · 3 - x · 2");
+}
+
+#[test]
+fn diff_command_round_trips() {
+    let cmd = parse_command("diff(x ^ 2 + y, x)")
+        .expect("parse")
+        .expect("command");
+    assert!(matches!(&cmd, Command::Diff(_, x) if x == "x"));
+    assert_eq!(print_command(&cmd), "diff(x ^ 2 + y, x)");
+    assert_eq!(parse_command(&print_command(&cmd)).unwrap(), Some(cmd.clone()));
+    // `diff e for x` is the same command.
+    assert_eq!(parse_command("diff x ^ 2 + y for x").unwrap(), Some(cmd));
+    let cmd = parse_command("diff (x + 1) ^ 2 for x").unwrap().unwrap();
+    assert_eq!(print_command(&cmd), "diff((x + 1) ^ 2, x)");
+    assert!(parse_command("diff x ^ 2").is_err());
+    assert!(parse_command("diff x ^ 2 for 2").is_err());
+    assert!(parse_command("diff(x ^ 2, 2)").is_err());
+    assert!(parse_command("diff(x ^ 2)").is_err());
+}
+
+#[test]
+fn diff_is_not_reserved() {
+    // Only a leading `diff` starts the command; elsewhere it is a name.
+    assert!(matches!(
+        parse_command("simplify diff(x ^ 2, x) + 1").expect("parse"),
+        Some(Command::Simplify(..))
+    ));
+    assert_eq!(show(&lower_str("diff(x, y)")), "diff(x, y)");
+    assert!(parse_command("let diff = 3").is_ok());
+}
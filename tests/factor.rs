@@ -0,0 +1,109 @@
+use num_rational::BigRational;
+use regler::ast::Command;
+use regler::kernel::factor::{factor, factor_poly};
+use regler::kernel::lower::lower;
+use regler::kernel::poly::{expand, Poly};
+use regler::kernel::print::to_surface;
+use regler::kernel::term::Term;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn factor_str(src: &str) -> String {
+    show(&factor(&lower_str(src)))
+}
+
+#[test]
+fn difference_of_squares_prints_naturally() {
+    assert_eq!(factor_str("x^2 - 1"), "(x - 1) · (x + 1)");
+    assert_eq!(factor_str("x^3 - x"), "x · (x - 1) · (x + 1)");
+    assert_eq!(factor_str("6·x^2 + 5·x + 1"), "(x · 2 + 1) · (x · 3 + 1)");
+}
+
+#[test]
+fn content_comes_out_as_a_constant() {
+    assert_eq!(factor_str("2·x^2 - 2"), "(x - 1) · (x + 1) · 2");
+    assert_eq!(factor_str("1 - x^2"), "-((x - 1) · (x + 1))");
+    assert_eq!(factor_str("x / 2 - 1 / 2"), "(x - 1) · (1 / 2)");
+    assert_eq!(factor_str("7"), "7");
+    let f = factor_poly(&Poly::from_term(&lower_str("-4·x^2 - 4·x")));
+    assert_eq!(f.unit, BigRational::from_integer((-4).into()));
+    assert_eq!(f.factors.len(), 2);
+}
+
+#[test]
+fn repeated_factors_get_exponents() {
+    assert_eq!(
+        factor_str("(x + 1)^3 · (x - 2)^2"),
+        "(x - 2) ^ 2 · (x + 1) ^ 3"
+    );
+    assert_eq!(factor_str("x^4 · (x^2 + 1)^2"), "x ^ 4 · (x ^ 2 + 1) ^ 2");
+}
+
+#[test]
+fn factors_without_rational_roots() {
+    // Sophie Germain: no rational roots, but two quadratic factors.
+    assert_eq!(
+        factor_str("x^4 + 4"),
+        "(x ^ 2 - x · 2 + 2) · (x ^ 2 + x · 2 + 2)"
+    );
+    assert_eq!(
+        factor_str("x^6 - 1"),
+        "(x - 1) · (x + 1) · (x ^ 2 - x + 1) · (x ^ 2 + x + 1)"
+    );
+    assert_eq!(
+        factor_str("(x^2 + 3)·(x^3 + x + 1)·(2·x - 7)"),
+        "(x · 2 - 7) · (x ^ 2 + 3) · (x ^ 3 + x + 1)"
+    );
+}
+
+#[test]
+fn irreducible_polynomials_stay_whole() {
+    assert_eq!(factor_str("x^2 + 1"), "x ^ 2 + 1");
+    // Irreducible over ℤ, though it splits modulo every prime.
+    assert_eq!(factor_str("x^4 - 10·x^2 + 1"), "x ^ 4 - x ^ 2 · 10 + 1");
+}
+
+#[test]
+fn multivariate_content_and_square_free_parts() {
+    assert_eq!(factor_str("a·x + a·y + b·x + b·y"), "(a + b) · (x + y)");
+    assert_eq!(factor_str("x^2·y - x·y^2"), "x · y · (x - y)");
+    assert_eq!(factor_str("x^2 - 2·x·y + y^2"), "(x - y) ^ 2");
+}
+
+#[test]
+fn factoring_inverts_expansion() {
+    for src in [
+        "(x - 1)^2 · (x^2 + x + 1) · 3",
+        "(a + b) · (a - b) · c",
+        "x^10 - 1",
+    ] {
+        let t = lower_str(src);
+        assert_eq!(expand(&factor(&t)), expand(&t));
+    }
+}
+
+#[test]
+fn fractions_and_other_heads() {
+    assert_eq!(
+        factor_str("(x^2 - 1) / (x^2 + 2·x + 1)"),
+        "(x - 1) · (x + 1) / (x + 1) ^ 2"
+    );
+    assert_eq!(factor_str("f(x^2 - 1) + 1"), "f((x - 1) · (x + 1)) + 1");
+}
+
+#[test]
+fn factor_command_round_trips() {
+    let cmd = parse_command("factor x ^ 2 - 1").unwrap().unwrap();
+    assert!(matches!(cmd, Command::Factor(_)));
+    assert_eq!(print_command(&cmd), "factor x ^ 2 - 1");
+    assert!(parse_expr("factor + 1").is_ok());
+    assert!(parse_command("let factor = 2").is_ok());
+}
@@ -0,0 +1,169 @@
+use regler::ast::{Command, MonomialOrder};
+use regler::kernel::groebner::{compare, groebner_basis, ideal_membership, Membership};
+use regler::kernel::lower::lower;
+use regler::kernel::poly::{Monomial, Poly};
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::simplify_assuming;
+use regler::kernel::term::{as_truth, Term};
+use regler::kernel::theory::Theory;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+const ORDERS: [MonomialOrder; 3] = [
+    MonomialOrder::Lex,
+    MonomialOrder::Grevlex,
+    MonomialOrder::Kbo,
+];
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn poly(src: &str) -> Poly {
+    Poly::from_term(&lower_str(src))
+}
+
+fn polys(srcs: &[&str]) -> Vec<Poly> {
+    srcs.iter().map(|s| poly(s)).collect()
+}
+
+fn monomial(src: &str) -> Monomial {
+    let p = poly(src);
+    let (m, _) = p.terms().next().expect("monomial");
+    m.clone()
+}
+
+/// Check a certificate: the goal is the sum of the cofactors times the
+/// hypotheses.
+fn assert_certified(goal: &str, hyps: &[&str], order: MonomialOrder) {
+    let (g, hs) = (poly(goal), polys(hyps));
+    match ideal_membership(&g, &hs, order) {
+        Membership::Member(cofactors) => {
+            assert_eq!(cofactors.len(), hs.len());
+            let sum = cofactors
+                .iter()
+                .zip(&hs)
+                .fold(Poly::zero(), |acc, (c, h)| &acc + &(c * h));
+            assert_eq!(sum, g, "{goal} under {order:?}");
+        }
+        other => panic!("{goal} under {order:?}: {other:?}"),
+    }
+}
+
+#[test]
+fn monomial_orders_disagree_where_they_should() {
+    let (a, b) = (monomial("x · z^2"), monomial("y^3"));
+    assert!(compare(MonomialOrder::Lex, &a, &b).is_gt());
+    // Same degree; grevlex prefers the smaller power of the last variable.
+    assert!(compare(MonomialOrder::Grevlex, &a, &b).is_lt());
+    assert!(compare(MonomialOrder::Grevlex, &monomial("x^2"), &monomial("y^3")).is_lt());
+    assert!(compare(MonomialOrder::Lex, &monomial("x^2"), &monomial("y^3")).is_gt());
+}
+
+#[test]
+fn kbo_order_ranks_heavier_indeterminates_first() {
+    // `f(x)` weighs 2, so it outweighs `x` and `y` at the same degree.
+    let (f, y) = (monomial("f(x)"), monomial("y"));
+    assert!(compare(MonomialOrder::Kbo, &f, &y).is_gt());
+    assert!(compare(MonomialOrder::Kbo, &monomial("y^2"), &f).is_lt());
+    assert!(compare(MonomialOrder::Kbo, &monomial("f(x) · y"), &monomial("y^3")).is_gt());
+    assert!(compare(MonomialOrder::Kbo, &monomial("x"), &monomial("y")).is_gt());
+}
+
+#[test]
+fn members_come_with_checked_certificates() {
+    for order in ORDERS {
+        assert_certified("x^2 - 1", &["x - 1"], order);
+        assert_certified("z - x", &["x - y", "y - z"], order);
+        assert_certified("x^3 - x", &["x^2 - 1"], order);
+        assert_certified("x · y", &["x^2", "x · y^2 - 1"], order);
+    }
+}
+
+#[test]
+fn geometry_style_goal_needs_the_basis() {
+    // A point on the unit circle scaled by `c` lies on the circle of radius `c`.
+    for order in ORDERS {
+        assert_certified(
+            "a^2 + b^2 - c^2",
+            &["x^2 + y^2 - 1", "a - x · c", "b - y · c"],
+            order,
+        );
+    }
+    // The goal only follows after an S-polynomial: `y` from `x·y - 1`, `x - y`.
+    for order in ORDERS {
+        assert_certified("y^2 - 1", &["x · y - 1", "x - y"], order);
+    }
+}
+
+#[test]
+fn non_members_report_a_remainder() {
+    let hs = polys(&["x^2 - 1"]);
+    match ideal_membership(&poly("x - 1"), &hs, MonomialOrder::Grevlex) {
+        Membership::NotMember(r) => assert_eq!(show(&r.to_term()), "x - 1"),
+        other => panic!("{other:?}"),
+    }
+    let hs = polys(&["x - y"]);
+    match ideal_membership(&poly("x^2 - y"), &hs, MonomialOrder::Lex) {
+        Membership::NotMember(r) => assert_eq!(r, poly("y^2 - y")),
+        other => panic!("{other:?}"),
+    }
+    assert!(!ideal_membership(&poly("y"), &polys(&["x"]), MonomialOrder::Kbo).is_member());
+}
+
+#[test]
+fn theory_rules_are_outside_the_ideal() {
+    // `prove f(x) = 1 assuming x = 0` needs the fact `f(0) = 1`, which ideal
+    // membership cannot see, so without `using` it falls back to
+    // `simplify_assuming`.
+    assert!(!ideal_membership(&poly("f(x) - 1"), &polys(&["x"]), MonomialOrder::Grevlex)
+        .is_member());
+    let mut theory = Theory::new();
+    theory.install_fact(&lower_str("f(0) = 1"), None, None);
+    let goal = simplify_assuming(&lower_str("f(x) = 1"), &lower_str("x = 0"), &theory);
+    assert_eq!(as_truth(&goal), Some(true));
+}
+
+#[test]
+fn inconsistent_hypotheses_prove_anything() {
+    for order in ORDERS {
+        assert_certified("1", &["x - 1", "x - 2"], order);
+        assert_certified("y^5 + 3", &["x · y - 1", "x"], order);
+    }
+}
+
+#[test]
+fn reduced_basis_elements_are_monic() {
+    let basis =
+        groebner_basis(&polys(&["2 · x - 4", "x · y - 1"]), MonomialOrder::Lex).expect("basis");
+    assert!(basis.contains(&poly("x - 2")));
+    assert!(basis.contains(&poly("y - 1/2")));
+    assert!(groebner_basis(&[], MonomialOrder::Lex)
+        .expect("basis")
+        .is_empty());
+}
+
+#[test]
+fn prove_assuming_round_trips() {
+    for src in [
+        "prove x ^ 2 = 1 assuming x = 1",
+        "prove z = x assuming x = y, y = z using grevlex",
+        "prove a = b assuming a - b = 0 using kbo by cases",
+        "prove x > 0 by cases",
+    ] {
+        let cmd = parse_command(src).unwrap().unwrap();
+        assert_eq!(print_command(&cmd), src);
+    }
+    let cmd = parse_command("prove x = 0 assuming x = 0 using lex")
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        cmd,
+        Command::Prove(_, ref hyps, Some(MonomialOrder::Lex), false) if hyps.len() == 1
+    ));
+    assert!(parse_command("prove x = 0 using plex").is_err());
+}
@@ -0,0 +1,101 @@
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::{apply_eq_conditional, simplify};
+use regler::kernel::term::{sym, Term};
+use regler::kernel::theory::{FactEffect, Theory};
+use regler::parser::parse_expr;
+use regler::printer::print_expr;
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn theory_from_facts(facts: &[&str]) -> Theory {
+    let mut theory = Theory::new();
+    theory.add_predicate_set(sym("Pos"), sym("x"), Term::Var(sym("ℝ")), lower_str("x > 0"));
+    for f in facts {
+        theory.install_fact(&lower_str(f), None, None);
+    }
+    theory
+}
+
+fn simp(src: &str, theory: &Theory) -> String {
+    print_expr(&to_surface(&simplify(&lower_str(src), theory)).expect("to_surface"))
+}
+
+#[test]
+fn implication_with_equation_installs_conditional_rule() {
+    let mut theory = Theory::new();
+    let effects = theory.install_fact(&lower_str("x ≠ 0 ⇒ x / x = 1"), None, None);
+    assert!(matches!(effects.as_slice(), [FactEffect::Inverses, FactEffect::RuleInstalled]));
+    assert_eq!(theory.rules.len(), 1);
+    assert_eq!(theory.rules[0].condition, Some(lower_str("x ≠ 0")));
+    assert_eq!(simp("3 / 3", &theory), "1");
+    assert_eq!(simp("y / y", &theory), "y / y");
+}
+
+#[test]
+fn premise_is_conjoined_with_existing_condition() {
+    let mut theory = Theory::new();
+    let cond = lower_str("x ∈ Pos");
+    theory.install_fact(&lower_str("x ≠ 1 ⇒ f(x) = g(x)"), None, Some(&cond));
+    assert_eq!(theory.rules[0].condition, Some(lower_str("x ∈ Pos ∧ x ≠ 1")));
+}
+
+#[test]
+fn nested_implications_conjoin_premises() {
+    let mut theory = Theory::new();
+    theory.install_fact(&lower_str("x > 0 ⇒ y > 0 ⇒ f(x, y) = g(x)"), None, None);
+    assert_eq!(theory.rules[0].condition, Some(lower_str("x > 0 ∧ y > 0")));
+}
+
+#[test]
+fn named_implication_is_applicable_with_its_premise() {
+    let mut theory = Theory::new();
+    theory.install_fact(&lower_str("x ≠ 0 ⇒ x / x = 1"), Some(sym("cancel")), None);
+    let nf = &theory.named[&sym("cancel")];
+    let fire = |target: &str| {
+        apply_eq_conditional(&nf.lhs, &nf.rhs, nf.condition.as_ref(), &lower_str(target), &theory)
+    };
+    assert!(fire("5 / 5").is_some());
+    assert!(fire("0 / 0").is_none());
+}
+
+#[test]
+fn atomic_conclusions_become_implications() {
+    let mut theory = Theory::new();
+    let effects = theory.install_fact(&lower_str("x > 0 ⇒ x ∈ Pos"), None, None);
+    assert!(matches!(effects.as_slice(), [FactEffect::ImplicationInstalled]));
+    let effects = theory.install_fact(&lower_str("exp(x) > 0"), None, None);
+    assert!(matches!(effects.as_slice(), [FactEffect::ImplicationInstalled]));
+    assert_eq!(theory.implications.len(), 2);
+    assert!(theory.rules.is_empty());
+}
+
+#[test]
+fn disjunctive_conclusion_stays_inert() {
+    let mut theory = Theory::new();
+    let effects = theory.install_fact(&lower_str("a · b = 0 ⇒ a = 0 ∨ b = 0"), None, None);
+    assert!(matches!(effects.as_slice(), [FactEffect::NotEquality]));
+    assert!(theory.implications.is_empty());
+}
+
+#[test]
+fn conditions_chain_through_implications() {
+    let theory = theory_from_facts(&[
+        "exp(x) > 0",
+        "x > 0 ⇒ x ∈ Pos",
+        "x ∈ Pos ⇒ x ≠ 0",
+        "x ≠ 0 ⇒ x / x = 1",
+    ]);
+    // exp(y) ≠ 0 ⇐ exp(y) ∈ Pos ⇐ exp(y) > 0
+    assert_eq!(simp("exp(y) / exp(y)", &theory), "1");
+    assert_eq!(simp("exp(y) ∈ Pos", &theory), "True");
+    assert_eq!(simp("log(y) / log(y)", &theory), "log(y) / log(y)");
+}
+
+#[test]
+fn circular_implications_terminate() {
+    let theory = theory_from_facts(&["x > 0 ⇒ x > 0", "x ≥ 1 ⇒ x > 0", "x > 0 ⇒ x ≥ 1"]);
+    assert_eq!(simp("a > 0", &theory), "a > 0");
+}
@@ -0,0 +1,183 @@
+use regler::ast::{Assoc, Command, Expr, InfixOp, InfixTable};
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface_with;
+use regler::kernel::rewrite::simplify;
+use regler::kernel::term::{sym, Term};
+use regler::kernel::theory::Theory;
+use regler::lexer::{tokenize_with, Token};
+use regler::parser::{parse_command, parse_command_with, parse_expr, parse_expr_with};
+use regler::printer::{print_command, print_expr};
+
+fn op(symbol: &str, prec: u8, assoc: Assoc) -> InfixOp {
+    InfixOp {
+        symbol: symbol.into(),
+        prec,
+        assoc,
+    }
+}
+
+fn table() -> InfixTable {
+    let mut ops = InfixTable::new();
+    ops.declare(op("∘", 65, Assoc::Right));
+    ops.declare(op("⊕", 50, Assoc::Left));
+    ops.declare(op("mod", 60, Assoc::Left));
+    ops.declare(op("∣", 40, Assoc::None));
+    ops.declare(op("<+>", 50, Assoc::Left));
+    ops
+}
+
+fn rt_expr(src: &str, ops: &InfixTable) {
+    let e1 = parse_expr_with(src, ops).expect("first parse");
+    let printed = print_expr(&e1);
+    let e2 = parse_expr_with(&printed, ops).expect("reparse");
+    assert_eq!(e1, e2, "round-trip failed; printed = {printed:?}");
+}
+
+fn kernel_rt(src: &str, ops: &InfixTable) -> String {
+    let t = lower(&parse_expr_with(src, ops).expect("parse")).expect("lower");
+    print_expr(&to_surface_with(&t, ops).expect("to_surface"))
+}
+
+// ── Declarations ─────────────────────────────────────────────────────────────
+
+#[test]
+fn infix_declaration_parses() {
+    let cmd = parse_command("infix 65 right ∘").unwrap().unwrap();
+    assert_eq!(cmd, Command::Infix(op("∘", 65, Assoc::Right)));
+    let cmd = parse_command("infix 60 left rem").unwrap().unwrap();
+    assert_eq!(cmd, Command::Infix(op("rem", 60, Assoc::Left)));
+    let cmd = parse_command("infix 40 none ∣").unwrap().unwrap();
+    assert_eq!(cmd, Command::Infix(op("∣", 40, Assoc::None)));
+}
+
+#[test]
+fn infix_declaration_reads_symbol_verbatim() {
+    let cmd = parse_command("infix 50 left <+>").unwrap().unwrap();
+    assert_eq!(cmd, Command::Infix(op("<+>", 50, Assoc::Left)));
+}
+
+#[test]
+fn infix_declaration_round_trips() {
+    for src in ["infix 65 right ∘", "infix 60 left rem", "infix 40 none ∣"] {
+        let cmd = parse_command(src).unwrap().unwrap();
+        assert_eq!(print_command(&cmd), src);
+    }
+}
+
+#[test]
+fn infix_declaration_rejects_bad_input() {
+    assert!(parse_command("infix 0 left ⊕").is_err());
+    assert!(parse_command("infix 100 left ⊕").is_err());
+    assert!(parse_command("infix 50 sideways ⊕").is_err());
+    assert!(parse_command("infix 50 left").is_err());
+    assert!(parse_command("infix 50 left to").is_err());
+}
+
+// ── Lexing and parsing ───────────────────────────────────────────────────────
+
+#[test]
+fn lexer_recognises_declared_symbols() {
+    let ops = table();
+    let toks = tokenize_with("a <+> b mod c", &ops).unwrap();
+    assert_eq!(toks[1], Token::UserOp("<+>".into()));
+    assert_eq!(toks[3], Token::UserOp("mod".into()));
+    // Built-in operators are unaffected by a declared symbol sharing a prefix.
+    let toks = tokenize_with("a < b", &ops).unwrap();
+    assert_eq!(toks[1], Token::Lt);
+}
+
+#[test]
+fn undeclared_symbol_is_a_parse_error() {
+    let err = parse_expr("a ⊕ b").unwrap_err();
+    assert!(err.0.contains("unknown operator `⊕`"), "{}", err.0);
+}
+
+#[test]
+fn precedence_from_declaration() {
+    let ops = table();
+    // ∘ (65) binds tighter than · (60)
+    let e = parse_expr_with("a · f ∘ g", &ops).unwrap();
+    assert!(matches!(e, Expr::BinOp(_, _, ref r) if matches!(**r, Expr::Infix(_, _, _))));
+    // ⊕ (50) binds looser than · (60)
+    let e = parse_expr_with("a ⊕ b · c", &ops).unwrap();
+    assert!(matches!(e, Expr::Infix(_, _, ref r) if matches!(**r, Expr::BinOp(_, _, _))));
+}
+
+#[test]
+fn associativity_from_declaration() {
+    let ops = table();
+    let e = parse_expr_with("f ∘ g ∘ h", &ops).unwrap();
+    match e {
+        Expr::Infix(_, l, r) => {
+            assert_eq!(*l, Expr::Ident("f".into()));
+            assert!(matches!(*r, Expr::Infix(_, _, _)));
+        }
+        _ => panic!("expected Infix, got {e:?}"),
+    }
+    let e = parse_expr_with("a ⊕ b ⊕ c", &ops).unwrap();
+    match e {
+        Expr::Infix(_, l, _) => assert!(matches!(*l, Expr::Infix(_, _, _))),
+        _ => panic!("expected Infix, got {e:?}"),
+    }
+}
+
+#[test]
+fn prefix_form_still_accepted() {
+    let ops = table();
+    let e = parse_expr_with("∘(f, g)", &ops).unwrap();
+    assert_eq!(
+        e,
+        Expr::App("∘".into(), vec![Expr::Ident("f".into()), Expr::Ident("g".into())])
+    );
+}
+
+#[test]
+fn user_infix_round_trips() {
+    let ops = table();
+    rt_expr("f ∘ g ∘ h", &ops);
+    rt_expr("(f ∘ g) ∘ h", &ops);
+    rt_expr("a ⊕ b ⊕ c", &ops);
+    rt_expr("a ⊕ (b ⊕ c)", &ops);
+    rt_expr("(a ⊕ b) · c", &ops);
+    rt_expr("n mod 2 = 0", &ops);
+    rt_expr("(a ∣ b) ∣ c", &ops);
+    rt_expr("-(a ⊕ b)", &ops);
+    rt_expr("a <+> b", &ops);
+}
+
+#[test]
+fn user_infix_commands_parse_with_table() {
+    let ops = table();
+    let cmd = parse_command_with("fact (f ∘ g) ∘ h = f ∘ (g ∘ h)", &ops).unwrap().unwrap();
+    assert_eq!(print_command(&cmd), "fact (f ∘ g) ∘ h = f ∘ g ∘ h");
+}
+
+// ── Kernel lowering and printing ─────────────────────────────────────────────
+
+#[test]
+fn user_infix_lowers_to_prefix_application() {
+    let ops = table();
+    let t = lower(&parse_expr_with("f ∘ g", &ops).unwrap()).unwrap();
+    assert_eq!(t, Term::App(sym("∘"), vec![Term::Var(sym("f")), Term::Var(sym("g"))]));
+}
+
+#[test]
+fn to_surface_prints_user_ops_infix() {
+    let ops = table();
+    assert_eq!(kernel_rt("∘(f, g)", &ops), "f ∘ g");
+    assert_eq!(kernel_rt("(a ⊕ b) · c", &ops), "(a ⊕ b) · c");
+    assert_eq!(kernel_rt("x mod 2 = 0", &ops), "x mod 2 = 0");
+}
+
+#[test]
+fn ac_user_op_unfolds_infix() {
+    let ops = table();
+    let mut theory = Theory::new();
+    for f in ["a ⊕ b = b ⊕ a", "(a ⊕ b) ⊕ c = a ⊕ (b ⊕ c)"] {
+        let t = lower(&parse_expr_with(f, &ops).unwrap()).unwrap();
+        theory.install_fact(&t, None, None);
+    }
+    let t = lower(&parse_expr_with("(c ⊕ a) ⊕ b", &ops).unwrap()).unwrap();
+    let t = simplify(&t, &theory);
+    assert_eq!(print_expr(&to_surface_with(&t, &ops).unwrap()), "a ⊕ b ⊕ c");
+}
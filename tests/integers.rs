@@ -0,0 +1,173 @@
+use num_bigint::BigUint;
+use regler::ast::{Expr, InfixTable};
+use regler::kernel::eval::evaluate;
+use regler::kernel::factor::factor;
+use regler::kernel::lower::lower;
+use regler::kernel::numtheory::factorize;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::simplify;
+use regler::kernel::term::{sym, Term};
+use regler::kernel::theory::Theory;
+use regler::lexer::{tokenize, Token};
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::print_expr;
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn eval_str(src: &str) -> String {
+    show(&evaluate(&lower_str(src)).expect("evaluate"))
+}
+
+fn eval_err(src: &str) -> String {
+    evaluate(&lower_str(src)).expect_err(src).0
+}
+
+fn big(s: &str) -> BigUint {
+    s.parse().expect("number")
+}
+
+#[test]
+fn mod_and_div_are_floored() {
+    assert_eq!(eval_str("17 mod 5"), "2");
+    assert_eq!(eval_str("-7 mod 3"), "2");
+    assert_eq!(eval_str("7 mod -3"), "-2");
+    assert_eq!(eval_str("-7 div 3"), "-3");
+    // Same precedence as `·`, left-associative.
+    assert_eq!(eval_str("2 · 7 mod 4"), "2");
+    assert_eq!(eval_str("100 div 7 div 2"), "7");
+    assert_eq!(eval_err("3 mod 0"), "division by zero");
+}
+
+#[test]
+fn integer_functions_reject_rationals() {
+    assert_eq!(eval_err("7.5 mod 2"), "`mod` is only defined on integers");
+    assert_eq!(eval_err("7 / 2 div 1"), "`div` is only defined on integers");
+    assert_eq!(eval_err("gcd(1 / 2, 3)"), "`gcd` is only defined on integers");
+    assert_eq!(
+        eval_err("binomial(5, 1 / 3)"),
+        "`binomial` is only defined on integers"
+    );
+}
+
+#[test]
+fn gcd_lcm_binomial() {
+    assert_eq!(eval_str("gcd(84, 36)"), "12");
+    assert_eq!(eval_str("gcd(-4, 6)"), "2");
+    assert_eq!(eval_str("gcd(0, 0)"), "0");
+    assert_eq!(eval_str("lcm(4, 6)"), "12");
+    assert_eq!(eval_str("lcm(0, 5)"), "0");
+    assert_eq!(eval_str("binomial(52, 5)"), "2598960");
+    assert_eq!(eval_str("binomial(5, 7)"), "0");
+    assert_eq!(eval_str("binomial(-2, 3)"), "-4");
+}
+
+#[test]
+fn factorials() {
+    assert_eq!(eval_str("0!"), "1");
+    assert_eq!(eval_str("5!"), "120");
+    assert_eq!(eval_str("factorial(20)"), "2432902008176640000");
+    assert_eq!(eval_str("3!!"), "720");
+    assert_eq!(eval_str("-3!"), "-6");
+    assert_eq!(eval_err("(-1)!"), "factorial of a negative number");
+    assert!(eval_err("100000!").contains("too large"));
+}
+
+#[test]
+fn factorial_syntax() {
+    assert_eq!(
+        parse_expr("n!").expect("parse"),
+        Expr::App("factorial".into(), vec![Expr::Ident("n".into())])
+    );
+    assert_eq!(
+        tokenize("n! = 6").expect("lex"),
+        vec![
+            Token::Ident("n".into()),
+            Token::Bang,
+            Token::Equals,
+            Token::Int(6.into())
+        ]
+    );
+    assert_eq!(tokenize("n!=6").expect("lex")[1], Token::NotEquals);
+    assert_eq!(tokenize("n != 6").expect("lex")[1], Token::NotEquals);
+    assert!(parse_command("infix 50 left !").is_err());
+}
+
+#[test]
+fn primality() {
+    assert_eq!(eval_str("isprime(97)"), "True");
+    assert_eq!(eval_str("isprime(1)"), "False");
+    assert_eq!(eval_str("isprime(2 ^ 61 - 1)"), "True");
+    assert_eq!(eval_str("isprime(2 ^ 67 - 1)"), "False");
+    assert_eq!(eval_str("isprime(x)"), "isprime(x)");
+}
+
+#[test]
+fn factorization_by_pollard_rho() {
+    let two = |k: u32| (big("2"), k);
+    assert_eq!(factorize(&big("1")), vec![]);
+    assert_eq!(factorize(&big("1024")), vec![two(10)]);
+    assert_eq!(
+        factorize(&big("147573952589676412927")),
+        vec![(big("193707721"), 1), (big("761838257287"), 1)]
+    );
+    assert_eq!(
+        factorize(&big("1000000016000000063")),
+        vec![(big("1000000007"), 1), (big("1000000009"), 1)]
+    );
+    assert_eq!(
+        factorize(&big("1000000014000000049")),
+        vec![(big("1000000007"), 2)]
+    );
+}
+
+#[test]
+fn factor_writes_prime_powers() {
+    let factor_str = |s: &str| show(&factor(&lower_str(s)));
+    assert_eq!(factor_str("360"), "2 ^ 3 · 3 ^ 2 · 5");
+    assert_eq!(factor_str("2 ^ 64 + 1"), "274177 · 67280421310721");
+    assert_eq!(factor_str("-12 / 35"), "-(2 ^ 2 · 3 / (5 · 7))");
+    assert_eq!(factor_str("1 / 8"), "1 / 2 ^ 3");
+    assert_eq!(factor_str("97"), "97");
+    // Only a whole numeric result is factored.
+    assert_eq!(factor_str("f(12)"), "f(12)");
+}
+
+#[test]
+fn predicates_become_decidable() {
+    let mut theory = Theory::new();
+    theory.add_predicate_set(
+        sym("Evens"),
+        sym("n"),
+        lower_str("ℤ"),
+        lower_str("n mod 2 = 0"),
+    );
+    let simp = |s: &str, th: &Theory| show(&simplify(&lower_str(s), th));
+    assert_eq!(simp("10 ∈ Evens", &theory), "True");
+    assert_eq!(simp("-3 ∈ Evens", &theory), "False");
+    assert_eq!(simp("x mod 2", &theory), "x mod 2");
+
+    theory.install_fact(&lower_str("g(n) = 0"), None, Some(&lower_str("isprime(n)")));
+    assert_eq!(simp("g(13)", &theory), "0");
+    assert_eq!(simp("g(15)", &theory), "g(15)");
+}
+
+#[test]
+fn mod_and_div_are_predeclared() {
+    let ops = InfixTable::new();
+    assert_eq!(ops.get("mod").map(|op| op.prec), Some(60));
+    assert_eq!(
+        print_expr(&parse_expr("a mod b").expect("parse")),
+        "a mod b"
+    );
+    assert_eq!(show(&lower_str("div(a, b)")), "a div b");
+    // The built-in operators cannot be redeclared.
+    for src in ["infix 55 right div", "infix 60 left mod"] {
+        assert!(parse_command(src).is_err(), "{src}");
+    }
+}
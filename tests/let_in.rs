@@ -0,0 +1,122 @@
+use regler::ast::{Command, Expr};
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::simplify;
+use regler::kernel::theory::Theory;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+fn rt_expr(src: &str) {
+    let e1 = parse_expr(src).expect("first parse");
+    let printed = print_expr(&e1);
+    let e2 = parse_expr(&printed).expect("reparse");
+    assert_eq!(e1, e2, "round-trip failed; printed = {printed:?}");
+}
+
+fn rt_cmd(src: &str) {
+    let c1 = parse_command(src).expect("parse").expect("command");
+    let printed = print_command(&c1);
+    let c2 = parse_command(&printed).expect("reparse").expect("command");
+    assert_eq!(c1, c2, "round-trip failed; printed = {printed:?}");
+}
+
+fn lowered(src: &str) -> String {
+    let t = lower(&parse_expr(src).expect("parse")).expect("lower");
+    print_expr(&to_surface(&t).expect("to_surface"))
+}
+
+fn simp_str(src: &str) -> String {
+    let t = lower(&parse_expr(src).expect("parse")).expect("lower");
+    let t = simplify(&t, &Theory::new());
+    print_expr(&to_surface(&t).expect("to_surface"))
+}
+
+// ── Parsing ──────────────────────────────────────────────────────────────────
+
+#[test]
+fn local_let_parses() {
+    let e = parse_expr("let r = a · a + b · b in sqrt(r)").expect("parse");
+    match &e {
+        Expr::Let(name, ty, value, body) => {
+            assert_eq!(name, "r");
+            assert!(ty.is_none());
+            assert_eq!(print_expr(value), "a · a + b · b");
+            assert_eq!(**body, Expr::App("sqrt".into(), vec![Expr::Ident("r".into())]));
+        }
+        _ => panic!("expected Let, got {e:?}"),
+    }
+}
+
+#[test]
+fn local_let_with_annotation_parses() {
+    let e = parse_expr("let h : ℚ = 1 / 2 in h + h").expect("parse");
+    match &e {
+        Expr::Let(_, Some(ty), _, _) => assert_eq!(**ty, Expr::Ident("ℚ".into())),
+        _ => panic!("expected annotated Let, got {e:?}"),
+    }
+}
+
+#[test]
+fn local_let_body_extends_rightward() {
+    let e = parse_expr("let x = 1 in x + y = z").expect("parse");
+    match &e {
+        Expr::Let(_, _, _, body) => assert_eq!(print_expr(body), "x + y = z"),
+        _ => panic!("expected Let, got {e:?}"),
+    }
+}
+
+#[test]
+fn local_let_missing_in_is_error() {
+    assert!(parse_expr("let x = 1").is_err());
+}
+
+// ── Printing round-trips ─────────────────────────────────────────────────────
+
+#[test]
+fn local_let_round_trips() {
+    rt_expr("let r = a · a + b · b in sqrt(r)");
+    rt_expr("let x = a + b in let y = c + d in x · y");
+    rt_expr("let h : ℚ = 1 / 2 in h + h");
+    rt_expr("a + (let x = 1 in x)");
+    rt_expr("(let x = 1 in x) + a");
+    rt_expr("-(let x = 1 in x)");
+    rt_expr("let x = (let y = 1 in y) in x");
+}
+
+#[test]
+fn local_let_inside_commands_round_trips() {
+    rt_cmd("simplify let x = a + 0 in x · x");
+    rt_cmd("evaluate let x = 3 in x ^ 2");
+    rt_cmd("fact f(x) = let y = x + 1 in y · y");
+}
+
+#[test]
+fn nested_binder_printing_parenthesizes_operand() {
+    let e = parse_expr("(let x = 1 in x) + 2").unwrap();
+    assert_eq!(print_expr(&e), "(let x = 1 in x) + 2");
+}
+
+// ── Lowering ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn local_let_lowers_by_substitution() {
+    assert_eq!(lowered("let r = a + b in r · r"), "(a + b) · (a + b)");
+}
+
+#[test]
+fn inner_local_let_shadows_outer() {
+    assert_eq!(lowered("let x = 1 in let x = 2 in x"), "2");
+    assert_eq!(lowered("let x = a in let y = x + 1 in y · x"), "(a + 1) · a");
+}
+
+#[test]
+fn local_let_feeds_simplify() {
+    assert_eq!(simp_str("let x = 2 + 3 in x · x"), "25");
+}
+
+#[test]
+fn local_let_does_not_leak_as_command() {
+    // A top-level `let` command is still a global binding, not an expression.
+    let cmd = parse_command("let x = 1").unwrap().unwrap();
+    assert!(matches!(cmd, Command::Let(_, None, Some(_))));
+}
@@ -0,0 +1,176 @@
+use num_bigint::BigUint;
+use regler::ast::Expr;
+use regler::kernel::eval::evaluate;
+use regler::kernel::lower::lower;
+use regler::kernel::modular::{element, quotient_modulus, set_modulus};
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::simplify;
+use regler::kernel::term::Term;
+use regler::kernel::theory::Theory;
+use regler::parser::parse_expr;
+use regler::printer::print_expr;
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn eval_str(src: &str) -> String {
+    show(&evaluate(&lower_str(src)).expect("evaluate"))
+}
+
+fn eval_err(src: &str) -> String {
+    evaluate(&lower_str(src)).expect_err(src).0
+}
+
+fn rt_expr(src: &str) {
+    let e1 = parse_expr(src).expect("first parse");
+    let printed = print_expr(&e1);
+    let e2 = parse_expr(&printed).expect("reparse");
+    assert_eq!(e1, e2, "round-trip failed; printed = {printed:?}");
+}
+
+/// A theory where `+` and `·` are AC with identities.
+fn ring() -> Theory {
+    let mut theory = Theory::new();
+    for fact in [
+        "x + 0 = x",
+        "x + y = y + x",
+        "(x + y) + z = x + (y + z)",
+        "x · 1 = x",
+        "x · y = y · x",
+        "(x · y) · z = x · (y · z)",
+    ] {
+        theory.install_fact(&lower_str(fact), None, None);
+    }
+    theory
+}
+
+#[test]
+fn residue_literals_are_canonical() {
+    assert_eq!(
+        parse_expr("3 (mod 7)").expect("parse"),
+        Expr::Residue(3.into(), 7.into())
+    );
+    assert_eq!(eval_str("10 (mod 7)"), "3 (mod 7)");
+    assert_eq!(eval_str("-1 (mod 7)"), "6 (mod 7)");
+    assert_eq!(lower_str("10 (mod 7)"), lower_str("3 (mod 7)"));
+    assert!(lower(&parse_expr("3 (mod 0)").expect("parse")).is_err());
+}
+
+#[test]
+fn arithmetic_folds_modulo_n() {
+    assert_eq!(eval_str("3 (mod 7) + 5"), "1 (mod 7)");
+    assert_eq!(eval_str("3 (mod 7) · 4 (mod 7)"), "5 (mod 7)");
+    assert_eq!(eval_str("2 - 5 (mod 7)"), "4 (mod 7)");
+    assert_eq!(eval_str("-(3 (mod 7))"), "4 (mod 7)");
+    assert_eq!(eval_str("3 (mod 7) ^ 6"), "1 (mod 7)");
+    assert_eq!(
+        eval_str("2 (mod 1000000007) ^ 1000000006"),
+        "1 (mod 1000000007)"
+    );
+}
+
+#[test]
+fn inverses() {
+    assert_eq!(eval_str("3 (mod 7) ^ -1"), "5 (mod 7)");
+    assert_eq!(eval_str("1 / 3 (mod 7)"), "5 (mod 7)");
+    assert_eq!(eval_str("3 (mod 7) / 5"), "2 (mod 7)");
+    // Rationals are read modulo n.
+    assert_eq!(eval_str("1 / 2 + 0 (mod 7)"), "4 (mod 7)");
+    assert_eq!(eval_err("3 (mod 6) ^ -1"), "3 has no inverse modulo 6");
+    assert_eq!(eval_err("1 / 0 (mod 7)"), "0 has no inverse modulo 7");
+}
+
+#[test]
+fn moduli_do_not_mix() {
+    assert_eq!(
+        eval_err("(3 (mod 7)) + (1 (mod 5))"),
+        "residues modulo 7 and 5 do not combine"
+    );
+    let theory = Theory::new();
+    assert_eq!(
+        show(&simplify(&lower_str("3 (mod 7) + 1 (mod 5)"), &theory)),
+        "(3 (mod 7)) + (1 (mod 5))"
+    );
+}
+
+#[test]
+fn equality_respects_the_modulus() {
+    assert_eq!(eval_str("3 (mod 7) = 10"), "True");
+    assert_eq!(eval_str("3 (mod 7) = 10 (mod 7)"), "True");
+    assert_eq!(eval_str("3 (mod 7) ≠ 4"), "True");
+    // Residues have no order.
+    assert_eq!(eval_str("3 (mod 7) < 4"), "3 (mod 7) < 4");
+    let theory = Theory::new();
+    assert_eq!(
+        show(&simplify(&lower_str("3 (mod 7) + 4 = 0"), &theory)),
+        "True"
+    );
+}
+
+#[test]
+fn ac_normalization_folds_residues() {
+    let theory = ring();
+    let simp = |s: &str| show(&simplify(&lower_str(s), &theory));
+    assert_eq!(simp("y + 3 (mod 7) + 5 + x"), "x + y + (1 (mod 7))");
+    assert_eq!(simp("y · 3 (mod 7) · 5 · 3"), "y · (3 (mod 7))");
+    assert_eq!(simp("x + 4 (mod 7) + 3"), "x + (0 (mod 7))");
+    // No polynomial normal form over ℤ/nℤ: the AC form is kept.
+    assert_eq!(simp("(y + 3 (mod 7)) ^ 2"), "(y + (3 (mod 7))) ^ 2");
+}
+
+#[test]
+fn residue_sets() {
+    let z7 = lower_str("ℤ/7ℤ");
+    assert_eq!(set_modulus(&z7), Some(BigUint::from(7u32)));
+    assert_eq!(set_modulus(&lower_str("ℤ / 7")), None);
+    assert_eq!(show(&z7), "ℤ / 7ℤ");
+    assert_eq!(
+        show(&element(&lower_str("1 / 2"), &BigUint::from(7u32)).expect("element")),
+        "4 (mod 7)"
+    );
+    assert!(element(&lower_str("x"), &BigUint::from(7u32)).is_err());
+
+    let theory = Theory::new();
+    let simp = |s: &str| show(&simplify(&lower_str(s), &theory));
+    assert_eq!(simp("3 (mod 7) ∈ ℤ/7ℤ"), "True");
+    assert_eq!(simp("3 (mod 5) ∈ ℤ/7ℤ"), "False");
+}
+
+#[test]
+fn moduli_must_exceed_one() {
+    assert_eq!(
+        quotient_modulus(&lower_str("ℤ/7ℤ")).map(Result::ok),
+        Some(Some(BigUint::from(7u32)))
+    );
+    // `xℤ` is a single name, so `ℤ/xℤ` has no literal modulus.
+    for src in ["ℤ/0ℤ", "ℤ/1ℤ", "ℤ / (-3 · ℤ)", "ℤ / (x · ℤ)", "ℤ/xℤ", "ℤ / 7"] {
+        let err = quotient_modulus(&lower_str(src)).expect(src).expect_err(src);
+        assert_eq!(
+            err.0,
+            "the modulus of ℤ/nℤ must be an integer literal greater than 1"
+        );
+        assert_eq!(set_modulus(&lower_str(src)), None);
+    }
+    assert!(quotient_modulus(&lower_str("ℚ / 7")).is_none());
+}
+
+#[test]
+fn residues_round_trip() {
+    for src in [
+        "3 (mod 7)",
+        "-3 (mod 7)",
+        "-(3 (mod 7))",
+        "3 (mod 7) ^ 2 + x",
+        "ℤ/7ℤ",
+        "ℤ / (7 · ℤ)",
+        "2 · 7ℤ",
+        "-7ℤ",
+    ] {
+        rt_expr(src);
+    }
+}
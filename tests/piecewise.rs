@@ -0,0 +1,168 @@
+use regler::ast::Expr;
+use regler::kernel::cases::simplify_by_cases;
+use regler::kernel::eval::evaluate;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::{simplify, simplify_assuming};
+use regler::kernel::term::Term;
+use regler::kernel::theory::{FactEffect, Theory};
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr, print_expr_ascii};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn simp(src: &str, theory: &Theory) -> String {
+    show(&simplify(&lower_str(src), theory))
+}
+
+fn abs_theory() -> Theory {
+    let mut theory = Theory::new();
+    theory.install_fact(&lower_str("abs(x) = if x ≥ 0 then x else -x"), None, None);
+    theory
+}
+
+#[test]
+fn if_parses_at_binder_level() {
+    let e = parse_expr("if x > 0 then x + 1 else y · 2").unwrap();
+    assert!(matches!(e, Expr::If(..)));
+    assert_eq!(print_expr(&e), "if x > 0 then x + 1 else y · 2");
+    // The else branch extends rightward; in operand position it needs brackets.
+    let e = parse_expr("(if p then 1 else 2) + 3").unwrap();
+    assert!(matches!(e, Expr::BinOp(..)));
+    assert_eq!(print_expr(&e), "(if p then 1 else 2) + 3");
+    assert!(parse_expr("if p then 1").is_err());
+    assert!(parse_expr("if p else 1").is_err());
+}
+
+#[test]
+fn if_round_trips_including_ascii() {
+    for src in [
+        "if a ≤ b then a else b",
+        "if ¬p then (if q then 1 else 2) else 3",
+    ] {
+        let e = parse_expr(src).unwrap();
+        assert_eq!(parse_expr(&print_expr(&e)).unwrap(), e);
+        assert_eq!(parse_expr(&print_expr_ascii(&e)).unwrap(), e);
+    }
+    assert_eq!(
+        show(&lower_str("if x = 0 then 1 else 2")),
+        "if x = 0 then 1 else 2"
+    );
+}
+
+#[test]
+fn fact_side_condition_follows_a_piecewise_rhs() {
+    let cmd = parse_command("fact f(x) = if x > 0 then 1 else 2 if x ≠ 5")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        print_command(&cmd),
+        "fact f(x) = (if x > 0 then 1 else 2) if x ≠ 5"
+    );
+}
+
+#[test]
+fn decided_conditions_select_a_branch() {
+    let theory = Theory::new();
+    assert_eq!(simp("if 1 < 2 then a else b", &theory), "a");
+    assert_eq!(simp("if 2 + 2 = 5 then a else b", &theory), "b");
+    assert_eq!(simp("if p then a else a", &theory), "a");
+    assert_eq!(simp("if p then True else q", &theory), "p ∨ q");
+}
+
+#[test]
+fn branches_are_simplified_under_the_condition() {
+    let theory = Theory::new();
+    assert_eq!(
+        simp("if y > 0 then (if y > 0 then a else b) else c", &theory),
+        "if y > 0 then a else c"
+    );
+    assert_eq!(
+        simp("if y > 2 then (if y = 0 then a else b) else c", &theory),
+        "if y > 2 then b else c"
+    );
+    assert_eq!(
+        simp("if y < 0 then c else (if y ≥ 0 then a else b)", &theory),
+        "if y < 0 then c else a"
+    );
+    let out = simplify_assuming(
+        &lower_str("if y > 0 then a else b"),
+        &lower_str("y = 3"),
+        &theory,
+    );
+    assert_eq!(show(&out), "a");
+}
+
+#[test]
+fn arithmetic_moves_into_branches_when_smaller() {
+    let theory = Theory::new();
+    assert_eq!(
+        simp("(if y > 0 then 1 else 2) + 3", &theory),
+        "if y > 0 then 4 else 5"
+    );
+    assert_eq!(
+        simp("2 · (if y > 0 then 1 else 2)", &theory),
+        "if y > 0 then 2 else 4"
+    );
+    assert_eq!(
+        simp("-(if y > 0 then 1 else 2)", &theory),
+        "if y > 0 then -1 else -2"
+    );
+    // Duplicating `z` would make the term heavier.
+    assert_eq!(
+        simp("(if y > 0 then y else 1) + z", &theory),
+        "(if y > 0 then y else 1) + z"
+    );
+}
+
+#[test]
+fn piecewise_fact_installs_a_rule_per_branch() {
+    let mut theory = Theory::new();
+    let effects = theory.install_fact(&lower_str("abs(x) = if x ≥ 0 then x else -x"), None, None);
+    assert!(matches!(
+        effects.as_slice(),
+        [FactEffect::RuleInstalled, FactEffect::RuleInstalled]
+    ));
+    assert_eq!(theory.rules[0].condition, Some(lower_str("x ≥ 0")));
+    assert_eq!(theory.rules[1].condition, Some(lower_str("x < 0")));
+}
+
+#[test]
+fn piecewise_definition_simplifies() {
+    let theory = abs_theory();
+    assert_eq!(simp("abs(2 - 5)", &theory), "3");
+    assert_eq!(simp("abs(4)", &theory), "4");
+    assert_eq!(simp("abs(y)", &theory), "abs(y)");
+    let cases = simplify_by_cases(&lower_str("abs(y)"), None, &theory);
+    assert_eq!(show(&cases), "if y ≥ 0 then y else -y");
+    let out = simplify_assuming(&lower_str("abs(y) + 1"), &lower_str("y > 2"), &theory);
+    assert_eq!(show(&out), "y + 1");
+}
+
+#[test]
+fn named_piecewise_fact_keeps_its_written_form() {
+    let mut theory = Theory::new();
+    theory.install_fact(
+        &lower_str("abs(x) = if x ≥ 0 then x else -x"),
+        Some("abs_def".into()),
+        None,
+    );
+    let nf = &theory.named["abs_def"];
+    assert_eq!(show(&nf.rhs), "if x ≥ 0 then x else -x");
+    assert_eq!(theory.rules.len(), 2);
+}
+
+#[test]
+fn evaluate_only_evaluates_the_selected_branch() {
+    let out = evaluate(&lower_str("if 0 = 0 then 0 else 1 / 0")).unwrap();
+    assert_eq!(show(&out), "0");
+    let out = evaluate(&lower_str("if 1 > 2 then 1 / 0 else 2 + 3")).unwrap();
+    assert_eq!(show(&out), "5");
+    assert!(evaluate(&lower_str("if 0 = 0 then 1 / 0 else 0")).is_err());
+}
@@ -0,0 +1,125 @@
+use num_rational::BigRational;
+use regler::ast::Command;
+use regler::kernel::lower::lower;
+use regler::kernel::poly::{expand, Poly};
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::{normalize, simplify};
+use regler::kernel::term::Term;
+use regler::kernel::theory::Theory;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn expand_str(src: &str) -> String {
+    show(&expand(&lower_str(src)))
+}
+
+fn theory_from_facts(facts: &[&str]) -> Theory {
+    let mut theory = Theory::new();
+    for f in facts {
+        theory.install_fact(&lower_str(f), None, None);
+    }
+    theory
+}
+
+const RING: &[&str] = &[
+    "a + b = b + a",
+    "(a + b) + c = a + (b + c)",
+    "x + 0 = x",
+    "a · b = b · a",
+    "(a · b) · c = a · (b · c)",
+    "x · 1 = x",
+];
+
+fn simp(src: &str, theory: &Theory) -> String {
+    show(&simplify(&lower_str(src), theory))
+}
+
+#[test]
+fn equal_polynomials_have_equal_representations() {
+    let p = Poly::from_term(&lower_str("(a + b) · (a - b)"));
+    let q = Poly::from_term(&lower_str("a^2 - b^2 + 0 · c"));
+    assert_eq!(p, q);
+    assert_eq!(p.total_degree(), 2);
+    let zero = Poly::from_term(&lower_str("a · a - a^2"));
+    assert!(zero.is_zero());
+    assert_eq!(zero.as_constant(), Some(BigRational::from_integer(0.into())));
+}
+
+#[test]
+fn normal_form_orders_by_degree_then_variables() {
+    assert_eq!(expand_str("(a + b)^2"), "a ^ 2 + a · b · 2 + b ^ 2");
+    assert_eq!(expand_str("(x - 1)^3"), "x ^ 3 - x ^ 2 · 3 + x · 3 - 1");
+    assert_eq!(expand_str("1 - a"), "-a + 1");
+    assert_eq!(expand_str("b - a · 2"), "-(a · 2) + b");
+    assert_eq!(expand_str("x / 2 + x / 2"), "x");
+}
+
+#[test]
+fn other_subterms_are_indeterminates() {
+    assert_eq!(expand_str("log(x) · 2 + 3 · log(x)"), "log(x) · 5");
+    assert_eq!(expand_str("f((x + 1)^2) - g(y)"), "f(x ^ 2 + x · 2 + 1) - g(y)");
+    assert_eq!(expand_str("x / y · 2"), "x / y · 2");
+    assert_eq!(expand_str("x ^ n · x ^ n"), "(x ^ n) ^ 2");
+}
+
+#[test]
+fn large_powers_of_sums_stay_folded() {
+    assert_eq!(expand_str("(a + b)^100 - (a + b)^100"), "0");
+    assert_eq!(expand_str("x^1000 · x"), "x ^ 1001");
+}
+
+#[test]
+fn expansion_is_idempotent() {
+    for src in ["(a + b) · (a - b) · c", "(1/2) · a - a", "-(x - y)^2 + f(z)"] {
+        let once = expand(&lower_str(src));
+        assert_eq!(expand(&once), once);
+        assert_eq!(expand(&lower_str(&show(&once))), once);
+    }
+}
+
+#[test]
+fn simplify_normalizes_ring_terms_in_a_ring_theory() {
+    let theory = theory_from_facts(RING);
+    assert!(theory.is_ring());
+    assert_eq!(simp("(a + b) · (a - b)", &theory), "a ^ 2 - b ^ 2");
+    assert_eq!(simp("a · a - a^2", &theory), "0");
+    assert_eq!(simp("(2 + a) + 3", &theory), "a + 5");
+    assert_eq!(simp("(a + b)^2 = a^2 + 2·a·b + b^2", &theory), "True");
+}
+
+#[test]
+fn simplify_leaves_ring_terms_without_the_marks() {
+    // `·` is not AC here, so the ring laws are not available.
+    let theory = theory_from_facts(&RING[..3]);
+    assert!(!theory.is_ring());
+    assert_eq!(simp("(a + b) · (a - b)", &theory), "(a + b) · (a - b)");
+    assert_eq!(simp("a · a - a^2", &Theory::new()), "a · a - a ^ 2");
+}
+
+#[test]
+fn normalize_uses_the_ring_laws_regardless() {
+    let mut theory = Theory::new();
+    theory.install_fact(&lower_str("f(x, y) = x · y"), None, None);
+    assert_eq!(show(&normalize(&lower_str("f(a + 1, a - 1)"), &theory)), "a ^ 2 - 1");
+    let identity = lower_str("(a - b) · (a^2 + a·b + b^2) = a^3 - b^3");
+    assert_eq!(show(&normalize(&identity, &Theory::new())), "True");
+}
+
+#[test]
+fn expand_and_normalize_commands_round_trip() {
+    for src in ["expand (a + b) ^ 2", "normalize a · (b + c) = a · b + a · c"] {
+        let cmd = parse_command(src).unwrap().unwrap();
+        assert!(matches!(cmd, Command::Expand(_) | Command::Normalize(_)));
+        assert_eq!(print_command(&cmd), src);
+    }
+    // `expand` is a command word only at the start of a command.
+    assert!(parse_expr("expand + 1").is_ok());
+}
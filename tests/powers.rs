@@ -0,0 +1,96 @@
+use regler::kernel::eval::evaluate;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::simplify;
+use regler::kernel::term::Term;
+use regler::kernel::theory::Theory;
+use regler::parser::parse_expr;
+use regler::printer::print_expr;
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn eval_str(src: &str) -> String {
+    show(&evaluate(&lower_str(src)).expect("evaluate"))
+}
+
+fn simplify_str(src: &str) -> String {
+    show(&simplify(&lower_str(src), &Theory::new()))
+}
+
+#[test]
+fn negative_exponents_invert() {
+    assert_eq!(eval_str("2 ^ -1"), "1 / 2");
+    assert_eq!(eval_str("(2 / 3) ^ -2"), "9 / 4");
+    assert_eq!(eval_str("(-2) ^ -3"), "-1 / 8");
+    assert_eq!(simplify_str("x · 2 ^ -2"), "x · (1 / 4)");
+}
+
+#[test]
+fn rational_and_integer_bases_fold() {
+    assert_eq!(eval_str("(1 / 2) ^ 3"), "1 / 8");
+    assert_eq!(eval_str("(-3) ^ 3"), "-27");
+    assert_eq!(simplify_str("(1 / 2) ^ 3"), "1 / 8");
+    assert_eq!(simplify_str("(-1) ^ 4"), "1");
+}
+
+#[test]
+fn exact_roots_come_out_rational() {
+    assert_eq!(eval_str("4 ^ (1 / 2)"), "2");
+    assert_eq!(eval_str("8 ^ (2 / 3)"), "4");
+    assert_eq!(eval_str("(9 / 4) ^ (-1 / 2)"), "2 / 3");
+    assert_eq!(eval_str("(-8) ^ (1 / 3)"), "-2");
+    assert_eq!(simplify_str("27 ^ (4 / 3)"), "81");
+}
+
+#[test]
+fn inexact_roots_become_normalized_radicals() {
+    assert_eq!(eval_str("8 ^ (1 / 2)"), "2 · sqrt(2)");
+    assert_eq!(eval_str("72 ^ (1 / 2)"), "6 · sqrt(2)");
+    assert_eq!(eval_str("4 ^ (1 / 6)"), "2 ^ (1 / 3)");
+    assert_eq!(eval_str("2 ^ (5 / 2)"), "4 · sqrt(2)");
+    // Square roots are written `sqrt`; denominators are rationalized.
+    assert_eq!(eval_str("(1 / 2) ^ (1 / 2)"), "1 / 2 · sqrt(2)");
+    assert_eq!(eval_str("(-2) ^ (1 / 3)"), "-(2 ^ (1 / 3))");
+    // Radicals are fixed points.
+    assert_eq!(eval_str("3 ^ (1 / 3)"), "3 ^ (1 / 3)");
+    assert_eq!(eval_str("3 ^ (1 / 2)"), "sqrt(3)");
+}
+
+#[test]
+fn even_roots_of_negatives_stay_unevaluated() {
+    assert_eq!(eval_str("(-4) ^ (1 / 2)"), "(-4) ^ (1 / 2)");
+    assert_eq!(simplify_str("(-1) ^ (1 / 2)"), "(-1) ^ (1 / 2)");
+}
+
+#[test]
+fn power_laws_combine_radicals() {
+    assert_eq!(eval_str("2 ^ (1 / 2) · 2 ^ (1 / 2)"), "2");
+    assert_eq!(eval_str("(2 ^ (1 / 2)) ^ 2"), "2");
+    assert_eq!(eval_str("2 ^ (1 / 2) · 3 ^ (1 / 2)"), "sqrt(6)");
+    assert_eq!(simplify_str("2 ^ (1 / 3) · 2 ^ (2 / 3)"), "2");
+    assert_eq!(simplify_str("8 ^ (1 / 2) + 2 ^ -2"), "1 / 4 + 2 · sqrt(2)");
+}
+
+#[test]
+fn zero_to_a_negative_power_is_an_error() {
+    let err = evaluate(&lower_str("0 ^ -1")).expect_err("0 ^ -1");
+    assert!(err.0.contains("division by zero"), "{}", err.0);
+    assert!(evaluate(&lower_str("0 ^ (-1 / 2)")).is_err());
+    assert_eq!(eval_str("0 ^ 0"), "1");
+    // `simplify` leaves it alone rather than failing.
+    assert_eq!(simplify_str("0 ^ -1"), "0 ^ -1");
+}
+
+#[test]
+fn huge_exponents() {
+    assert_eq!(eval_str("1 ^ (10 ^ 30)"), "1");
+    assert_eq!(eval_str("(-1) ^ (10 ^ 30 + 1)"), "-1");
+    assert!(evaluate(&lower_str("2 ^ (10 ^ 30)")).is_err());
+    assert_eq!(eval_str("2 ^ (1 / 100000)"), "2 ^ (1 / 100000)");
+}
@@ -0,0 +1,220 @@
+use regler::ast::Command;
+use regler::kernel::lower::lower;
+use regler::kernel::poly::Poly;
+use regler::kernel::print::to_surface;
+use regler::kernel::ratfun::{cancel, together, RatFun};
+use regler::kernel::rewrite::{simplify, simplify_assuming};
+use regler::kernel::term::Term;
+use regler::kernel::theory::{FactEffect, Theory};
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn poly(src: &str) -> Poly {
+    Poly::from_term(&lower_str(src))
+}
+
+fn together_str(src: &str) -> String {
+    show(&together(&lower_str(src)))
+}
+
+fn cancel_str(src: &str) -> (String, Vec<String>) {
+    let (t, conditions) = cancel(&lower_str(src)).expect("cancel");
+    (show(&t), conditions.iter().map(show).collect())
+}
+
+const FIELD: &[&str] = &[
+    "a + b = b + a",
+    "(a + b) + c = a + (b + c)",
+    "x + 0 = x",
+    "a · b = b · a",
+    "(a · b) · c = a · (b · c)",
+    "x · 1 = x",
+    "x / x = 1 if x ≠ 0",
+];
+
+fn theory_from_facts(facts: &[&str]) -> Theory {
+    let mut theory = Theory::new();
+    for f in facts {
+        let (prop, cond) = match f.split_once(" if ") {
+            Some((p, c)) => (p, Some(lower_str(c))),
+            None => (*f, None),
+        };
+        theory.install_fact(&lower_str(prop), None, cond.as_ref());
+    }
+    theory
+}
+
+#[test]
+fn gcd_of_multivariate_polynomials() {
+    let g = poly("(x + y)^2 · (x - 1)").gcd(&poly("(x + y) · (x - 1)^3 · y"));
+    assert_eq!(show(&g.to_term()), "x ^ 2 + x · y - x - y");
+    assert_eq!(poly("x^2 - y^2").gcd(&poly("x + 2")), poly("1"));
+    assert_eq!(poly("x · 6").gcd(&poly("x^2 · 4")), poly("x"));
+    assert_eq!(poly("0").gcd(&poly("y · 3 + 3")), poly("y + 1"));
+}
+
+#[test]
+fn exact_division_fails_on_a_remainder() {
+    let q = poly("x^3 - y^3")
+        .div_exact(&poly("x - y"))
+        .expect("divides");
+    assert_eq!(q, poly("x^2 + x·y + y^2"));
+    assert!(poly("x^2 + 1").div_exact(&poly("x - 1")).is_none());
+    assert!(poly("x").div_exact(&poly("0")).is_none());
+}
+
+#[test]
+fn together_uses_the_least_common_denominator() {
+    assert_eq!(together_str("x / y + 1 / y"), "(x + 1) / y");
+    assert_eq!(together_str("1 / x + 1 / y"), "(x + y) / (x · y)");
+    assert_eq!(
+        together_str("a / (x · y) + b / (x · z)"),
+        "(a · z + b · y) / (x · y · z)"
+    );
+    assert_eq!(together_str("x^-2 + 1"), "(x ^ 2 + 1) / x ^ 2");
+    assert_eq!(together_str("x / 2 + x / 3"), together_str("x · 5 / 6"));
+}
+
+#[test]
+fn together_keeps_every_denominator() {
+    // `1 / (1 / x)` is undefined at `x = 0`, so `x` stays in the denominator.
+    assert_eq!(together_str("1 / (1 / x)"), "x ^ 2 / x");
+    assert_eq!(together_str("x / 0"), "x / 0");
+}
+
+#[test]
+fn zero_numerators_keep_their_domain() {
+    // `together` does not extend the domain, so the denominator stays …
+    assert_eq!(together_str("0 / x"), "0 / x");
+    assert_eq!(together_str("1 / x - 1 / x"), "0 / x");
+    assert_eq!(together_str("1 / 2 - 1 / 2"), "0");
+    // … and `cancel` removes it under a side condition.
+    assert_eq!(
+        cancel_str("1 / x - 1 / x"),
+        ("0".to_string(), vec!["x ≠ 0".to_string()])
+    );
+}
+
+#[test]
+fn cancel_rejects_division_by_zero() {
+    for src in ["(x ^ 2 - 1) / (x - 1) / 0", "x / (y - y)", "f(1 / 0)"] {
+        assert_eq!(cancel(&lower_str(src)).expect_err(src).0, "division by zero");
+    }
+}
+
+#[test]
+fn normal_form_has_coprime_integer_coefficients() {
+    let q = RatFun::from_term(&lower_str("(x / 2) / (1/3 - x / 4)"));
+    assert_eq!(show(&q.to_term()), "-(x · 6) / (x · 3 - 4)");
+    assert_eq!(q.numerator(), &poly("-6 · x"));
+    assert_eq!(q.denominator(), &poly("3 · x - 4"));
+}
+
+#[test]
+fn cancel_reports_the_denominator_condition() {
+    assert_eq!(
+        cancel_str("(x^2 - 1) / (x - 1)"),
+        ("x + 1".into(), vec!["x ≠ 1".into()])
+    );
+    assert_eq!(
+        cancel_str("(x^2 - y^2) / (x^2 - 2·x·y + y^2)"),
+        (
+            "(x + y) / (x - y)".into(),
+            vec!["x ^ 2 - x · y · 2 + y ^ 2 ≠ 0".into()]
+        )
+    );
+    assert_eq!(
+        cancel_str("x / (x · y)"),
+        ("1 / y".into(), vec!["x · y ≠ 0".into()])
+    );
+    assert_eq!(
+        cancel_str("1 / (1 / x)"),
+        ("x".into(), vec!["x ≠ 0".into()])
+    );
+}
+
+#[test]
+fn cancel_without_common_factors_has_no_condition() {
+    assert_eq!(
+        cancel_str("(x + 1) / (x - 1)"),
+        ("(x + 1) / (x - 1)".into(), vec![])
+    );
+    assert_eq!(cancel_str("(x · 4) / 6"), ("x · (2 / 3)".into(), vec![]));
+    let (t, conditions) = cancel_str("f((y^2 - 4) / (y + 2)) · 2");
+    assert_eq!(
+        (t.as_str(), conditions),
+        ("f(y - 2) · 2", vec!["y ≠ -2".to_string()])
+    );
+}
+
+#[test]
+fn simplify_cancels_only_under_a_proved_condition() {
+    let theory = theory_from_facts(FIELD);
+    assert!(theory.is_field());
+    let t = lower_str("(x^2 - 1) / (x - 1)");
+    assert_eq!(show(&simplify(&t, &theory)), "(x ^ 2 - 1) / (x - 1)");
+    assert_eq!(
+        show(&simplify_assuming(&t, &lower_str("x > 1"), &theory)),
+        "x + 1"
+    );
+    assert_eq!(
+        show(&simplify(&lower_str("1 / x - 1 / (x + 1)"), &theory)),
+        "1 / (x ^ 2 + x)"
+    );
+    assert_eq!(
+        show(&simplify(&lower_str("(x^2 - 4) / (3 - 1)"), &theory)),
+        "x ^ 2 · (1 / 2) - 2"
+    );
+}
+
+#[test]
+fn inverses_are_earned_from_the_division_fact() {
+    let ring = theory_from_facts(&FIELD[..6]);
+    assert!(ring.is_ring() && !ring.is_field());
+    assert_eq!(
+        show(&simplify(&lower_str("1 / x + 1 / x"), &ring)),
+        "1 / x · 2"
+    );
+    let mut theory = theory_from_facts(&FIELD[..6]);
+    let effects = theory.install_fact(&lower_str("x ≠ 0 ⇒ x / x = 1"), None, None);
+    assert!(matches!(
+        effects.as_slice(),
+        [FactEffect::Inverses, FactEffect::RuleInstalled]
+    ));
+    assert!(theory.is_field());
+    assert_eq!(
+        show(&simplify(&lower_str("1 / x + 1 / x"), &theory)),
+        "2 / x"
+    );
+    // Inverses alone do not make a field.
+    assert!(!theory_from_facts(&FIELD[6..]).is_field());
+}
+
+#[test]
+fn together_and_cancel_commands_round_trip() {
+    for src in ["together 1 / x + 1 / y", "cancel (x ^ 2 - 1) / (x - 1)"] {
+        let cmd = parse_command(src).unwrap().unwrap();
+        assert!(matches!(cmd, Command::Together(_) | Command::Cancel(_)));
+        assert_eq!(print_command(&cmd), src);
+    }
+    // Command words are keywords only at the start of a command.
+    assert!(parse_expr("cancel + 1").is_ok());
+    for src in [
+        "fact cancel : x / x = 1 if x ≠ 0",
+        "let together = 2",
+        "simplify together · cancel",
+        "check cancel",
+        "together cancel / x + 1",
+    ] {
+        let cmd = parse_command(src).unwrap().unwrap();
+        assert_eq!(parse_command(&print_command(&cmd)).unwrap().unwrap(), cmd);
+    }
+}
@@ -79,3 +79,17 @@ fn parens_only_when_needed() {
     let e = parse_expr("a + (b · c)").unwrap();
     assert_eq!(print_expr(&e), "a + b · c");
 }
+
+#[test]
+fn negative_bases_are_bracketed() {
+    for (src, printed) in [
+        ("(-4) ^ (1 / 2)", "(-4) ^ (1 / 2)"),
+        ("(-2.5) ^ 2", "(-2.5) ^ 2"),
+        ("(-x) ^ 2", "(-x) ^ 2"),
+        ("2 ^ -3", "2 ^ -3"),
+        ("-(4 ^ 2)", "-(4 ^ 2)"),
+    ] {
+        rt_expr(src);
+        assert_eq!(print_expr(&parse_expr(src).expect("parse")), printed);
+    }
+}
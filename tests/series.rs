@@ -0,0 +1,202 @@
+use num_rational::BigRational;
+use num_traits::Zero;
+use regler::ast::Command;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::series::{series, Series};
+use regler::kernel::term::{sym, Term};
+use regler::kernel::theory::Theory;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn rat(n: i64, d: i64) -> BigRational {
+    BigRational::new(n.into(), d.into())
+}
+
+fn expand_in(theory: &Theory, src: &str, point: i64, order: u32) -> Series {
+    series(&lower_str(src), &sym("x"), &rat(point, 1), order, theory).expect("series")
+}
+
+fn expand(src: &str, point: i64, order: u32) -> Series {
+    expand_in(&Theory::new(), src, point, order)
+}
+
+fn show(s: &Series) -> String {
+    print_expr(&to_surface(&s.to_term()).expect("to_surface"))
+}
+
+fn coefficients(s: &Series) -> Vec<BigRational> {
+    s.coefficients().to_vec()
+}
+
+#[test]
+fn elementary_functions() {
+    assert_eq!(
+        show(&expand("exp(x)", 0, 5)),
+        "x ^ 4 · (1 / 24) + x ^ 3 · (1 / 6) + x ^ 2 · (1 / 2) + x + 1 + O(x ^ 5)"
+    );
+    assert_eq!(
+        show(&expand("sin(x)", 0, 6)),
+        "x ^ 5 · (1 / 120) - x ^ 3 · (1 / 6) + x + O(x ^ 6)"
+    );
+    assert_eq!(
+        show(&expand("cos(x)", 0, 5)),
+        "x ^ 4 · (1 / 24) - x ^ 2 · (1 / 2) + 1 + O(x ^ 5)"
+    );
+    assert_eq!(
+        show(&expand("log(x)", 1, 4)),
+        "(x - 1) ^ 3 · (1 / 3) - (x - 1) ^ 2 · (1 / 2) + (x - 1) + O((x - 1) ^ 4)"
+    );
+    assert_eq!(
+        show(&expand("sqrt(x)", 4, 3)),
+        "-((x - 4) ^ 2 · (1 / 64)) + (x - 4) · (1 / 4) + 2 + O((x - 4) ^ 3)"
+    );
+}
+
+#[test]
+fn polynomials_are_rewritten_about_the_point() {
+    assert_eq!(
+        show(&expand("x ^ 3 - 2 · x", 2, 5)),
+        "(x - 2) ^ 3 + (x - 2) ^ 2 · 6 + (x - 2) · 10 + 4 + O((x - 2) ^ 5)"
+    );
+    assert_eq!(
+        show(&expand("1 / (1 + x)", -2, 3)),
+        "-((x + 2) ^ 2) - (x + 2) - 1 + O((x + 2) ^ 3)"
+    );
+    assert_eq!(show(&expand("x ^ 4", 0, 3)), "O(x ^ 3)");
+}
+
+#[test]
+fn arithmetic_on_series() {
+    let s = expand("sin(x)", 0, 8);
+    let c = expand("cos(x)", 0, 8);
+    assert_eq!(show(&(&(&s * &s) + &(&c * &c))), "1 + O(x ^ 8)");
+    let tan = s.div(&c).expect("quotient");
+    assert_eq!(tan, expand("sin(x) / cos(x)", 0, 8));
+    assert_eq!(
+        show(&tan),
+        "x ^ 7 · (17 / 315) + x ^ 5 · (2 / 15) + x ^ 3 · (1 / 3) + x + O(x ^ 8)"
+    );
+    // A sum of series is only as precise as the less precise one.
+    assert_eq!((&s + &expand("x", 0, 3)).order(), 3);
+    assert_eq!(expand("(1 - x) ^ -1", 0, 4), expand("1 / (1 - x)", 0, 4));
+}
+
+#[test]
+#[should_panic(expected = "do not combine")]
+fn series_about_different_points_do_not_add() {
+    let _ = &expand("x", 0, 3) + &expand("x", 1, 3);
+}
+
+#[test]
+#[should_panic(expected = "do not combine")]
+fn series_in_different_variables_do_not_multiply() {
+    let y = series(&lower_str("y"), &sym("y"), &rat(0, 1), 3, &Theory::new()).expect("series");
+    let _ = &expand("x", 0, 3) * &y;
+}
+
+#[test]
+fn quotients_cancel_common_powers() {
+    let q = expand("sin(x) / x", 0, 6);
+    assert_eq!(q.order(), 6, "expanded further first, so no order is lost");
+    assert_eq!(show(&q), "x ^ 4 · (1 / 120) - x ^ 2 · (1 / 6) + 1 + O(x ^ 6)");
+    // Dividing the series themselves lowers the order instead.
+    let direct = Series::div(&expand("sin(x)", 0, 6), &expand("x", 0, 6)).expect("quotient");
+    assert_eq!(direct.order(), 5);
+    assert!(Series::div(&expand("x", 0, 4), &expand("x ^ 2", 0, 4)).is_none());
+    assert!(expand("x", 0, 4).recip().is_none());
+}
+
+#[test]
+fn composition() {
+    let inner = expand("sin(x)", 0, 6);
+    let outer = expand("exp(x)", 0, 6);
+    assert_eq!(inner.compose(&outer), Some(expand("exp(sin(x))", 0, 6)));
+    assert_eq!(
+        show(&expand("exp(sin(x))", 0, 5)),
+        "-(x ^ 4 · (1 / 8)) + x ^ 2 · (1 / 2) + x + 1 + O(x ^ 5)"
+    );
+    // The outer series must be about the inner one's value.
+    assert!(expand("1 + x", 0, 4).compose(&outer).is_none());
+}
+
+#[test]
+fn reversion_inverts_functions() {
+    // arcsin(x) = x + x ^ 3 / 6 + 3 · x ^ 5 / 40 + …
+    let arcsin = expand("sin(x)", 0, 7).reversion().expect("invertible");
+    assert_eq!(
+        coefficients(&arcsin),
+        vec![
+            rat(0, 1),
+            rat(1, 1),
+            rat(0, 1),
+            rat(1, 6),
+            rat(0, 1),
+            rat(3, 40),
+            rat(0, 1)
+        ]
+    );
+    // The inverse of exp about 0 is log about 1.
+    assert_eq!(
+        expand("exp(x)", 0, 5).reversion(),
+        Some(expand("log(x)", 1, 5))
+    );
+    assert!(expand("x ^ 2", 0, 4).reversion().is_none());
+}
+
+#[test]
+fn taylor_series_from_the_theory() {
+    let mut theory = Theory::new();
+    for fact in ["D(f) = f", "f(0) = 1"] {
+        theory.install_fact(&lower_str(fact), None, None);
+    }
+    assert_eq!(expand_in(&theory, "f(x)", 0, 6), expand("exp(x)", 0, 6));
+    assert_eq!(
+        show(&expand_in(&theory, "f(2 · x)", 0, 4)),
+        "x ^ 3 · (4 / 3) + x ^ 2 · 2 + x · 2 + 1 + O(x ^ 4)"
+    );
+    let err = series(
+        &lower_str("g(x)"),
+        &sym("x"),
+        &BigRational::zero(),
+        3,
+        &theory,
+    )
+    .expect_err("unknown");
+    assert!(err.0.contains("`g`"), "{}", err.0);
+}
+
+#[test]
+fn irrational_coefficients_and_poles_are_errors() {
+    let theory = Theory::new();
+    let fails = |src: &str, point: i64| {
+        series(&lower_str(src), &sym("x"), &rat(point, 1), 4, &theory).is_err()
+    };
+    assert!(fails("1 / x", 0));
+    assert!(fails("exp(x)", 1));
+    assert!(fails("log(x)", 2));
+    assert!(fails("sqrt(x)", 0));
+    assert!(fails("sqrt(x)", 2));
+    assert!(fails("y · x", 0));
+    assert!(series(&lower_str("x"), &sym("x"), &BigRational::zero(), 0, &theory).is_err());
+}
+
+#[test]
+fn series_command_round_trips() {
+    let src = "series exp(x) / (1 - x) about x = 1 / 2 to order 4";
+    let cmd = parse_command(src).expect("parse").expect("command");
+    assert!(matches!(&cmd, Command::Series(_, x, _, 4) if x == "x"));
+    assert_eq!(print_command(&cmd), src);
+    assert!(parse_command("series exp(x) about x to order 4").is_err());
+    assert!(parse_command("series exp(x) about x = 0 to 4").is_err());
+    // `series` and `about` are not reserved outside the command.
+    let cmd = parse_command("series series(about) about x = 0 to order 2")
+        .expect("parse")
+        .expect("command");
+    assert!(matches!(&cmd, Command::Series(_, x, _, 2) if x == "x"));
+    assert!(parse_command("let about = series + 1").is_ok());
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use regler::ast::Command;
+use regler::kernel::lower::lower;
+use regler::kernel::print::to_surface;
+use regler::kernel::rewrite::normalize;
+use regler::kernel::solve::{solve, Solution};
+use regler::kernel::subst::subst;
+use regler::kernel::term::{as_truth, sym, Symbol, Term};
+use regler::kernel::theory::Theory;
+use regler::parser::{parse_command, parse_expr};
+use regler::printer::{print_command, print_expr};
+
+fn lower_str(src: &str) -> Term {
+    lower(&parse_expr(src).expect("parse")).expect("lower")
+}
+
+fn show(t: &Term) -> String {
+    print_expr(&to_surface(t).expect("to_surface"))
+}
+
+fn solve_strs(eqs: &[&str], unknowns: &[&str]) -> Solution {
+    let eqs: Vec<Term> = eqs.iter().map(|e| lower_str(e)).collect();
+    let unknowns: Vec<Symbol> = unknowns.iter().map(|x| sym(x)).collect();
+    solve(&eqs, &unknowns).expect("solve")
+}
+
+/// The values of `unknowns` in a unique solution, as printed.
+fn values(solution: &Solution, unknowns: &[&str]) -> Vec<String> {
+    match solution {
+        Solution::Unique(sigma) => unknowns.iter().map(|x| show(&sigma[&sym(x)])).collect(),
+        other => panic!("expected a unique solution, got {other:?}"),
+    }
+}
+
+/// Whether every equation normalizes to `True` under `sigma`.
+fn satisfies(eqs: &[&str], sigma: &HashMap<Symbol, Term>) -> bool {
+    let theory = Theory::new();
+    eqs.iter()
+        .all(|e| as_truth(&normalize(&subst(&lower_str(e), sigma), &theory)) == Some(true))
+}
+
+#[test]
+fn one_equation_in_one_unknown() {
+    assert_eq!(
+        values(&solve_strs(&["2 · x + 3 = 7"], &["x"]), &["x"]),
+        ["2"]
+    );
+    assert_eq!(
+        values(&solve_strs(&["3 · x = 1 - x"], &["x"]), &["x"]),
+        ["1 / 4"]
+    );
+    assert_eq!(values(&solve_strs(&["x / 2 = 5"], &["x"]), &["x"]), ["10"]);
+}
+
+#[test]
+fn systems_with_one_solution() {
+    let eqs = ["x + y = 3", "x - y = 1"];
+    let solution = solve_strs(&eqs, &["x", "y"]);
+    assert_eq!(values(&solution, &["x", "y"]), ["2", "1"]);
+    let eqs = ["x + y + z = 6", "x - y = 0", "2 · z = x + 4"];
+    let solution = solve_strs(&eqs, &["x", "y", "z"]);
+    assert_eq!(
+        values(&solution, &["x", "y", "z"]),
+        ["8 / 5", "8 / 5", "14 / 5"]
+    );
+    // More equations than unknowns, consistently.
+    let eqs = ["x + y = 3", "x - y = 1", "2 · x = 4"];
+    assert_eq!(
+        values(&solve_strs(&eqs, &["x", "y"]), &["x", "y"]),
+        ["2", "1"]
+    );
+}
+
+#[test]
+fn other_variables_are_parameters() {
+    let solution = solve_strs(&["x + a = 3"], &["x"]);
+    assert_eq!(values(&solution, &["x"]), ["-a + 3"]);
+    let solution = solve_strs(&["x + a = 0"], &["x"]);
+    assert_eq!(values(&solution, &["x"]), ["-a"]);
+    let solution = solve_strs(&["2 · x + a / 3 = b ^ 2"], &["x"]);
+    assert_eq!(values(&solution, &["x"]), ["b ^ 2 · (1 / 2) - a · (1 / 6)"]);
+    let solution = solve_strs(&["x + y = s", "x - y = d"], &["x", "y"]);
+    assert_eq!(
+        values(&solution, &["x", "y"]),
+        ["d · (1 / 2) + s · (1 / 2)", "-(d · (1 / 2)) + s · (1 / 2)"]
+    );
+}
+
+#[test]
+fn underdetermined_systems_are_parametric() {
+    let eqs = ["x + y + z = 6", "x - y = 0"];
+    match solve_strs(&eqs, &["x", "y", "z"]) {
+        Solution::Parametric(sigma, free) => {
+            assert_eq!(free, [sym("z")]);
+            assert_eq!(show(&sigma[&sym("x")]), "-(z · (1 / 2)) + 3");
+            assert!(!sigma.contains_key(&sym("z")));
+            assert!(satisfies(&eqs, &sigma));
+        }
+        other => panic!("expected a parametric solution, got {other:?}"),
+    }
+    match solve_strs(&["0 = 0"], &["x"]) {
+        Solution::Parametric(sigma, free) => {
+            assert!(sigma.is_empty());
+            assert_eq!(free, [sym("x")]);
+        }
+        other => panic!("expected a parametric solution, got {other:?}"),
+    }
+}
+
+#[test]
+fn inconsistent_systems() {
+    assert_eq!(
+        solve_strs(&["x + y = 1", "2 · x + 2 · y = 3"], &["x", "y"]),
+        Solution::Inconsistent
+    );
+    assert_eq!(solve_strs(&["x = x + 1"], &["x"]), Solution::Inconsistent);
+    // With parameters, consistency is a condition on them.
+    match solve_strs(&["x = a", "x = b"], &["x"]) {
+        Solution::Conditional(condition) => assert_eq!(show(&condition), "-a + b = 0"),
+        other => panic!("expected a condition, got {other:?}"),
+    }
+}
+
+#[test]
+fn repeated_unknowns_are_refused() {
+    let err = solve(&[lower_str("x + y = 1")], &[sym("x"), sym("x")]).expect_err("repeated");
+    assert_eq!(err.0, "the unknown `x` is listed twice");
+    assert!(solve(&[lower_str("x + y = 1")], &[sym("x"), sym("y"), sym("x")]).is_err());
+}
+
+#[test]
+fn solutions_substitute_back() {
+    let eqs = ["2 · x - y = 0", "x + 3 · y = 7"];
+    match solve_strs(&eqs, &["x", "y"]) {
+        Solution::Unique(sigma) => assert!(satisfies(&eqs, &sigma)),
+        other => panic!("expected a unique solution, got {other:?}"),
+    }
+    let eqs = ["x + 2 · y = a", "3 · x + 4 · y = b"];
+    match solve_strs(&eqs, &["x", "y"]) {
+        Solution::Unique(sigma) => assert!(satisfies(&eqs, &sigma)),
+        other => panic!("expected a unique solution, got {other:?}"),
+    }
+}
+
+#[test]
+fn nonlinear_equations_are_refused() {
+    let fails = |eq: &str| solve(&[lower_str(eq)], &[sym("x")]).is_err();
+    assert!(fails("x ^ 2 = 4"));
+    assert!(fails("a · x = 1"));
+    assert!(fails("log(x) = 1"));
+    assert!(fails("x · y = 1"));
+    assert!(fails("x + 1"));
+    assert!(solve(&[lower_str("x · y = 1")], &[sym("x"), sym("y")]).is_err());
+}
+
+#[test]
+fn solve_command_round_trips() {
+    for src in [
+        "solve 2 · x + 3 = 7 for x",
+        "solve {x + y = 3, x - y = 1} for x, y",
+    ] {
+        let cmd = parse_command(src).expect("parse").expect("command");
+        assert_eq!(print_command(&cmd), src);
+    }
+    assert!(matches!(
+        parse_command("solve {x = 1, y = 2} for x, y").expect("parse"),
+        Some(Command::Solve(eqs, unknowns)) if eqs.len() == 2 && unknowns == ["x", "y"]
+    ));
+    assert!(parse_command("solve x = 1").is_err());
+    assert!(parse_command("solve {x = 1 for x").is_err());
+    assert!(parse_command("let solve = 3").is_ok());
+}